@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::Path;
+
+use helix_db::helixc::parser::types::{Content, HxFile, Source};
+use helix_db::helixc::parser::HelixParser;
+
+use crate::codegen::{self, Lang};
+use crate::schema_graph::{self, GraphFormat};
+
+const USAGE: &str = "Usage:
+    helixql-lsp                                  Run the language server over stdio
+    helixql-lsp schema-graph [--format mermaid|dot|json] [--version N] [DIR]
+    helixql-lsp codegen [--lang ts|rust|python] [DIR]";
+
+/// Parse every .hx/.hql file in a directory into a single `Source`
+pub fn load_source(dir: &Path) -> std::result::Result<Source, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if let Some(ext) = path.extension() {
+            if ext == "hx" || ext == "hql" {
+                files.push(HxFile {
+                    name: path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    content: fs::read_to_string(&path)
+                        .map_err(|e| format!("{}: {}", path.display(), e))?,
+                });
+            }
+        }
+    }
+    if files.is_empty() {
+        return Err(format!("no .hx or .hql files found in {}", dir.display()));
+    }
+    parse_files(files)
+}
+
+/// Parse a set of files into a single `Source`
+pub fn parse_files(files: Vec<HxFile>) -> std::result::Result<Source, String> {
+    let content = Content {
+        content: String::new(),
+        files,
+        source: Source::default(),
+    };
+    HelixParser::parse_source(&content).map_err(|e| e.to_string())
+}
+
+/// Parse HQL text as a single file, for tests
+#[cfg(test)]
+pub fn parse_text(text: &str) -> Source {
+    parse_files(vec![HxFile {
+        name: "queries.hx".to_string(),
+        content: text.to_string(),
+    }])
+    .unwrap()
+}
+
+fn schema_graph(args: &[String]) -> std::result::Result<String, String> {
+    let mut format = GraphFormat::Mermaid;
+    let mut version = None;
+    let mut dir = ".".to_string();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" | "-f" => {
+                let name = args.next().ok_or("--format needs a value")?;
+                format =
+                    GraphFormat::parse(name).ok_or_else(|| format!("unknown format '{}'", name))?;
+            }
+            "--version" => {
+                let value = args.next().ok_or("--version needs a value")?;
+                version = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid schema version '{}'", value))?,
+                );
+            }
+            other => dir = other.to_string(),
+        }
+    }
+
+    let source = load_source(Path::new(&dir))?;
+    let graph = schema_graph::build(&source, version).ok_or("no schema found")?;
+    Ok(schema_graph::render(&graph, format))
+}
+
+fn codegen(args: &[String]) -> std::result::Result<String, String> {
+    let mut lang = Lang::Ts;
+    let mut dir = ".".to_string();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lang" | "-l" => {
+                let name = args.next().ok_or("--lang needs a value")?;
+                lang = Lang::parse(name).ok_or_else(|| format!("unknown language '{}'", name))?;
+            }
+            other => dir = other.to_string(),
+        }
+    }
+
+    let source = load_source(Path::new(&dir))?;
+    if source.queries.is_empty() {
+        return Err("no queries found".to_string());
+    }
+    Ok(codegen::generate(&source, lang))
+}
+
+/// Run a CLI subcommand. Returns `None` when the arguments don't name one,
+/// in which case the language server should start as usual.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let result = match command.as_str() {
+        "schema-graph" => schema_graph(rest),
+        "codegen" => codegen(rest),
+        "help" | "--help" | "-h" => Ok(USAGE.to_string()),
+        _ => return None,
+    };
+
+    match result {
+        Ok(output) => {
+            println!("{}", output.trim_end());
+            Some(0)
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            Some(1)
+        }
+    }
+}
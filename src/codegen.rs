@@ -0,0 +1,706 @@
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+use helix_db::helixc::parser::types::{FieldType, Parameter, Query, Source};
+
+use crate::shapes::{self, Shape};
+
+/// Command name for generating a client through `workspace/executeCommand`
+pub const COMMAND: &str = "helixql.codegen";
+
+/// Languages client code can be generated in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    Ts,
+    Rust,
+    Python,
+}
+
+impl Lang {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ts" | "typescript" => Some(Lang::Ts),
+            "rust" | "rs" => Some(Lang::Rust),
+            "python" | "py" => Some(Lang::Python),
+            _ => None,
+        }
+    }
+}
+
+/// Arguments of the `helixql.codegen` command
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodegenArgs {
+    pub text_document: TextDocumentIdentifier,
+    #[serde(default)]
+    pub lang: Lang,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodegenResult {
+    pub lang: Lang,
+    pub content: String,
+}
+
+/// HTTP path a query is served under
+pub fn endpoint(query: &Query) -> String {
+    format!("/{}", query.name)
+}
+
+/// Client code for every query in the project: a request type, a response type
+/// and a function posting to the query's endpoint
+pub fn generate(source: &Source, lang: Lang) -> String {
+    let mut out = match lang {
+        Lang::Ts => TS_PRELUDE,
+        Lang::Rust => RUST_PRELUDE,
+        Lang::Python => PYTHON_PRELUDE,
+    }
+    .to_string();
+    for query in &source.queries {
+        let response = shapes::return_shape(source, query);
+        out.push('\n');
+        out.push_str(&match lang {
+            Lang::Ts => typescript(source, query, &response),
+            Lang::Rust => rust(source, query, &response),
+            Lang::Python => python(source, query, &response),
+        });
+    }
+    out
+}
+
+/// Words of an identifier, split at underscores and lower-to-upper case changes
+fn words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c == '_' || c == '-' {
+            previous = None;
+            continue;
+        }
+        let boundary = match previous {
+            None => true,
+            Some(p) => p.is_lowercase() && c.is_uppercase(),
+        };
+        if boundary {
+            words.push(String::new());
+        }
+        if let Some(word) = words.last_mut() {
+            word.push(c);
+        }
+        previous = Some(c);
+    }
+    words
+}
+
+fn pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => pascal,
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// `name`, or `name_` when it is reserved in the target language
+fn unreserved(name: String, keywords: &[&str]) -> String {
+    if keywords.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// Shape of a parameter as sent in the request body
+fn parameter_shape(source: &Source, param: &Parameter) -> Shape {
+    shapes::field_shape(source, &param.param_type.1)
+}
+
+// TypeScript
+
+const TS_PRELUDE: &str = r#"// Generated by helixql-lsp codegen. Do not edit.
+
+/** UUID in its hyphenated string form */
+export type Uuid = string;
+/** RFC 3339 timestamp, e.g. "2024-01-01T00:00:00Z" */
+export type DateTime = string;
+/**
+ * 64-bit integer, sent and received as a plain JSON integer. Values beyond
+ * Number.MAX_SAFE_INTEGER are read as bigint where JSON.parse passes the source
+ * text to revivers (Node 21+, current browsers); elsewhere they lose precision.
+ */
+export type Int64 = number | bigint;
+/** 128-bit unsigned integer, read and written like Int64 */
+export type U128 = number | bigint;
+
+export let baseUrl = "http://localhost:6969";
+
+const BIGINT_MARKER = "__helixql_bigint__";
+
+/** JSON.stringify, writing bigints as plain integers */
+function stringify(body: unknown): string {
+  return JSON.stringify(body, (_key, value) =>
+    typeof value === "bigint" ? BIGINT_MARKER + value.toString() : value,
+  ).replace(new RegExp(`"${BIGINT_MARKER}(-?\\d+)"`, "g"), "$1");
+}
+
+/** Reads integers too large for a number as bigint */
+function revive(_key: string, value: unknown, context?: { source?: string }): unknown {
+  if (
+    typeof value === "number" &&
+    !Number.isSafeInteger(value) &&
+    context?.source !== undefined &&
+    /^-?\d+$/.test(context.source)
+  ) {
+    return BigInt(context.source);
+  }
+  return value;
+}
+
+async function post<T>(path: string, body: unknown): Promise<T> {
+  const response = await fetch(baseUrl + path, {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: stringify(body),
+  });
+  if (!response.ok) {
+    throw new Error(`${path} failed with ${response.status}: ${await response.text()}`);
+  }
+  return JSON.parse(await response.text(), revive) as T;
+}
+"#;
+
+const TS_KEYWORDS: &[&str] = &[
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "new",
+    "null",
+    "return",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+];
+
+fn typescript(source: &Source, query: &Query, response: &Shape) -> String {
+    let name = pascal_case(&query.name);
+    let fields: Vec<String> = query
+        .parameters
+        .iter()
+        .map(|param| {
+            format!(
+                "  {}{}: {};\n",
+                param.name.1,
+                if param.is_optional { "?" } else { "" },
+                parameter_shape(source, param).typescript_with_aliases(1)
+            )
+        })
+        .collect();
+    format!(
+        "export interface {name}Request {{\n{fields}}}\n\n\
+         export type {name}Response = {response};\n\n\
+         export function {function}(request: {name}Request): Promise<{name}Response> {{\n  \
+         return post(\"{path}\", request);\n}}\n",
+        name = name,
+        fields = fields.concat(),
+        response = response.typescript_with_aliases(0),
+        function = unreserved(camel_case(&query.name), TS_KEYWORDS),
+        path = endpoint(query),
+    )
+}
+
+// Rust
+
+const RUST_PRELUDE: &str = r#"// Generated by helixql-lsp codegen. Do not edit.
+// Needs the `serde` (with `derive`), `serde_json` and `reqwest` (with `json`) crates.
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+/// UUID in its hyphenated string form
+pub type Uuid = String;
+/// RFC 3339 timestamp, e.g. "2024-01-01T00:00:00Z"
+pub type DateTime = String;
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:6969";
+"#;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while",
+];
+
+fn rust_field(key: &str) -> String {
+    let ident = snake_case(key);
+    if RUST_KEYWORDS.contains(&ident.as_str()) {
+        format!("r#{}", ident)
+    } else {
+        ident
+    }
+}
+
+/// Rust type for `shape`; objects become structs named `name`, appended to `structs`
+fn rust_type(shape: &Shape, name: &str, structs: &mut Vec<String>) -> String {
+    match shape {
+        Shape::Scalar(ty) => match ty {
+            FieldType::String => "String",
+            FieldType::Uuid => "Uuid",
+            FieldType::Date => "DateTime",
+            FieldType::Boolean => "bool",
+            FieldType::F32 => "f32",
+            FieldType::F64 => "f64",
+            FieldType::I8 => "i8",
+            FieldType::I16 => "i16",
+            FieldType::I32 => "i32",
+            FieldType::I64 => "i64",
+            FieldType::U8 => "u8",
+            FieldType::U16 => "u16",
+            FieldType::U32 => "u32",
+            FieldType::U64 => "u64",
+            FieldType::U128 => "u128",
+            _ => "serde_json::Value",
+        }
+        .to_string(),
+        Shape::Object(fields) => {
+            let body: Vec<String> = fields
+                .iter()
+                .map(|(key, shape)| {
+                    let ty = rust_type(shape, &format!("{}{}", name, pascal_case(key)), structs);
+                    rust_struct_field(key, &ty, false)
+                })
+                .collect();
+            structs.push(format!(
+                "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct {} {{\n{}}}\n",
+                name,
+                body.concat()
+            ));
+            name.to_string()
+        }
+        Shape::Array(item) => format!(
+            "Vec<{}>",
+            rust_type(item, &format!("{}Item", name), structs)
+        ),
+        Shape::Tuple(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| rust_type(item, &format!("{}{}", name, i), structs))
+                .collect();
+            format!("({})", items.join(", "))
+        }
+        Shape::Unknown => "serde_json::Value".to_string(),
+    }
+}
+
+fn rust_struct_field(key: &str, ty: &str, optional: bool) -> String {
+    let field = rust_field(key);
+    let mut attrs = String::new();
+    if field.trim_start_matches("r#") != key {
+        attrs.push_str(&format!("    #[serde(rename = \"{}\")]\n", key));
+    }
+    if optional {
+        attrs.push_str("    #[serde(skip_serializing_if = \"Option::is_none\")]\n");
+        format!("{}    pub {}: Option<{}>,\n", attrs, field, ty)
+    } else {
+        format!("{}    pub {}: {},\n", attrs, field, ty)
+    }
+}
+
+fn rust(source: &Source, query: &Query, response: &Shape) -> String {
+    let name = pascal_case(&query.name);
+    let mut structs = Vec::new();
+
+    let fields: Vec<String> = query
+        .parameters
+        .iter()
+        .map(|param| {
+            let ty = rust_type(
+                &parameter_shape(source, param),
+                &format!("{}Request{}", name, pascal_case(&param.name.1)),
+                &mut structs,
+            );
+            rust_struct_field(&param.name.1, &ty, param.is_optional)
+        })
+        .collect();
+    structs.push(format!(
+        "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct {}Request {{\n{}}}\n",
+        name,
+        fields.concat()
+    ));
+
+    let response_type = rust_type(response, &format!("{}Response", name), &mut structs);
+    if response_type != format!("{}Response", name) {
+        structs.push(format!("pub type {}Response = {};\n", name, response_type));
+    }
+
+    format!(
+        "{structs}\n\
+         pub async fn {function}(\n    \
+         client: &reqwest::Client,\n    \
+         base_url: &str,\n    \
+         request: &{name}Request,\n\
+         ) -> reqwest::Result<{name}Response> {{\n    \
+         client\n        \
+         .post(format!(\"{{}}{path}\", base_url))\n        \
+         .json(request)\n        \
+         .send()\n        \
+         .await?\n        \
+         .error_for_status()?\n        \
+         .json()\n        \
+         .await\n\
+         }}\n",
+        structs = structs.join("\n"),
+        function = rust_field(&query.name),
+        name = name,
+        path = endpoint(query),
+    )
+}
+
+// Python
+
+const PYTHON_PRELUDE: &str = r#"# Generated by helixql-lsp codegen. Do not edit.
+from __future__ import annotations
+
+import json
+import urllib.request
+from typing import Any, List, Tuple, TypedDict
+
+try:
+    from typing import NotRequired
+except ImportError:  # Python < 3.11
+    from typing_extensions import NotRequired
+
+# UUID in its hyphenated string form
+Uuid = str
+# RFC 3339 timestamp, e.g. "2024-01-01T00:00:00Z"
+DateTime = str
+
+BASE_URL = "http://localhost:6969"
+
+
+def _post(path: str, body: Any, base_url: str) -> Any:
+    request = urllib.request.Request(
+        base_url + path,
+        data=json.dumps(body).encode(),
+        headers={"Content-Type": "application/json"},
+        method="POST",
+    )
+    with urllib.request.urlopen(request) as response:
+        return json.load(response)
+"#;
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Python type for `shape`; objects become TypedDicts named `name`, appended to `classes`
+fn python_type(shape: &Shape, name: &str, classes: &mut Vec<String>) -> String {
+    match shape {
+        Shape::Scalar(ty) => match ty {
+            FieldType::String => "str",
+            FieldType::Uuid => "Uuid",
+            FieldType::Date => "DateTime",
+            FieldType::Boolean => "bool",
+            FieldType::F32 | FieldType::F64 => "float",
+            ty if crate::infer::is_numeric(ty) => "int",
+            _ => "Any",
+        }
+        .to_string(),
+        Shape::Object(fields) => {
+            let fields: Vec<(String, String)> = fields
+                .iter()
+                .map(|(key, shape)| {
+                    let ty = python_type(shape, &format!("{}{}", name, pascal_case(key)), classes);
+                    (key.clone(), ty)
+                })
+                .collect();
+            classes.push(python_class(name, &fields));
+            name.to_string()
+        }
+        Shape::Array(item) => format!(
+            "List[{}]",
+            python_type(item, &format!("{}Item", name), classes)
+        ),
+        Shape::Tuple(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| python_type(item, &format!("{}{}", name, i), classes))
+                .collect();
+            format!("Tuple[{}]", items.join(", "))
+        }
+        Shape::Unknown => "Any".to_string(),
+    }
+}
+
+/// A TypedDict; keys that are not identifiers need the functional syntax
+fn python_class(name: &str, fields: &[(String, String)]) -> String {
+    let plain = fields.iter().all(|(key, _)| {
+        !PYTHON_KEYWORDS.contains(&key.as_str())
+            && key
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_alphanumeric() || c == '_')
+    });
+    if plain {
+        let body: Vec<String> = fields
+            .iter()
+            .map(|(key, ty)| format!("    {}: {}\n", key, ty))
+            .collect();
+        let body = if body.is_empty() {
+            "    pass\n".to_string()
+        } else {
+            body.concat()
+        };
+        format!("class {}(TypedDict):\n{}", name, body)
+    } else {
+        let entries: Vec<String> = fields
+            .iter()
+            .map(|(key, ty)| format!("\"{}\": {}", key, ty))
+            .collect();
+        format!(
+            "{} = TypedDict(\"{}\", {{{}}})\n",
+            name,
+            name,
+            entries.join(", ")
+        )
+    }
+}
+
+fn python(source: &Source, query: &Query, response: &Shape) -> String {
+    let name = pascal_case(&query.name);
+    let mut classes = Vec::new();
+
+    let fields: Vec<(String, String)> = query
+        .parameters
+        .iter()
+        .map(|param| {
+            let ty = python_type(
+                &parameter_shape(source, param),
+                &format!("{}Request{}", name, pascal_case(&param.name.1)),
+                &mut classes,
+            );
+            let ty = if param.is_optional {
+                format!("NotRequired[{}]", ty)
+            } else {
+                ty
+            };
+            (param.name.1.clone(), ty)
+        })
+        .collect();
+    classes.push(python_class(&format!("{}Request", name), &fields));
+
+    let response_type = python_type(response, &format!("{}Response", name), &mut classes);
+    if response_type != format!("{}Response", name) {
+        classes.push(format!("{}Response = {}\n", name, response_type));
+    }
+
+    format!(
+        "\n{classes}\n\n\
+         def {function}(request: {name}Request, base_url: str = BASE_URL) -> {name}Response:\n    \
+         return _post(\"{path}\", request, base_url)\n",
+        classes = classes.join("\n\n"),
+        function = unreserved(snake_case(&query.name), PYTHON_KEYWORDS),
+        name = name,
+        path = endpoint(query),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_text;
+
+    #[test]
+    fn words_split_at_underscores_dashes_and_case_changes() {
+        assert_eq!(words("getUserByID"), ["get", "User", "By", "ID"]);
+        assert_eq!(words("get_user_by_id"), ["get", "user", "by", "id"]);
+        assert_eq!(words("GetUser"), ["Get", "User"]);
+        assert_eq!(words("add-friend"), ["add", "friend"]);
+        assert_eq!(words("__private__name"), ["private", "name"]);
+        assert_eq!(words("user2"), ["user2"]);
+        assert!(words("_").is_empty());
+    }
+
+    #[test]
+    fn pascal_case_capitalizes_each_word() {
+        assert_eq!(pascal_case("getUserByID"), "GetUserByID");
+        assert_eq!(pascal_case("create_user"), "CreateUser");
+        assert_eq!(pascal_case("User"), "User");
+        assert_eq!(pascal_case(""), "");
+    }
+
+    #[test]
+    fn snake_case_lowercases_and_joins_words() {
+        assert_eq!(snake_case("getUserByID"), "get_user_by_id");
+        assert_eq!(snake_case("CreateUser"), "create_user");
+        assert_eq!(snake_case("already_snake"), "already_snake");
+        assert_eq!(snake_case("add-friend"), "add_friend");
+    }
+
+    #[test]
+    fn camel_case_lowercases_the_first_letter() {
+        assert_eq!(camel_case("create_user"), "createUser");
+        assert_eq!(camel_case("GetUser"), "getUser");
+    }
+
+    const QUERIES: &str = "N::User { name: String, age: U64 }
+
+QUERY GetUser(id: ID, min_age?: U64) =>
+    user <- N<User>(id)
+    RETURN user
+";
+
+    /// Client code generated for `QUERIES`, after the prelude
+    fn generated(lang: Lang, prelude: &str) -> String {
+        let out = generate(&parse_text(QUERIES), lang);
+        out.strip_prefix(prelude).unwrap().to_string()
+    }
+
+    #[test]
+    fn typescript_client_matches() {
+        let expected = r#"
+export interface GetUserRequest {
+  id: Uuid;
+  min_age?: Int64;
+}
+
+export type GetUserResponse = {
+  user: {
+    id: Uuid;
+    label: string;
+    name: string;
+    age: Int64;
+  };
+};
+
+export function getUser(request: GetUserRequest): Promise<GetUserResponse> {
+  return post("/GetUser", request);
+}
+"#;
+        assert_eq!(generated(Lang::Ts, TS_PRELUDE), expected);
+    }
+
+    #[test]
+    fn rust_client_matches() {
+        let expected = r#"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetUserRequest {
+    pub id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_age: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetUserResponseUser {
+    pub id: Uuid,
+    pub label: String,
+    pub name: String,
+    pub age: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetUserResponse {
+    pub user: GetUserResponseUser,
+}
+
+pub async fn get_user(
+    client: &reqwest::Client,
+    base_url: &str,
+    request: &GetUserRequest,
+) -> reqwest::Result<GetUserResponse> {
+    client
+        .post(format!("{}/GetUser", base_url))
+        .json(request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+}
+"#;
+        assert_eq!(generated(Lang::Rust, RUST_PRELUDE), expected);
+    }
+
+    #[test]
+    fn python_client_matches() {
+        let expected = r#"
+
+class GetUserRequest(TypedDict):
+    id: Uuid
+    min_age: NotRequired[int]
+
+
+class GetUserResponseUser(TypedDict):
+    id: Uuid
+    label: str
+    name: str
+    age: int
+
+
+class GetUserResponse(TypedDict):
+    user: GetUserResponseUser
+
+
+def get_user(request: GetUserRequest, base_url: str = BASE_URL) -> GetUserResponse:
+    return _post("/GetUser", request, base_url)
+"#;
+        assert_eq!(generated(Lang::Python, PYTHON_PRELUDE), expected);
+    }
+}
@@ -0,0 +1,623 @@
+use pest::error::{ErrorVariant, InputLocation};
+use pest::Parser;
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, Position};
+
+use crate::docs::{self, Category};
+use crate::infer::{ElementType, Vars};
+use crate::snippets::SchemaNames;
+use crate::syntax::{self, HqlParser, LineIndex, Rule};
+
+type Keyword = (&'static str, &'static str);
+
+const TOP_LEVEL: &[Keyword] = &[
+    ("QUERY", "Define a query"),
+    ("N::", "Node schema"),
+    ("E::", "Edge schema"),
+    ("V::", "Vector schema"),
+    ("MIGRATION", "Schema migration"),
+    ("schema::", "Versioned schema block"),
+    ("#[mcp]", "Expose the query as an MCP tool"),
+    ("#[model]", "Embedding model for the query"),
+];
+
+const SCHEMA_ITEMS: &[Keyword] = &[
+    ("N::", "Node schema"),
+    ("E::", "Edge schema"),
+    ("V::", "Vector schema"),
+];
+
+const MACROS: &[Keyword] = &[
+    ("#[mcp]", "Expose the query as an MCP tool"),
+    ("#[model]", "Embedding model for the query"),
+];
+
+const FIELD_MODIFIERS: &[Keyword] = &[
+    ("INDEX", "Index the field"),
+    ("UNIQUE", "Uniqueness constraint"),
+];
+
+const DEFAULT_VALUES: &[Keyword] = &[
+    ("NOW", "Current timestamp"),
+    ("NONE", "No value"),
+    ("true", "Boolean true"),
+    ("false", "Boolean false"),
+];
+
+const PRIMITIVE_TYPES: &[Keyword] = &[
+    ("String", "UTF-8 string"),
+    ("Boolean", "true or false"),
+    ("F32", "32-bit float"),
+    ("F64", "64-bit float"),
+    ("I8", "8-bit signed integer"),
+    ("I16", "16-bit signed integer"),
+    ("I32", "32-bit signed integer"),
+    ("I64", "64-bit signed integer"),
+    ("U8", "8-bit unsigned integer"),
+    ("U16", "16-bit unsigned integer"),
+    ("U32", "32-bit unsigned integer"),
+    ("U64", "64-bit unsigned integer"),
+    ("U128", "128-bit unsigned integer"),
+    ("Date", "Timestamp"),
+    ("ID", "Element identifier"),
+];
+
+const GRAPH_STEPS: &[Keyword] = &[
+    ("Out", "Traverse outgoing edges"),
+    ("In", "Traverse incoming edges"),
+    ("OutE", "Get outgoing edge objects"),
+    ("InE", "Get incoming edge objects"),
+    ("FromN", "Get source node"),
+    ("ToN", "Get target node"),
+    ("FromV", "Get source vertex"),
+    ("ToV", "Get target vertex"),
+    ("ShortestPath", "Find shortest path"),
+    ("ShortestPathBFS", "Shortest path (BFS)"),
+    ("ShortestPathDijkstras", "Shortest path (Dijkstra)"),
+    ("ShortestPathAStar", "Shortest path (A*)"),
+];
+
+const BOOL_OPERATIONS: &[Keyword] = &[
+    ("GT", "Greater than"),
+    ("GTE", "Greater than or equal"),
+    ("LT", "Less than"),
+    ("LTE", "Less than or equal"),
+    ("EQ", "Equal"),
+    ("NEQ", "Not equal"),
+    ("CONTAINS", "Contains value"),
+    ("IS_IN", "Value in set"),
+];
+
+const CREATIONS: &[Keyword] = &[
+    ("AddN", "Add node"),
+    ("AddE", "Add edge"),
+    ("AddV", "Add vector"),
+    ("BatchAddV", "Batch add vectors"),
+];
+
+const SOURCES: &[Keyword] = &[
+    ("N", "Nodes"),
+    ("E", "Edges"),
+    ("V", "Vectors"),
+    ("SearchV", "Search vectors"),
+    ("SearchBM25", "BM25 search"),
+];
+
+const BOOL_EXPRESSIONS: &[Keyword] = &[
+    ("EXISTS", "Check existence"),
+    ("AND", "Logical AND"),
+    ("OR", "Logical OR"),
+    ("true", "Boolean true"),
+    ("false", "Boolean false"),
+];
+
+const MATH_FUNCTIONS: &[Keyword] = &[
+    ("ADD", "Addition"),
+    ("SUB", "Subtraction"),
+    ("MUL", "Multiplication"),
+    ("DIV", "Division"),
+    ("POW", "Power"),
+    ("MOD", "Modulo"),
+    ("ABS", "Absolute value"),
+    ("SQRT", "Square root"),
+    ("LN", "Natural logarithm"),
+    ("LOG10", "Base-10 logarithm"),
+    ("LOG", "Logarithm"),
+    ("EXP", "Exponential"),
+    ("CEIL", "Round up"),
+    ("FLOOR", "Round down"),
+    ("ROUND", "Round to nearest"),
+    ("SIN", "Sine"),
+    ("COS", "Cosine"),
+    ("TAN", "Tangent"),
+    ("ASIN", "Arc sine"),
+    ("ACOS", "Arc cosine"),
+    ("ATAN", "Arc tangent"),
+    ("ATAN2", "Two-argument arc tangent"),
+    ("PI", "π"),
+    ("E", "Euler's number"),
+    ("MIN", "Minimum"),
+    ("MAX", "Maximum"),
+    ("SUM", "Sum"),
+    ("AVG", "Average"),
+    ("COUNT", "Number of values"),
+];
+
+/// Keywords offered when the cursor context can't be determined
+const KEYWORDS: &[(&str, &str, CompletionItemKind)] = &[
+    ("QUERY", "Define a query", CompletionItemKind::KEYWORD),
+    ("RETURN", "Return values", CompletionItemKind::KEYWORD),
+    ("WHERE", "Filter condition", CompletionItemKind::KEYWORD),
+    ("FOR", "Loop iteration", CompletionItemKind::KEYWORD),
+    ("IN", "In operator", CompletionItemKind::KEYWORD),
+    ("AND", "Logical AND", CompletionItemKind::KEYWORD),
+    ("OR", "Logical OR", CompletionItemKind::KEYWORD),
+    ("EXISTS", "Check existence", CompletionItemKind::KEYWORD),
+    ("NONE", "No value", CompletionItemKind::KEYWORD),
+    ("DROP", "Delete data", CompletionItemKind::KEYWORD),
+    ("MIGRATION", "Schema migration", CompletionItemKind::KEYWORD),
+    ("FIRST", "First element", CompletionItemKind::KEYWORD),
+    ("AS", "Alias/cast", CompletionItemKind::KEYWORD),
+    (
+        "UNIQUE",
+        "Uniqueness constraint",
+        CompletionItemKind::KEYWORD,
+    ),
+    ("N::", "Node schema", CompletionItemKind::KEYWORD),
+    ("E::", "Edge schema", CompletionItemKind::KEYWORD),
+    ("V::", "Vector schema", CompletionItemKind::KEYWORD),
+    ("AddN", "Add node", CompletionItemKind::FUNCTION),
+    ("AddE", "Add edge", CompletionItemKind::FUNCTION),
+    ("AddV", "Add vector", CompletionItemKind::FUNCTION),
+    (
+        "BatchAddV",
+        "Batch add vectors",
+        CompletionItemKind::FUNCTION,
+    ),
+    ("SearchV", "Search vectors", CompletionItemKind::FUNCTION),
+    ("SearchBM25", "BM25 search", CompletionItemKind::FUNCTION),
+    ("Embed", "Generate embeddings", CompletionItemKind::FUNCTION),
+    ("UpsertN", "Upsert node", CompletionItemKind::FUNCTION),
+    ("UpsertE", "Upsert edge", CompletionItemKind::FUNCTION),
+    ("UpsertV", "Upsert vector", CompletionItemKind::FUNCTION),
+    ("RerankRRF", "Rerank (RRF)", CompletionItemKind::FUNCTION),
+    ("RerankMMR", "Rerank (MMR)", CompletionItemKind::FUNCTION),
+];
+
+/// Steps any graph element supports
+const ELEMENT_STEPS: &[&str] = &[
+    "WHERE",
+    "RANGE",
+    "ORDER",
+    "COUNT",
+    "ID",
+    "UPDATE",
+    "FIRST",
+    "AGGREGATE_BY",
+    "GROUP_BY",
+];
+
+const NODE_STEPS: &[&str] = &[
+    "Out",
+    "In",
+    "OutE",
+    "InE",
+    "ShortestPath",
+    "ShortestPathBFS",
+    "ShortestPathDijkstras",
+    "ShortestPathAStar",
+    "AddE",
+    "UpsertN",
+];
+
+const EDGE_STEPS: &[&str] = &["FromN", "ToN", "FromV", "ToV", "UpsertE"];
+
+const VECTOR_STEPS: &[&str] = &[
+    "Out",
+    "In",
+    "OutE",
+    "InE",
+    "AddE",
+    "RerankRRF",
+    "RerankMMR",
+    "UpsertV",
+];
+
+/// Which schema items a type argument names
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TypeKind {
+    Node,
+    Edge,
+    Vector,
+    /// Edge endpoints, which may be nodes or vectors
+    Endpoint,
+}
+
+/// What the grammar accepts at the cursor
+#[derive(Debug)]
+pub struct Context {
+    /// Rules the parser tried at the cursor
+    rules: Vec<Rule>,
+    /// The cursor follows a `::` the expected rules start with
+    after_colons: bool,
+    /// The type argument being written, when the cursor follows `N<`, `AddE<`, `From:`...
+    type_kind: Option<TypeKind>,
+    /// The variable a step chain at the cursor starts from, as in `user::`
+    variable: Option<String>,
+}
+
+impl Context {
+    /// Whether a parameter or variable name may be written at the cursor
+    pub fn accepts_variables(&self) -> bool {
+        !self.after_colons
+            && self.type_kind.is_none()
+            && self.rules.iter().any(|rule| {
+                matches!(
+                    rule,
+                    Rule::identifier
+                        | Rule::evaluates_to_anything
+                        | Rule::evaluates_to_bool
+                        | Rule::evaluates_to_number
+                        | Rule::math_expression
+                        | Rule::to_order
+                        | Rule::id_arg
+                        | Rule::id_args
+                        | Rule::object_field
+                        | Rule::update_field
+                        | Rule::vector_data
+                        | Rule::array_creation
+                )
+            })
+    }
+
+    pub fn variable(&self) -> Option<&str> {
+        self.variable.as_deref()
+    }
+}
+
+/// The identifier `text` ends with, empty if it ends with anything else
+fn trailing_word(text: &str) -> &str {
+    &text[text
+        .trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        .len()..]
+}
+
+fn type_kind(before: &str) -> Option<TypeKind> {
+    let before = before.trim_end();
+    if before.ends_with("From:") || before.ends_with("To:") {
+        return Some(TypeKind::Endpoint);
+    }
+    match trailing_word(before.strip_suffix('<')?.trim_end()) {
+        "N" | "AddN" | "SearchBM25" => Some(TypeKind::Node),
+        "V" | "AddV" | "SearchV" | "BatchAddV" => Some(TypeKind::Vector),
+        "E" | "AddE" | "Out" | "In" | "OutE" | "InE" => Some(TypeKind::Edge),
+        k if k.starts_with("ShortestPath") => Some(TypeKind::Edge),
+        _ => None,
+    }
+}
+
+/// The variable `before` applies a first step to, as in `user::`. Chains that
+/// continue a traversal (`N<User>::Out<Follows>::`) or start from `_` have none.
+fn traversed_variable(before: &str) -> Option<String> {
+    let before = before.trim_end().strip_suffix("::")?.trim_end();
+    let name = trailing_word(before);
+    let preceding = before[..before.len() - name.len()].trim_end();
+    let starts_chain = !preceding.ends_with([':', '>', ')', '}']);
+    (starts_chain && name.starts_with(|c: char| c.is_ascii_alphabetic())).then(|| name.to_string())
+}
+
+/// Parse the current item up to the cursor and report what the grammar expects there.
+/// Only that item is parsed, so syntax errors in earlier ones don't hide the context.
+/// The word being typed is left out, so the client filters the result against it.
+/// Returns `None` when the item has a syntax error before the cursor.
+pub fn context_at(text: &str, position: Position) -> Option<Context> {
+    let before = &text[..LineIndex::new(text).offset(position)];
+    let before = &before[..before.len() - trailing_word(before).len()];
+    let start = syntax::item_starts(before)
+        .last()
+        .copied()
+        .unwrap_or_default();
+    let item = &before[start..];
+
+    let error = match HqlParser::parse(Rule::source, item) {
+        Ok(_) => {
+            return Some(Context {
+                rules: vec![Rule::source],
+                after_colons: false,
+                type_kind: None,
+                variable: None,
+            })
+        }
+        Err(error) => error,
+    };
+    let ErrorVariant::ParsingError { positives, .. } = error.variant else {
+        return None;
+    };
+    let pos = match error.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span((start, _)) => start,
+    };
+    let after_colons = match item[pos..].trim() {
+        "" => false,
+        "::" => true,
+        _ => return None,
+    };
+
+    Some(Context {
+        rules: positives,
+        after_colons,
+        type_kind: type_kind(&item[..pos]),
+        variable: traversed_variable(&item[..pos]),
+    })
+}
+
+/// Rules that begin with `::`, expanded into the rules that may follow it
+fn colon_rules(rule: Rule) -> &'static [Rule] {
+    match rule {
+        Rule::step => &[
+            Rule::graph_step,
+            Rule::order_by,
+            Rule::aggregate,
+            Rule::group_by,
+            Rule::where_step,
+            Rule::count,
+            Rule::ID,
+            Rule::range_step,
+            Rule::AddE,
+            Rule::rerank_rrf,
+            Rule::rerank_mmr,
+        ],
+        Rule::last_step => &[
+            Rule::bool_operations,
+            Rule::update,
+            Rule::upsert_v,
+            Rule::upsert_e,
+            Rule::upsert_n,
+            Rule::first,
+        ],
+        Rule::to_from => &[Rule::from, Rule::to],
+        Rule::from => &[Rule::from],
+        Rule::to => &[Rule::to],
+        _ => &[],
+    }
+}
+
+/// Keywords that can start `rule`
+fn keywords(rule: Rule) -> (&'static [Keyword], CompletionItemKind) {
+    use CompletionItemKind as Kind;
+    match rule {
+        Rule::source | Rule::query_def | Rule::schema_def | Rule::migration_def => {
+            (TOP_LEVEL, Kind::KEYWORD)
+        }
+        Rule::node_def | Rule::edge_def | Rule::vector_def | Rule::item_def => {
+            (SCHEMA_ITEMS, Kind::KEYWORD)
+        }
+        Rule::anon_decl => (&[("_::", "Drop the item")], Kind::KEYWORD),
+        Rule::cast => (&[("AS", "Cast to a type")], Kind::KEYWORD),
+        Rule::built_in_macro => (MACROS, Kind::KEYWORD),
+        Rule::field_def | Rule::index => (FIELD_MODIFIERS, Kind::KEYWORD),
+        Rule::edge_modifier => (&[("UNIQUE", "Uniqueness constraint")], Kind::KEYWORD),
+        Rule::default => (&[("DEFAULT", "Default value")], Kind::KEYWORD),
+        Rule::now => (&[("NOW", "Current timestamp")], Kind::CONSTANT),
+        Rule::none => (&[("NONE", "No value")], Kind::CONSTANT),
+        Rule::boolean => (&DEFAULT_VALUES[2..], Kind::CONSTANT),
+        Rule::param_type => (PRIMITIVE_TYPES, Kind::TYPE_PARAMETER),
+
+        Rule::for_loop => (&[("FOR", "Loop iteration")], Kind::KEYWORD),
+        Rule::return_stmt => (&[("RETURN", "Return values")], Kind::KEYWORD),
+        Rule::drop => (&[("DROP", "Delete data")], Kind::KEYWORD),
+        Rule::creation_stmt => (CREATIONS, Kind::FUNCTION),
+        Rule::exists => (&BOOL_EXPRESSIONS[..1], Kind::KEYWORD),
+        Rule::and => (&BOOL_EXPRESSIONS[1..2], Kind::KEYWORD),
+        Rule::or => (&BOOL_EXPRESSIONS[2..3], Kind::KEYWORD),
+        Rule::traversal => (&SOURCES[..4], Kind::FUNCTION),
+        Rule::search_vector => (&SOURCES[3..4], Kind::FUNCTION),
+        Rule::bm25_search => (&SOURCES[4..], Kind::FUNCTION),
+        Rule::anonymous_traversal => (&[("_", "The current element")], Kind::VARIABLE),
+        Rule::math_function_call | Rule::math_function_name => (MATH_FUNCTIONS, Kind::FUNCTION),
+        Rule::vector_data | Rule::embed_method => {
+            (&[("Embed", "Generate embeddings")], Kind::FUNCTION)
+        }
+        Rule::order_by_type => (
+            &[("Asc", "Ascending"), ("Desc", "Descending")],
+            Kind::ENUM_MEMBER,
+        ),
+
+        Rule::graph_step => (GRAPH_STEPS, Kind::METHOD),
+        Rule::bool_operations => (BOOL_OPERATIONS, Kind::METHOD),
+        Rule::where_step => (&[("WHERE", "Filter by condition")], Kind::METHOD),
+        Rule::range_step => (&[("RANGE", "Select range")], Kind::METHOD),
+        Rule::order_by => (&[("ORDER", "Order results")], Kind::METHOD),
+        Rule::count => (&[("COUNT", "Count elements")], Kind::METHOD),
+        Rule::ID => (&[("ID", "Get identifier")], Kind::METHOD),
+        Rule::first => (&[("FIRST", "First element")], Kind::METHOD),
+        Rule::update => (&[("UPDATE", "Update properties")], Kind::METHOD),
+        Rule::upsert_n => (&[("UpsertN", "Upsert node")], Kind::METHOD),
+        Rule::upsert_e => (&[("UpsertE", "Upsert edge")], Kind::METHOD),
+        Rule::upsert_v => (&[("UpsertV", "Upsert vector")], Kind::METHOD),
+        Rule::aggregate => (&[("AGGREGATE_BY", "Aggregate by field")], Kind::METHOD),
+        Rule::group_by => (&[("GROUP_BY", "Group by field")], Kind::METHOD),
+        Rule::rerank_rrf => (&[("RerankRRF", "Rerank (RRF)")], Kind::METHOD),
+        Rule::rerank_mmr => (&[("RerankMMR", "Rerank (MMR)")], Kind::METHOD),
+        Rule::AddE => (&[("AddE", "Add edge")], Kind::FUNCTION),
+        Rule::from => (&[("From", "Edge source")], Kind::METHOD),
+        Rule::to => (&[("To", "Edge target")], Kind::METHOD),
+        _ => (&[], Kind::KEYWORD),
+    }
+}
+
+/// Rules an expression rule may start with
+fn expression_rules(rule: Rule) -> &'static [Rule] {
+    match rule {
+        Rule::evaluates_to_anything => &[
+            Rule::creation_stmt,
+            Rule::exists,
+            Rule::none,
+            Rule::traversal,
+            Rule::search_vector,
+            Rule::bm25_search,
+            Rule::math_function_call,
+            Rule::boolean,
+            Rule::and,
+            Rule::or,
+        ],
+        Rule::evaluates_to_bool => &[
+            Rule::exists,
+            Rule::boolean,
+            Rule::and,
+            Rule::or,
+            Rule::traversal,
+        ],
+        Rule::evaluates_to_number | Rule::math_expression => {
+            &[Rule::math_function_call, Rule::traversal]
+        }
+        Rule::to_order => &[Rule::anonymous_traversal],
+        Rule::object_field | Rule::update_field => &[Rule::evaluates_to_anything],
+        _ => &[],
+    }
+}
+
+/// A keyword completion documented by its reference entry; `math` picks the math
+/// function entry for names that also mean something else, such as `E` and `COUNT`
+fn keyword_item(label: &str, detail: &str, kind: CompletionItemKind, math: bool) -> CompletionItem {
+    let name = label.trim_end_matches("::");
+    let doc = if math {
+        docs::lookup_as(name, Category::Math)
+    } else {
+        docs::lookup(name)
+    };
+    CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        detail: Some(detail.to_string()),
+        documentation: doc.map(|doc| doc.documentation()),
+        sort_text: Some(format!("1{}", label)),
+        ..Default::default()
+    }
+}
+
+fn type_items<'a>(
+    names: &'a [String],
+    detail: &'static str,
+) -> impl Iterator<Item = CompletionItem> + 'a {
+    names.iter().map(move |name| CompletionItem {
+        label: name.clone(),
+        kind: Some(CompletionItemKind::CLASS),
+        detail: Some(detail.to_string()),
+        ..Default::default()
+    })
+}
+
+/// Completion items for what the grammar expects at the cursor
+pub fn items(context: &Context, names: &SchemaNames) -> Vec<CompletionItem> {
+    let mut rules = Vec::new();
+    for &rule in &context.rules {
+        if context.after_colons {
+            rules.extend_from_slice(colon_rules(rule));
+        } else if !expression_rules(rule).is_empty() {
+            for &inner in expression_rules(rule) {
+                rules.push(inner);
+                rules.extend_from_slice(expression_rules(inner));
+            }
+        } else {
+            rules.push(rule);
+        }
+    }
+
+    let mut items: Vec<CompletionItem> = Vec::new();
+    for rule in rules {
+        let (keywords, kind) = keywords(rule);
+        let math = matches!(rule, Rule::math_function_call | Rule::math_function_name);
+        for (label, detail) in keywords {
+            // `E` may be offered both as edges and as the math constant
+            if !items
+                .iter()
+                .any(|item| item.label == *label && item.detail.as_deref() == Some(*detail))
+            {
+                items.push(keyword_item(label, detail, kind, math));
+            }
+        }
+
+        match rule {
+            Rule::param_type => {
+                items.extend(type_items(&names.nodes, "Node type"));
+                items.extend(type_items(&names.edges, "Edge type"));
+                items.extend(type_items(&names.vectors, "Vector type"));
+            }
+            Rule::identifier_upper | Rule::type_args => match context.type_kind {
+                Some(TypeKind::Node) => items.extend(type_items(&names.nodes, "Node type")),
+                Some(TypeKind::Edge) => items.extend(type_items(&names.edges, "Edge type")),
+                Some(TypeKind::Vector) => items.extend(type_items(&names.vectors, "Vector type")),
+                Some(TypeKind::Endpoint) => {
+                    items.extend(type_items(&names.nodes, "Node type"));
+                    items.extend(type_items(&names.vectors, "Vector type"));
+                }
+                None => {}
+            },
+            _ => {}
+        }
+    }
+    items
+}
+
+/// Parameters and variables in scope with their inferred types, sorted before keywords
+pub fn variable_items(vars: &Vars) -> Vec<CompletionItem> {
+    let mut items: Vec<CompletionItem> = vars
+        .iter()
+        .map(|(name, ty)| CompletionItem {
+            label: name.clone(),
+            kind: Some(CompletionItemKind::VARIABLE),
+            detail: Some(ty.label()),
+            sort_text: Some(format!("0{}", name)),
+            ..Default::default()
+        })
+        .collect();
+    items.sort_by(|a, b| a.label.cmp(&b.label));
+    items
+}
+
+/// Keep only the steps an element of type `ty` supports
+pub fn retain_steps(items: &mut Vec<CompletionItem>, ty: &ElementType) {
+    let specific: &[&str] = match ty {
+        ElementType::Node(_) => NODE_STEPS,
+        ElementType::Edge(_) => EDGE_STEPS,
+        ElementType::Vector(_) => VECTOR_STEPS,
+        ElementType::Scalar(_) => {
+            items.retain(|item| BOOL_OPERATIONS.iter().any(|(op, _)| *op == item.label));
+            return;
+        }
+        ElementType::Unknown => return,
+    };
+    items.retain(|item| {
+        let label = item.label.as_str();
+        ELEMENT_STEPS.contains(&label) || specific.contains(&label)
+    });
+}
+
+/// Keyword list for when the context at the cursor is unknown
+pub fn keyword_items() -> Vec<CompletionItem> {
+    KEYWORDS
+        .iter()
+        .map(|(label, detail, kind)| keyword_item(label, detail, *kind, false))
+        .collect()
+}
+
+/// Replace plain items with the snippet of the same label, when there is one
+pub fn with_snippets(
+    items: Vec<CompletionItem>,
+    snippets: &[CompletionItem],
+) -> Vec<CompletionItem> {
+    items
+        .into_iter()
+        .map(|item| {
+            snippets
+                .iter()
+                .find(|snippet| snippet.label == item.label)
+                .map(|snippet| CompletionItem {
+                    documentation: item.documentation.clone(),
+                    sort_text: item.sort_text.clone(),
+                    ..snippet.clone()
+                })
+                .unwrap_or(item)
+        })
+        .collect()
+}
@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+use helix_db::helixc::parser::types::{
+    Expression, ExpressionType, GraphStepType, Loc, Query, Source, StartNode, Statement,
+    StatementType, Step, StepType, Traversal,
+};
+
+use crate::infer::{self, ElementType, Vars};
+use crate::walk::{self, Visitor};
+
+/// How a query touches a schema item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    Read,
+    Create,
+    Update,
+    Drop,
+}
+
+/// One place where a query touches a schema item, e.g. `N::Customer`
+#[derive(Debug, Clone)]
+pub struct Usage {
+    pub item: String,
+    pub access: Access,
+    pub loc: Loc,
+}
+
+struct UsageCollector<'a> {
+    source: &'a Source,
+    vars: Vars,
+    usages: Vec<Usage>,
+}
+
+impl UsageCollector<'_> {
+    fn record(&mut self, ty: &ElementType, access: Access, loc: &Loc) {
+        if let Some(item) = ty.schema_item() {
+            self.usages.push(Usage {
+                item,
+                access,
+                loc: loc.clone(),
+            });
+        }
+    }
+
+    fn record_name(&mut self, prefix: &str, name: &Option<String>, access: Access, loc: &Loc) {
+        if let Some(name) = name {
+            self.usages.push(Usage {
+                item: format!("{}::{}", prefix, name),
+                access,
+                loc: loc.clone(),
+            });
+        }
+    }
+}
+
+impl Visitor for UsageCollector<'_> {
+    fn statement(&mut self, stmt: &Statement) {
+        if let StatementType::Drop(expr) = &stmt.statement {
+            let ty = infer::expression_type(self.source, expr, &self.vars);
+            self.record(&ty, Access::Drop, &stmt.loc);
+        }
+        infer::bind_loop(stmt, &mut self.vars);
+    }
+
+    fn statement_end(&mut self, stmt: &Statement) {
+        infer::bind_assignment(self.source, stmt, &mut self.vars);
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        match &expr.expr {
+            ExpressionType::AddNode(add) => {
+                self.record_name("N", &add.node_type, Access::Create, &add.loc)
+            }
+            ExpressionType::AddEdge(add) => {
+                self.record_name("E", &add.edge_type, Access::Create, &add.loc)
+            }
+            ExpressionType::AddVector(add) => {
+                self.record_name("V", &add.vector_type, Access::Create, &add.loc)
+            }
+            ExpressionType::BatchAddVector(add) => {
+                self.record_name("V", &add.vector_type, Access::Create, &add.loc)
+            }
+            ExpressionType::SearchVector(sv) => {
+                self.record_name("V", &sv.vector_type, Access::Read, &sv.loc)
+            }
+            ExpressionType::BM25Search(bm25) => {
+                self.record_name("N", &bm25.type_arg, Access::Read, &bm25.loc)
+            }
+            _ => {}
+        }
+    }
+
+    fn traversal(&mut self, traversal: &Traversal) {
+        let types =
+            infer::traversal_types(self.source, traversal, &self.vars, &ElementType::Unknown);
+
+        if matches!(
+            traversal.start,
+            StartNode::Node { .. } | StartNode::Edge { .. } | StartNode::Vector { .. }
+        ) {
+            self.record(&types[0], Access::Read, &traversal.loc);
+        }
+
+        for (i, step) in traversal.steps.iter().enumerate() {
+            self.step_usage(step, &types[i], &types[i + 1]);
+        }
+    }
+}
+
+impl UsageCollector<'_> {
+    fn step_usage(&mut self, step: &Step, current: &ElementType, next: &ElementType) {
+        match &step.step {
+            StepType::Node(graph_step) | StepType::Edge(graph_step) => {
+                match &graph_step.step {
+                    GraphStepType::Out(edge)
+                    | GraphStepType::In(edge)
+                    | GraphStepType::OutE(edge)
+                    | GraphStepType::InE(edge) => {
+                        self.record(&ElementType::Edge(edge.clone()), Access::Read, &step.loc)
+                    }
+                    GraphStepType::ShortestPath(sp) => {
+                        self.record_name("E", &sp.type_arg, Access::Read, &step.loc)
+                    }
+                    GraphStepType::ShortestPathBFS(sp) => {
+                        self.record_name("E", &sp.type_arg, Access::Read, &step.loc)
+                    }
+                    GraphStepType::ShortestPathDijkstras(sp) => {
+                        self.record_name("E", &sp.type_arg, Access::Read, &step.loc)
+                    }
+                    GraphStepType::ShortestPathAStar(sp) => {
+                        self.record_name("E", &sp.type_arg, Access::Read, &step.loc)
+                    }
+                    _ => {}
+                }
+                if next != current {
+                    self.record(next, Access::Read, &step.loc);
+                }
+            }
+            StepType::Update(_)
+            | StepType::UpsertN(_)
+            | StepType::UpsertE(_)
+            | StepType::UpsertV(_) => self.record(current, Access::Update, &step.loc),
+            StepType::AddEdge(add) => {
+                self.record_name("E", &add.edge_type, Access::Create, &step.loc)
+            }
+            StepType::SearchVector(sv) => {
+                self.record_name("V", &sv.vector_type, Access::Read, &step.loc)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Every place `query` reads, creates, updates or drops a schema item
+pub fn query_usages(source: &Source, query: &Query) -> Vec<Usage> {
+    let mut collector = UsageCollector {
+        source,
+        vars: infer::parameters(query),
+        usages: Vec::new(),
+    };
+    walk::walk_query(&mut collector, query);
+    collector.usages
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyGraphParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// Schema items a single query touches, grouped by access kind
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryDependencies {
+    pub query: String,
+    pub reads: BTreeSet<String>,
+    pub creates: BTreeSet<String>,
+    pub updates: BTreeSet<String>,
+    pub drops: BTreeSet<String>,
+}
+
+/// Queries touching a single schema item, grouped by access kind
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemDependents {
+    pub item: String,
+    pub read_by: BTreeSet<String>,
+    pub created_by: BTreeSet<String>,
+    pub updated_by: BTreeSet<String>,
+    pub dropped_by: BTreeSet<String>,
+}
+
+/// Response of `helixql/dependencyGraph`: the query to item view and its reverse
+#[derive(Debug, Default, Serialize)]
+pub struct DependencyGraph {
+    pub queries: Vec<QueryDependencies>,
+    pub items: Vec<ItemDependents>,
+}
+
+/// All schema items declared in any schema version, as `N::Name`, `E::Name` or `V::Name`
+pub fn schema_items(source: &Source) -> BTreeSet<String> {
+    let mut items = BTreeSet::new();
+    for schema in source.schema.values() {
+        items.extend(
+            schema
+                .node_schemas
+                .iter()
+                .map(|n| format!("N::{}", n.name.1)),
+        );
+        items.extend(
+            schema
+                .edge_schemas
+                .iter()
+                .map(|e| format!("E::{}", e.name.1)),
+        );
+        items.extend(
+            schema
+                .vector_schemas
+                .iter()
+                .map(|v| format!("V::{}", v.name)),
+        );
+    }
+    items
+}
+
+pub fn dependency_graph(source: &Source) -> DependencyGraph {
+    let mut items: BTreeMap<String, ItemDependents> = schema_items(source)
+        .into_iter()
+        .map(|item| {
+            (
+                item.clone(),
+                ItemDependents {
+                    item,
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+
+    let mut queries = Vec::new();
+    for query in &source.queries {
+        let mut deps = QueryDependencies {
+            query: query.name.clone(),
+            ..Default::default()
+        };
+        for usage in query_usages(source, query) {
+            let (forward, reverse) = {
+                let entry = items
+                    .entry(usage.item.clone())
+                    .or_insert_with(|| ItemDependents {
+                        item: usage.item.clone(),
+                        ..Default::default()
+                    });
+                match usage.access {
+                    Access::Read => (&mut deps.reads, &mut entry.read_by),
+                    Access::Create => (&mut deps.creates, &mut entry.created_by),
+                    Access::Update => (&mut deps.updates, &mut entry.updated_by),
+                    Access::Drop => (&mut deps.drops, &mut entry.dropped_by),
+                }
+            };
+            forward.insert(usage.item);
+            reverse.insert(query.name.clone());
+        }
+        queries.push(deps);
+    }
+
+    DependencyGraph {
+        queries,
+        items: items.into_values().collect(),
+    }
+}
@@ -0,0 +1,340 @@
+use serde_json::{json, Value};
+use tower_lsp::lsp_types::{
+    CodeDescription, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
+    NumberOrString, Url,
+};
+
+use helix_db::helixc::analyzer::diagnostic::{
+    Diagnostic as HelixDiagnostic, DiagnosticSeverity as HelixSeverity,
+};
+use helix_db::helixc::analyzer::error_codes::ErrorCode;
+use helix_db::helixc::parser::types::{
+    GraphStepType, Loc, Query, Source, Statement, StatementType, Step, StepType,
+};
+
+use crate::fields::{self, FieldRef};
+use crate::infer::{self, ElementType};
+use crate::walk::{self, Visitor};
+use crate::{lint, loc_to_range, loc_uri};
+
+/// What an analyzer error is about, which decides the locations that explain it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Subject {
+    /// A property of the element at the location: its type and field definitions
+    Field,
+    /// The edge type a step at the location traverses: its definition
+    EdgeType,
+    /// A variable declared at the location: its earlier declaration
+    Redeclaration,
+    /// Nothing else in the project explains it
+    Location,
+}
+
+/// Stable code for an analyzer error kind, and what the error is about
+fn error_kind(code: ErrorCode) -> (&'static str, Subject) {
+    use ErrorCode::*;
+    use Subject::*;
+    match code {
+        E101 => ("E101", Location),
+        E102 => ("E102", Location),
+        E103 => ("E103", Location),
+        E105 => ("E105", Location),
+        E106 => ("E106", Location),
+        E201 => ("E201", Location),
+        E202 => ("E202", Field),
+        E203 => ("E203", Location),
+        E204 => ("E204", Field),
+        E205 => ("E205", Field),
+        E206 => ("E206", Location),
+        E207 => ("E207", EdgeType),
+        E208 => ("E208", Field),
+        E209 => ("E209", Location),
+        E210 => ("E210", Location),
+        E301 => ("E301", Location),
+        E302 => ("E302", Redeclaration),
+        E304 => ("E304", Location),
+        E305 => ("E305", Location),
+        E401 => ("E401", Location),
+        E501 => ("E501", Location),
+        E601 => ("E601", Location),
+        E602 => ("E602", Location),
+        E603 => ("E603", Location),
+        E604 => ("E604", Location),
+        E611 => ("E611", Location),
+        E612 => ("E612", Location),
+        E621 => ("E621", Location),
+        E622 => ("E622", Field),
+        E623 => ("E623", EdgeType),
+        E624 => ("E624", EdgeType),
+        E625 => ("E625", EdgeType),
+        E626 => ("E626", EdgeType),
+        E631 => ("E631", Location),
+        E632 => ("E632", Location),
+        E633 => ("E633", Location),
+        E641 => ("E641", Location),
+        E642 => ("E642", Location),
+        E643 => ("E643", Field),
+        E644 => ("E644", Location),
+        E645 => ("E645", Location),
+        E646 => ("E646", Location),
+        E651 => ("E651", Location),
+        E652 => ("E652", Location),
+        E653 => ("E653", Location),
+        W101 => ("W101", Location),
+    }
+}
+
+/// Whether `inner` lies inside `outer`
+pub fn within(inner: &Loc, outer: &Loc) -> bool {
+    inner.filepath == outer.filepath
+        && (outer.start.line, outer.start.column) <= (inner.start.line, inner.start.column)
+        && (inner.end.line, inner.end.column) <= (outer.end.line, outer.end.column)
+}
+
+/// Whether one of the two locations lies inside the other
+fn overlapping(a: &Loc, b: &Loc) -> bool {
+    within(a, b) || within(b, a)
+}
+
+/// Lines and columns a location covers, for picking the narrowest of several
+fn extent(loc: &Loc) -> (usize, usize) {
+    (
+        loc.end.line - loc.start.line,
+        loc.end.column.abs_diff(loc.start.column),
+    )
+}
+
+/// Where a node, edge or vector type is defined
+fn definition(source: &Source, owner: &ElementType) -> Option<Loc> {
+    match owner {
+        ElementType::Node(name) => infer::node_schema(source, name).map(|n| n.name.0.clone()),
+        ElementType::Edge(name) => infer::edge_schema(source, name).map(|e| e.name.0.clone()),
+        ElementType::Vector(name) => infer::vector_schema(source, name).map(|v| v.loc.clone()),
+        _ => None,
+    }
+}
+
+/// Locations that explain an analyzer diagnostic, found from the syntax at its
+/// location according to what its error code is about
+fn related_locations(
+    source: &Source,
+    diag: &HelixDiagnostic,
+    subject: Subject,
+) -> Vec<(Loc, String)> {
+    let Some(query) = source
+        .queries
+        .iter()
+        .find(|q| within(&diag.location, &q.loc))
+    else {
+        return Vec::new();
+    };
+    match subject {
+        Subject::Field => field_definitions(source, query, &diag.location),
+        Subject::EdgeType => edge_definition(source, query, &diag.location),
+        Subject::Redeclaration => earlier_declaration(query, &diag.location),
+        Subject::Location => Vec::new(),
+    }
+}
+
+/// The type and field definitions of the property referenced at `loc`
+fn field_definitions(source: &Source, query: &Query, loc: &Loc) -> Vec<(Loc, String)> {
+    let refs: Vec<FieldRef> = fields::query_field_refs(source, query)
+        .into_iter()
+        .filter(|field_ref| overlapping(&field_ref.loc, loc))
+        .collect();
+    let Some(narrowest) = refs.iter().map(|r| extent(&r.loc)).min() else {
+        return Vec::new();
+    };
+    let mut candidates: Vec<&FieldRef> = refs
+        .iter()
+        .filter(|r| extent(&r.loc) == narrowest)
+        .collect();
+    // Several properties read by one construct: keep those the location names
+    if candidates.len() > 1 && candidates.iter().any(|r| loc.span.contains(&r.name)) {
+        candidates.retain(|r| loc.span.contains(&r.name));
+    }
+
+    let mut related = Vec::new();
+    for field_ref in candidates {
+        let Some(item) = field_ref.owner.schema_item() else {
+            continue;
+        };
+        if let Some(defined) = definition(source, &field_ref.owner) {
+            let entry = (defined, format!("{} is defined here", item));
+            if !related.contains(&entry) {
+                related.push(entry);
+            }
+        }
+        if let Some((_, field)) =
+            infer::field_declaration(source, &field_ref.owner, &field_ref.name)
+        {
+            let entry = (
+                field.loc.clone(),
+                format!(
+                    "`{}` is declared as {} on {}",
+                    field.name,
+                    crate::Backend::field_type_to_string(&field.field_type),
+                    item
+                ),
+            );
+            if !related.contains(&entry) {
+                related.push(entry);
+            }
+        }
+    }
+    related
+}
+
+/// Edge types traversed by the steps of a query, with the step's location
+#[derive(Default)]
+struct EdgeSteps {
+    steps: Vec<(Loc, String)>,
+}
+
+impl Visitor for EdgeSteps {
+    fn step(&mut self, step: &Step) {
+        if let StepType::Node(graph) | StepType::Edge(graph) = &step.step {
+            if let GraphStepType::Out(name)
+            | GraphStepType::In(name)
+            | GraphStepType::OutE(name)
+            | GraphStepType::InE(name) = &graph.step
+            {
+                self.steps.push((step.loc.clone(), name.clone()));
+            }
+        }
+    }
+}
+
+/// The definition of the edge type the step at `loc` traverses
+fn edge_definition(source: &Source, query: &Query, loc: &Loc) -> Vec<(Loc, String)> {
+    let mut edges = EdgeSteps::default();
+    walk::walk_query(&mut edges, query);
+    edges
+        .steps
+        .iter()
+        .filter(|(step, _)| overlapping(step, loc))
+        .min_by_key(|(step, _)| extent(step))
+        .and_then(|(_, name)| {
+            let edge = infer::edge_schema(source, name)?;
+            Some((edge.name.0.clone(), format!("E::{} is defined here", name)))
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Assignments of a query in order, including those inside FOR loops
+fn assignments(statements: &[Statement], out: &mut Vec<(Loc, String)>) {
+    for stmt in statements {
+        match &stmt.statement {
+            StatementType::Assignment(assignment) => {
+                out.push((assignment.loc.clone(), assignment.variable.clone()))
+            }
+            StatementType::ForLoop(for_loop) => assignments(&for_loop.statements, out),
+            _ => {}
+        }
+    }
+}
+
+/// The parameter or assignment first declaring the variable declared at `loc`
+fn earlier_declaration(query: &Query, loc: &Loc) -> Vec<(Loc, String)> {
+    let mut declared: Vec<(Loc, String)> = query
+        .parameters
+        .iter()
+        .map(|p| (p.name.0.clone(), p.name.1.clone()))
+        .collect();
+    assignments(&query.statements, &mut declared);
+    let Some(position) = declared
+        .iter()
+        .rposition(|(declaration, _)| overlapping(declaration, loc))
+    else {
+        return Vec::new();
+    };
+    let name = &declared[position].1;
+    declared[..position]
+        .iter()
+        .find(|(_, earlier)| earlier == name)
+        .map(|(earlier, _)| {
+            (
+                earlier.clone(),
+                format!("`{}` is first declared here", name),
+            )
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Structured payload for code actions: the error code, the analyzer's hint,
+/// and its suggested fix in the same shape lint fixes use
+fn diagnostic_data(code: &str, diag: &HelixDiagnostic) -> Value {
+    let mut data = json!({ "errorCode": code });
+    if let Some(hint) = &diag.hint {
+        data["hint"] = json!(hint);
+    }
+    if let Some(fix) = &diag.fix {
+        if let Some(loc) = fix.to_remove.as_ref().or(fix.span.as_ref()) {
+            data["fix"] = json!(lint::FixData {
+                title: diag
+                    .hint
+                    .clone()
+                    .unwrap_or_else(|| "Apply suggested fix".to_string()),
+                range: loc_to_range(loc),
+                new_text: fix.to_add.clone().unwrap_or_default(),
+                uri: None,
+            });
+        }
+    }
+    data
+}
+
+/// Reference page of an analyzer error code under the docs site at `base`
+fn error_doc_url(base: &str, code: &str) -> String {
+    format!(
+        "{}/errors#{}",
+        base.trim_end_matches('/'),
+        code.to_lowercase()
+    )
+}
+
+/// Convert an analyzer diagnostic, attaching its code, doc link, related locations and data
+pub fn from_analyzer(
+    dir_key: &str,
+    source: &Source,
+    diag: &HelixDiagnostic,
+    docs_base: &str,
+) -> Diagnostic {
+    let severity = match diag.severity {
+        HelixSeverity::Error => DiagnosticSeverity::ERROR,
+        HelixSeverity::Warning => DiagnosticSeverity::WARNING,
+        HelixSeverity::Info => DiagnosticSeverity::INFORMATION,
+        HelixSeverity::Hint => DiagnosticSeverity::HINT,
+        HelixSeverity::Empty => DiagnosticSeverity::INFORMATION,
+    };
+    let (code, subject) = error_kind(diag.error_code);
+    let code = code.to_string();
+    let related: Vec<DiagnosticRelatedInformation> = related_locations(source, diag, subject)
+        .into_iter()
+        .filter_map(|(loc, message)| {
+            Some(DiagnosticRelatedInformation {
+                location: Location {
+                    uri: loc_uri(dir_key, &loc)?,
+                    range: loc_to_range(&loc),
+                },
+                message,
+            })
+        })
+        .collect();
+
+    Diagnostic {
+        range: loc_to_range(&diag.location),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.clone())),
+        code_description: Url::parse(&error_doc_url(docs_base, &code))
+            .ok()
+            .map(|href| CodeDescription { href }),
+        source: Some("helixql".to_string()),
+        message: diag.message.clone(),
+        related_information: (!related.is_empty()).then_some(related),
+        data: Some(diagnostic_data(&code, diag)),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,615 @@
+use tower_lsp::lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel,
+    SignatureInformation,
+};
+
+use crate::lint::DOCS_BASE;
+
+/// HelixQL version the reference table describes
+pub const DOCS_VERSION: &str = "1.2";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Category {
+    Keyword,
+    Schema,
+    Step,
+    Comparison,
+    Creation,
+    Search,
+    Macro,
+    Math,
+    Type,
+    Value,
+}
+
+impl Category {
+    fn label(self) -> &'static str {
+        match self {
+            Category::Keyword => "keyword",
+            Category::Schema => "schema",
+            Category::Step => "step",
+            Category::Comparison => "comparison",
+            Category::Creation => "creation",
+            Category::Search => "search",
+            Category::Macro => "macro",
+            Category::Math => "math function",
+            Category::Type => "type",
+            Category::Value => "value",
+        }
+    }
+
+    /// Page of the language reference documenting the category
+    const fn page(self) -> &'static str {
+        match self {
+            Category::Keyword => "queries",
+            Category::Schema => "schema",
+            Category::Step => "traversals",
+            Category::Comparison => "conditions",
+            Category::Creation => "mutations",
+            Category::Search => "search",
+            Category::Macro => "macros",
+            Category::Math => "math",
+            Category::Type => "types",
+            Category::Value => "values",
+        }
+    }
+}
+
+/// A parameter of a call-like construct, as shown in signature help
+#[derive(Debug)]
+pub struct Param {
+    pub name: &'static str,
+    pub doc: &'static str,
+}
+
+/// Reference entry for a built-in keyword, step, function, macro or type
+#[derive(Debug)]
+pub struct Doc {
+    pub name: &'static str,
+    pub category: Category,
+    pub summary: &'static str,
+    /// How the construct is written. For calls, every parameter name appears
+    /// inside the parentheses, in order.
+    pub syntax: &'static str,
+    pub example: Option<&'static str>,
+    pub returns: Option<&'static str>,
+    pub params: &'static [Param],
+    /// HelixQL version that introduced the construct
+    pub since: &'static str,
+    /// Reference page documenting the construct, relative to the reference root
+    pub link: &'static str,
+}
+
+const fn doc(
+    name: &'static str,
+    category: Category,
+    summary: &'static str,
+    syntax: &'static str,
+) -> Doc {
+    Doc {
+        name,
+        category,
+        summary,
+        syntax,
+        example: None,
+        returns: None,
+        params: &[],
+        since: DOCS_VERSION,
+        link: category.page(),
+    }
+}
+
+const fn param(name: &'static str, doc: &'static str) -> Param {
+    Param { name, doc }
+}
+
+impl Doc {
+    const fn example(mut self, example: &'static str) -> Self {
+        self.example = Some(example);
+        self
+    }
+
+    const fn returns(mut self, returns: &'static str) -> Self {
+        self.returns = Some(returns);
+        self
+    }
+
+    const fn params(mut self, params: &'static [Param]) -> Self {
+        self.params = params;
+        self
+    }
+}
+
+use Category::*;
+
+/// Parameter name of a field object, as written in the syntax lines
+const FIELDS: &str = "{field: value, ...}";
+
+const VALUE: &[Param] = &[param("value", "Value or expression to compare against")];
+const UNARY: &[Param] = &[param("x", "Number")];
+const BINARY: &[Param] = &[param("a", "Left operand"), param("b", "Right operand")];
+const UPDATE_FIELDS: &[Param] = &[param(FIELDS, "Properties to set")];
+
+pub static DOCS: &[Doc] = &[
+    // Query structure
+    doc(
+        "QUERY",
+        Keyword,
+        "Defines a query: its parameters, the statements it runs and the values it returns.",
+        "QUERY Name(param: Type, optional?: Type) =>\n    ...\n    RETURN value",
+    )
+    .example("QUERY GetUser(id: ID) =>\n    user <- N<User>(id)\n    RETURN user"),
+    doc(
+        "RETURN",
+        Keyword,
+        "Ends a query with the values it returns: variables, literals, traversals or an object.",
+        "RETURN value, ...",
+    )
+    .example("RETURN user::{name, age}"),
+    doc(
+        "FOR",
+        Keyword,
+        "Runs the loop body once for every element of a collection parameter or variable.",
+        "FOR item IN collection {\n    ...\n}",
+    )
+    .example("FOR {name, age} IN users {\n    AddN<User>({name: name, age: age})\n}"),
+    doc("IN", Keyword, "Names the collection a `FOR` loop iterates over.", "FOR item IN collection"),
+    doc(
+        "DROP",
+        Keyword,
+        "Deletes the elements a traversal reaches, along with their edges.",
+        "DROP traversal",
+    )
+    .example("DROP N<User>(id)::OutE<Follows>"),
+    doc(
+        "EXISTS",
+        Keyword,
+        "Whether a traversal reaches at least one element. Prefix with `!` to negate.",
+        "EXISTS(traversal)",
+    )
+    .returns("Boolean")
+    .params(&[param("traversal", "Traversal to test")])
+    .example("N<User>::WHERE(EXISTS(_::Out<Follows>))"),
+    doc(
+        "AND",
+        Keyword,
+        "True when every condition holds. Prefix with `!` to negate.",
+        "AND(condition, ...)",
+    )
+    .returns("Boolean")
+    .params(&[param("condition", "Boolean expressions or anonymous traversals")])
+    .example("WHERE(AND(_::{age}::GT(18), _::{active}::EQ(true)))"),
+    doc(
+        "OR",
+        Keyword,
+        "True when any condition holds. Prefix with `!` to negate.",
+        "OR(condition, ...)",
+    )
+    .returns("Boolean")
+    .params(&[param("condition", "Boolean expressions or anonymous traversals")]),
+    doc(
+        "NONE",
+        Value,
+        "No value. Usable as a field default and in migrations.",
+        "NONE",
+    ),
+    doc(
+        "NOW",
+        Value,
+        "The current timestamp, evaluated when the element is created.",
+        "field: Date DEFAULT NOW",
+    )
+    .returns("Date"),
+    doc(
+        "MIGRATION",
+        Keyword,
+        "Maps the items and fields of one schema version onto the next.",
+        "MIGRATION schema::1 => schema::2 {\n    N::Old => N::New { new_field: old_field }\n}",
+    ),
+    doc(
+        "AS",
+        Keyword,
+        "Casts a field to another type while migrating it.",
+        "new_field: old_field AS Type",
+    )
+    .example("age: age AS I64"),
+    // Schema definitions
+    doc(
+        "N",
+        Schema,
+        "Declares a node type, or starts a traversal from nodes of a type.",
+        "N::Name { field: Type }\nN<Name>(id)",
+    )
+    .example("N::User {\n    INDEX email: String,\n    name: String\n}"),
+    doc(
+        "E",
+        Schema,
+        "Declares an edge type between two node or vector types, or starts a traversal from edges.",
+        "E::Name {\n    From: Type,\n    To: Type,\n    Properties: { field: Type }\n}\nE<Name>(id)",
+    ),
+    doc(
+        "V",
+        Schema,
+        "Declares a vector type, or starts a traversal from vectors of a type.",
+        "V::Name { field: Type }\nV<Name>(id)",
+    ),
+    doc(
+        "schema",
+        Schema,
+        "Groups schema items into a numbered version that migrations can refer to.",
+        "schema::1 {\n    N::User { ... }\n}",
+    ),
+    doc("From", Schema, "Source type of an edge, or the source node in `AddE` and path steps.", "From: Type\n::From(id)"),
+    doc("To", Schema, "Target type of an edge, or the target node in `AddE` and path steps.", "To: Type\n::To(id)"),
+    doc("Properties", Schema, "Fields stored on each edge of the type.", "Properties: { field: Type }"),
+    doc(
+        "INDEX",
+        Schema,
+        "Indexes a field so `N<Type>({field: value})` looks elements up without a scan. `UNIQUE INDEX` also rejects duplicate values.",
+        "INDEX field: Type\nUNIQUE INDEX field: Type",
+    ),
+    doc(
+        "UNIQUE",
+        Schema,
+        "On an index, rejects duplicate field values. On an edge type, allows at most one edge between any two elements.",
+        "UNIQUE INDEX field: Type\nE::Name UNIQUE { ... }",
+    ),
+    doc(
+        "DEFAULT",
+        Schema,
+        "Value a field takes when a creation doesn't set it.",
+        "field: Type DEFAULT value",
+    )
+    .example("created_at: Date DEFAULT NOW"),
+    // Graph steps
+    doc("Out", Step, "Nodes reached over outgoing edges of a type.", "::Out<Edge>")
+        .returns("Elements of the edge's `To` type")
+        .example("N<User>(id)::Out<Follows>"),
+    doc("In", Step, "Nodes reached over incoming edges of a type.", "::In<Edge>")
+        .returns("Elements of the edge's `From` type")
+        .example("N<User>(id)::In<Follows>"),
+    doc("OutE", Step, "Outgoing edges of a type.", "::OutE<Edge>").returns("Edges"),
+    doc("InE", Step, "Incoming edges of a type.", "::InE<Edge>").returns("Edges"),
+    doc("FromN", Step, "Source node of each edge.", "::FromN").returns("Nodes"),
+    doc("ToN", Step, "Target node of each edge.", "::ToN").returns("Nodes"),
+    doc("FromV", Step, "Source vector of each edge.", "::FromV").returns("Vectors"),
+    doc("ToV", Step, "Target vector of each edge.", "::ToV").returns("Vectors"),
+    doc(
+        "ShortestPath",
+        Step,
+        "Shortest unweighted path over edges of a type.",
+        "::ShortestPath<Edge>::To(id)",
+    )
+    .returns("Path")
+    .example("N<City>(a)::ShortestPath<Road>::To(b)"),
+    doc(
+        "ShortestPathBFS",
+        Step,
+        "Shortest unweighted path found by breadth-first search.",
+        "::ShortestPathBFS<Edge>::To(id)",
+    )
+    .returns("Path"),
+    doc(
+        "ShortestPathDijkstras",
+        Step,
+        "Shortest weighted path by Dijkstra's algorithm. The weight is evaluated per edge and must not be negative.",
+        "::ShortestPathDijkstras<Edge>(weight)::To(id)",
+    )
+    .returns("Path")
+    .params(&[param("weight", "Edge weight, e.g. `_::{distance}`")])
+    .example("N<City>(a)::ShortestPathDijkstras<Road>(_::{distance})::To(b)"),
+    doc(
+        "ShortestPathAStar",
+        Step,
+        "Shortest weighted path by A* search, guided by a heuristic property on the nodes.",
+        "::ShortestPathAStar<Edge>(weight, \"heuristic\")::To(id)",
+    )
+    .returns("Path")
+    .params(&[
+        param("weight", "Edge weight, e.g. `_::{distance}`"),
+        param("heuristic", "Name of the node property estimating the remaining distance"),
+    ]),
+    doc("WHERE", Step, "Keeps the elements for which the condition holds.", "::WHERE(condition)")
+        .params(&[param("condition", "Boolean expression or anonymous traversal")])
+        .example("N<User>::WHERE(_::{age}::GT(18))"),
+    doc(
+        "PREFILTER",
+        Step,
+        "Filters vector search candidates before ranking, rather than after.",
+        "SearchV<Type>(vector, k)::PREFILTER(condition)",
+    )
+    .params(&[param("condition", "Boolean expression or anonymous traversal")]),
+    doc("COUNT", Step, "Number of elements.", "::COUNT").returns("U64"),
+    doc("FIRST", Step, "The first element only.", "::FIRST"),
+    doc("ID", Step, "Identifier of each element; as a parameter type, an element identifier.", "::ID\nparam: ID")
+        .returns("ID"),
+    doc("RANGE", Step, "Elements from `start` (inclusive) to `end` (exclusive).", "::RANGE(start, end)")
+        .params(&[param("start", "First index"), param("end", "Index to stop before")])
+        .example("N<User>::RANGE(0, 10)"),
+    doc(
+        "ORDER",
+        Step,
+        "Sorts the elements by a property.",
+        "::ORDER<Asc|Desc>(property)",
+    )
+    .params(&[param("property", "Anonymous traversal to a property, e.g. `_::{age}`")])
+    .example("N<User>::ORDER<Desc>(_::{age})"),
+    doc("Asc", Value, "Ascending order.", "::ORDER<Asc>(property)"),
+    doc("Desc", Value, "Descending order.", "::ORDER<Desc>(property)"),
+    doc(
+        "AGGREGATE_BY",
+        Step,
+        "Groups elements by the listed properties and returns each group with its elements and count.",
+        "::AGGREGATE_BY(property, ...)",
+    )
+    .params(&[param("property", "Properties that form the group key")])
+    .example("N<User>::AGGREGATE_BY(country)"),
+    doc(
+        "GROUP_BY",
+        Step,
+        "Groups elements by the listed properties and returns each distinct key with its count.",
+        "::GROUP_BY(property, ...)",
+    )
+    .params(&[param("property", "Properties that form the group key")]),
+    doc("UPDATE", Step, "Sets properties on every element.", "::UPDATE({field: value, ...})")
+        .params(UPDATE_FIELDS)
+        .example("N<User>(id)::UPDATE({name: name})"),
+    doc(
+        "UpsertN",
+        Creation,
+        "Updates the nodes a traversal reaches, or creates one when it reaches none.",
+        "::UpsertN({field: value, ...})",
+    )
+    .params(UPDATE_FIELDS),
+    doc(
+        "UpsertE",
+        Creation,
+        "Updates the edges a traversal reaches, or creates one between the given nodes.",
+        "::UpsertE({field: value, ...})::From(id)::To(id)",
+    )
+    .params(UPDATE_FIELDS),
+    doc(
+        "UpsertV",
+        Creation,
+        "Updates the vectors a traversal reaches, or inserts one.",
+        "::UpsertV(vector, {field: value, ...})",
+    )
+    .params(&[
+        param("vector", "Vector data or `Embed(text)`"),
+        param(FIELDS, "Properties to set"),
+    ]),
+    doc(
+        "RerankRRF",
+        Step,
+        "Reranks search results by Reciprocal Rank Fusion.",
+        "::RerankRRF(k: k)",
+    )
+    .params(&[param("k", "Rank constant, 60 by default")]),
+    doc(
+        "RerankMMR",
+        Step,
+        "Reranks search results by Maximal Marginal Relevance, trading relevance against diversity.",
+        "::RerankMMR(lambda: lambda, distance: distance)",
+    )
+    .params(&[
+        param("lambda", "Weight of relevance against diversity, from 0 to 1"),
+        param("distance", "\"cosine\", \"euclidean\" or \"dotproduct\""),
+    ]),
+    // Comparisons
+    doc("GT", Comparison, "Greater than.", "::GT(value)").returns("Boolean").params(VALUE),
+    doc("GTE", Comparison, "Greater than or equal.", "::GTE(value)").returns("Boolean").params(VALUE),
+    doc("LT", Comparison, "Less than.", "::LT(value)").returns("Boolean").params(VALUE),
+    doc("LTE", Comparison, "Less than or equal.", "::LTE(value)").returns("Boolean").params(VALUE),
+    doc("EQ", Comparison, "Equal.", "::EQ(value)").returns("Boolean").params(VALUE),
+    doc("NEQ", Comparison, "Not equal.", "::NEQ(value)").returns("Boolean").params(VALUE),
+    doc("CONTAINS", Comparison, "Whether a string or array contains the value.", "::CONTAINS(value)")
+        .returns("Boolean")
+        .params(VALUE),
+    doc("IS_IN", Comparison, "Whether the value is one of a set.", "::IS_IN(values)")
+        .returns("Boolean")
+        .params(&[param("values", "Array literal or array parameter")]),
+    // Creation
+    doc("AddN", Creation, "Creates a node.", "AddN<Type>({field: value, ...})")
+        .returns("Node")
+        .params(&[param(FIELDS, "Properties of the new node")])
+        .example("AddN<User>({name: name, age: age})"),
+    doc(
+        "AddE",
+        Creation,
+        "Creates an edge between two elements.",
+        "AddE<Type>({field: value, ...})::From(id)::To(id)",
+    )
+    .returns("Edge")
+    .params(&[param(FIELDS, "Properties of the new edge")])
+    .example("AddE<Follows>::From(user)::To(other)"),
+    doc("AddV", Creation, "Inserts a vector.", "AddV<Type>(vector, {field: value, ...})")
+        .returns("Vector")
+        .params(&[
+            param("vector", "`[F64]` data or `Embed(text)`"),
+            param(FIELDS, "Properties of the new vector"),
+        ]),
+    doc("BatchAddV", Creation, "Inserts every vector of an array parameter.", "BatchAddV<Type>(vectors)")
+        .returns("Vectors")
+        .params(&[param("vectors", "Array parameter of vector objects")]),
+    // Search
+    doc(
+        "SearchV",
+        Search,
+        "The `k` vectors nearest to a query vector.",
+        "SearchV<Type>(vector, k)",
+    )
+    .returns("Vectors")
+    .params(&[
+        param("vector", "`[F64]` data or `Embed(text)`"),
+        param("k", "Number of results"),
+    ])
+    .example("SearchV<Doc>(Embed(text), 10)"),
+    doc(
+        "SearchBM25",
+        Search,
+        "The `k` nodes best matching a text by BM25 keyword ranking.",
+        "SearchBM25<Type>(text, k)",
+    )
+    .returns("Nodes")
+    .params(&[param("text", "Search text"), param("k", "Number of results")])
+    .example("SearchBM25<Article>(query, 20)"),
+    doc(
+        "Embed",
+        Search,
+        "Embeds text with the query's embedding model, see `#[model]`.",
+        "Embed(text)",
+    )
+    .returns("[F64]")
+    .params(&[param("text", "String literal or parameter")]),
+    // Macros
+    doc("#[mcp]", Macro, "Exposes the query below as an MCP tool.", "#[mcp]\nQUERY ..."),
+    doc(
+        "#[model]",
+        Macro,
+        "Sets the embedding model `Embed` uses in the query below.",
+        "#[model(\"provider:model\")]\nQUERY ...",
+    )
+    .params(&[param("model", "Model name")]),
+    // Math functions
+    doc("ADD", Math, "Sum of two numbers.", "ADD(a, b)").returns("Number").params(BINARY),
+    doc("SUB", Math, "Difference of two numbers.", "SUB(a, b)").returns("Number").params(BINARY),
+    doc("MUL", Math, "Product of two numbers.", "MUL(a, b)").returns("Number").params(BINARY),
+    doc("DIV", Math, "Quotient of two numbers.", "DIV(a, b)").returns("Number").params(BINARY),
+    doc("POW", Math, "`a` raised to the power `b`.", "POW(a, b)").returns("Number").params(BINARY),
+    doc("MOD", Math, "Remainder of `a` divided by `b`.", "MOD(a, b)").returns("Number").params(BINARY),
+    doc("ABS", Math, "Absolute value.", "ABS(x)").returns("Number").params(UNARY),
+    doc("SQRT", Math, "Square root.", "SQRT(x)").returns("F64").params(UNARY),
+    doc("LN", Math, "Natural logarithm.", "LN(x)").returns("F64").params(UNARY),
+    doc("LOG10", Math, "Base-10 logarithm.", "LOG10(x)").returns("F64").params(UNARY),
+    doc("LOG", Math, "Logarithm of `x` in base `b`.", "LOG(x, b)")
+        .returns("F64")
+        .params(&[param("x", "Number"), param("b", "Base")]),
+    doc("EXP", Math, "`e` raised to the power `x`.", "EXP(x)").returns("F64").params(UNARY),
+    doc("CEIL", Math, "Smallest integer not below `x`.", "CEIL(x)").returns("Number").params(UNARY),
+    doc("FLOOR", Math, "Largest integer not above `x`.", "FLOOR(x)").returns("Number").params(UNARY),
+    doc("ROUND", Math, "Nearest integer.", "ROUND(x)").returns("Number").params(UNARY),
+    doc("SIN", Math, "Sine, in radians.", "SIN(x)").returns("F64").params(UNARY),
+    doc("COS", Math, "Cosine, in radians.", "COS(x)").returns("F64").params(UNARY),
+    doc("TAN", Math, "Tangent, in radians.", "TAN(x)").returns("F64").params(UNARY),
+    doc("ASIN", Math, "Arc sine, in radians.", "ASIN(x)").returns("F64").params(UNARY),
+    doc("ACOS", Math, "Arc cosine, in radians.", "ACOS(x)").returns("F64").params(UNARY),
+    doc("ATAN", Math, "Arc tangent, in radians.", "ATAN(x)").returns("F64").params(UNARY),
+    doc("ATAN2", Math, "Angle of the point (`x`, `y`), in radians.", "ATAN2(y, x)")
+        .returns("F64")
+        .params(&[param("y", "Y coordinate"), param("x", "X coordinate")]),
+    doc("PI", Math, "The constant π.", "PI()").returns("F64"),
+    doc("E", Math, "Euler's number, the base of the natural logarithm.", "E()").returns("F64"),
+    doc("MIN", Math, "Smallest of the values.", "MIN(values)")
+        .returns("Number")
+        .params(&[param("values", "Numbers to compare")]),
+    doc("MAX", Math, "Largest of the values.", "MAX(values)")
+        .returns("Number")
+        .params(&[param("values", "Numbers to compare")]),
+    doc("SUM", Math, "Sum of the values.", "SUM(values)")
+        .returns("Number")
+        .params(&[param("values", "Numbers to add up")]),
+    doc("AVG", Math, "Mean of the values.", "AVG(values)")
+        .returns("F64")
+        .params(&[param("values", "Numbers to average")]),
+    doc("COUNT", Math, "Number of values.", "COUNT(values)")
+        .returns("U64")
+        .params(&[param("values", "Values to count")]),
+    // Types
+    doc("String", Type, "UTF-8 text.", "field: String"),
+    doc("Boolean", Type, "`true` or `false`.", "field: Boolean"),
+    doc("F32", Type, "32-bit floating point number.", "field: F32"),
+    doc("F64", Type, "64-bit floating point number.", "field: F64"),
+    doc("I8", Type, "8-bit signed integer.", "field: I8"),
+    doc("I16", Type, "16-bit signed integer.", "field: I16"),
+    doc("I32", Type, "32-bit signed integer.", "field: I32"),
+    doc("I64", Type, "64-bit signed integer.", "field: I64"),
+    doc("U8", Type, "8-bit unsigned integer.", "field: U8"),
+    doc("U16", Type, "16-bit unsigned integer.", "field: U16"),
+    doc("U32", Type, "32-bit unsigned integer.", "field: U32"),
+    doc("U64", Type, "64-bit unsigned integer.", "field: U64"),
+    doc("U128", Type, "128-bit unsigned integer.", "field: U128"),
+    doc("Date", Type, "Timestamp. Accepts RFC 3339 strings and `NOW`.", "field: Date"),
+];
+
+/// Reference entry for a built-in name; macros are looked up as `#[mcp]`, `#[model]`
+pub fn lookup(name: &str) -> Option<&'static Doc> {
+    DOCS.iter().find(|doc| doc.name == name)
+}
+
+/// Reference entry for `name` in `category`, for names with more than one meaning:
+/// `E` starts edge traversals and is a math constant, `COUNT` is a step and an aggregate
+pub fn lookup_as(name: &str, category: Category) -> Option<&'static Doc> {
+    DOCS.iter()
+        .find(|doc| doc.name == name && doc.category == category)
+        .or_else(|| lookup(name))
+}
+
+impl Doc {
+    pub fn url(&self) -> String {
+        let anchor: String = self
+            .name
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        format!(
+            "{}/reference/{}#{}",
+            DOCS_BASE,
+            self.link,
+            anchor.to_lowercase()
+        )
+    }
+
+    /// Markdown body shared by hovers and completion documentation
+    pub fn markdown(&self) -> String {
+        let mut text = format!("{}\n\n```hql\n{}\n```", self.summary, self.syntax);
+        if let Some(returns) = self.returns {
+            text.push_str(&format!("\n\n**Returns** `{}`", returns));
+        }
+        if let Some(example) = self.example {
+            text.push_str(&format!("\n\n**Example**\n\n```hql\n{}\n```", example));
+        }
+        text.push_str(&format!(
+            "\n\n_{}_ · since HelixQL {} · [Reference]({})",
+            self.category.label(),
+            self.since,
+            self.url()
+        ));
+        text
+    }
+
+    pub fn documentation(&self) -> Documentation {
+        Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: self.markdown(),
+        })
+    }
+
+    /// Signature help for call-like entries; parameters are located in the first
+    /// line of the syntax after its opening parenthesis
+    pub fn signature(&self) -> Option<SignatureInformation> {
+        if self.params.is_empty() {
+            return None;
+        }
+        let label = self.syntax.lines().next().unwrap_or_default();
+        let mut from = label.find('(')?;
+        let mut parameters = Vec::new();
+        for param in self.params {
+            let start = from + label[from..].find(param.name)?;
+            let end = start + param.name.len();
+            parameters.push(ParameterInformation {
+                label: ParameterLabel::LabelOffsets([
+                    label[..start].encode_utf16().count() as u32,
+                    label[..end].encode_utf16().count() as u32,
+                ]),
+                documentation: Some(Documentation::String(param.doc.to_string())),
+            });
+            from = end;
+        }
+        Some(SignatureInformation {
+            label: label.to_string(),
+            documentation: Some(Documentation::String(self.summary.to_string())),
+            parameters: Some(parameters),
+            active_parameter: None,
+        })
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use helix_db::helixc::parser::types::{
+    BooleanOpType, DefaultValue, Expression, ExpressionType, FieldAddition, FieldPrefix,
+    FieldValueType, GraphStepType, IdType, Loc, Query, ReturnType, Source, StartNode, Statement,
+    StatementType, StepType, Traversal, ValueType,
+};
+
+use crate::infer::{self, ElementType, Vars};
+use crate::migration;
+
+/// Whether a field reference reads the property or assigns it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldAccess {
+    Read,
+    Write,
+}
+
+/// A schema field referenced from a query, resolved against the element it belongs to
+#[derive(Debug, Clone)]
+pub struct FieldRef {
+    pub owner: ElementType,
+    pub name: String,
+    /// Location of the construct naming the field; the parser only keeps coarse spans
+    pub loc: Loc,
+}
+
+/// Walks a query tracking the element type every step applies to, so that
+/// `_::{age}`, `user::{name}` or `UPDATE({name: ...})` resolve to a schema field
+struct FieldWalker<'a> {
+    source: &'a Source,
+    vars: Vars,
+    refs: Vec<FieldRef>,
+}
+
+impl FieldWalker<'_> {
+    fn push(&mut self, owner: &ElementType, name: &str, loc: &Loc) {
+        if owner.schema_item().is_some() {
+            self.refs.push(FieldRef {
+                owner: owner.clone(),
+                name: name.to_string(),
+                loc: loc.clone(),
+            });
+        }
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            match &stmt.statement {
+                StatementType::Assignment(assignment) => {
+                    self.expression(&assignment.value, &ElementType::Unknown)
+                }
+                StatementType::Expression(expr) | StatementType::Drop(expr) => {
+                    self.expression(expr, &ElementType::Unknown)
+                }
+                StatementType::ForLoop(_) => {}
+            }
+            infer::bind(self.source, stmt, &mut self.vars);
+            if let StatementType::ForLoop(for_loop) = &stmt.statement {
+                self.statements(&for_loop.statements);
+            }
+        }
+    }
+
+    /// Object literal keys of `AddN`/`AddE`/`AddV` bodies are writes on the created type
+    fn created_fields(
+        &mut self,
+        owner: &ElementType,
+        fields: &Option<HashMap<String, ValueType>>,
+        loc: &Loc,
+    ) {
+        for name in fields.iter().flat_map(|fields| fields.keys()) {
+            self.push(owner, name, loc);
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression, context: &ElementType) {
+        match &expr.expr {
+            ExpressionType::Traversal(traversal) => {
+                self.traversal(traversal, context);
+            }
+            ExpressionType::AddNode(add) => {
+                if let Some(t) = &add.node_type {
+                    self.created_fields(&ElementType::Node(t.clone()), &add.fields, &expr.loc);
+                }
+            }
+            ExpressionType::AddEdge(add) => {
+                if let Some(t) = &add.edge_type {
+                    self.created_fields(&ElementType::Edge(t.clone()), &add.fields, &expr.loc);
+                }
+            }
+            ExpressionType::AddVector(add) => {
+                if let Some(t) = &add.vector_type {
+                    self.created_fields(&ElementType::Vector(t.clone()), &add.fields, &expr.loc);
+                }
+            }
+            ExpressionType::SearchVector(sv) => {
+                if let (Some(t), Some(pre_filter)) = (&sv.vector_type, &sv.pre_filter) {
+                    self.expression(pre_filter, &ElementType::Vector(t.clone()));
+                }
+            }
+            ExpressionType::ArrayLiteral(items)
+            | ExpressionType::And(items)
+            | ExpressionType::Or(items) => {
+                for item in items {
+                    self.expression(item, context);
+                }
+            }
+            ExpressionType::Exists(exists) => self.expression(&exists.expr, context),
+            ExpressionType::Not(inner) => self.expression(inner, context),
+            ExpressionType::MathFunctionCall(call) => {
+                for arg in &call.args {
+                    self.expression(arg, context);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn field_additions(
+        &mut self,
+        owner: &ElementType,
+        fields: &[FieldAddition],
+        access: FieldAccess,
+    ) {
+        for field in fields {
+            match &field.value.value {
+                // `{name}` and `{alias: name}` read `name` from the element unless it is a variable
+                FieldValueType::Identifier(name)
+                    if access == FieldAccess::Read && !self.vars.contains_key(name) =>
+                {
+                    self.push(owner, name, &field.loc)
+                }
+                FieldValueType::Traversal(traversal) => {
+                    self.traversal(traversal, owner);
+                }
+                FieldValueType::Expression(expr) => self.expression(expr, owner),
+                FieldValueType::Fields(nested) => {
+                    self.field_additions(owner, nested, FieldAccess::Read)
+                }
+                _ => {}
+            }
+            if access == FieldAccess::Write {
+                self.push(owner, &field.key, &field.loc);
+            }
+        }
+    }
+
+    fn return_value(&mut self, ret: &ReturnType) {
+        match ret {
+            ReturnType::Array(items) => {
+                for item in items {
+                    self.return_value(item);
+                }
+            }
+            ReturnType::Object(fields) => {
+                for value in fields.values() {
+                    self.return_value(value);
+                }
+            }
+            ReturnType::Expression(expr) => self.expression(expr, &ElementType::Unknown),
+            ReturnType::Empty => {}
+        }
+    }
+
+    /// Record field references in a traversal and return the element type after each step
+    fn traversal(&mut self, traversal: &Traversal, context: &ElementType) -> Vec<ElementType> {
+        let types = infer::traversal_types(self.source, traversal, &self.vars, context);
+
+        match &traversal.start {
+            StartNode::Node { ids, .. }
+            | StartNode::Edge { ids, .. }
+            | StartNode::Vector { ids, .. } => {
+                for id in ids.iter().flatten() {
+                    if let IdType::ByIndex { index, .. } = id {
+                        if let IdType::Identifier { value, loc } = index.as_ref() {
+                            self.push(&types[0], value, loc);
+                        }
+                    }
+                }
+            }
+            StartNode::SearchVector(sv) => {
+                if let Some(pre_filter) = &sv.pre_filter {
+                    self.expression(pre_filter, &types[0]);
+                }
+            }
+            _ => {}
+        }
+
+        for (i, step) in traversal.steps.iter().enumerate() {
+            let current = types[i].clone();
+            match &step.step {
+                StepType::Object(object) => {
+                    self.field_additions(&current, &object.fields, FieldAccess::Read)
+                }
+                StepType::Closure(closure) => {
+                    self.vars
+                        .insert(closure.identifier.clone(), current.clone());
+                    self.field_additions(&current, &closure.object.fields, FieldAccess::Read);
+                }
+                StepType::Exclude(exclude) => {
+                    for (loc, name) in &exclude.fields {
+                        self.push(&current, name, loc);
+                    }
+                }
+                StepType::Update(update) => {
+                    self.field_additions(&current, &update.fields, FieldAccess::Write)
+                }
+                StepType::UpsertN(upsert) => {
+                    self.field_additions(&current, &upsert.fields, FieldAccess::Write)
+                }
+                StepType::UpsertE(upsert) => {
+                    self.field_additions(&current, &upsert.fields, FieldAccess::Write)
+                }
+                StepType::UpsertV(upsert) => {
+                    self.field_additions(&current, &upsert.fields, FieldAccess::Write)
+                }
+                StepType::AddEdge(add) => {
+                    if let Some(t) = &add.edge_type {
+                        self.created_fields(&ElementType::Edge(t.clone()), &add.fields, &step.loc);
+                    }
+                }
+                StepType::Where(expr) => self.expression(expr, &current),
+                StepType::OrderBy(order_by) => self.expression(&order_by.expression, &current),
+                StepType::Range((start, end)) => {
+                    self.expression(start, &current);
+                    self.expression(end, &current);
+                }
+                StepType::Aggregate(aggregate) => {
+                    for name in &aggregate.properties {
+                        self.push(&current, name, &step.loc);
+                    }
+                }
+                StepType::GroupBy(group_by) => {
+                    for name in &group_by.properties {
+                        self.push(&current, name, &step.loc);
+                    }
+                }
+                StepType::BooleanOperation(op) => match &op.op {
+                    BooleanOpType::And(exprs) | BooleanOpType::Or(exprs) => {
+                        for expr in exprs {
+                            self.expression(expr, &current);
+                        }
+                    }
+                    BooleanOpType::GreaterThan(expr)
+                    | BooleanOpType::GreaterThanOrEqual(expr)
+                    | BooleanOpType::LessThan(expr)
+                    | BooleanOpType::LessThanOrEqual(expr)
+                    | BooleanOpType::Equal(expr)
+                    | BooleanOpType::NotEqual(expr)
+                    | BooleanOpType::Contains(expr)
+                    | BooleanOpType::IsIn(expr) => self.expression(expr, &current),
+                },
+                StepType::Node(graph_step) | StepType::Edge(graph_step) => {
+                    // Path weights are evaluated against the edges being traversed
+                    let (type_arg, weight) = match &graph_step.step {
+                        GraphStepType::ShortestPathDijkstras(sp) => {
+                            (&sp.type_arg, sp.weight_expression.as_deref())
+                        }
+                        GraphStepType::ShortestPathAStar(sp) => {
+                            (&sp.type_arg, sp.weight_expression.as_deref())
+                        }
+                        _ => (&None, None),
+                    };
+                    if let (Some(edge), Some(weight)) = (type_arg, weight) {
+                        self.expression(weight, &ElementType::Edge(edge.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        types
+    }
+}
+
+/// Every schema field a query reads or writes
+pub fn query_field_refs(source: &Source, query: &Query) -> Vec<FieldRef> {
+    let mut walker = FieldWalker {
+        source,
+        vars: infer::parameters(query),
+        refs: Vec::new(),
+    };
+    walker.statements(&query.statements);
+    for ret in &query.return_values {
+        walker.return_value(ret);
+    }
+    walker.refs
+}
+
+/// Element types whose `name` field is referenced on `line` (1-based) of `query`.
+/// Locations are coarse, so the narrowest constructs on that line win; when none
+/// cover it, every owner of `name` in the query is returned.
+pub fn owners_at(source: &Source, query: &Query, name: &str, line: usize) -> Vec<ElementType> {
+    let refs: Vec<FieldRef> = query_field_refs(source, query)
+        .into_iter()
+        .filter(|field_ref| field_ref.name == name)
+        .collect();
+    let covering: Vec<&FieldRef> = refs
+        .iter()
+        .filter(|r| r.loc.start.line <= line && line <= r.loc.end.line)
+        .collect();
+    let candidates: Vec<&FieldRef> = match covering
+        .iter()
+        .map(|r| r.loc.end.line - r.loc.start.line)
+        .min()
+    {
+        Some(narrowest) => covering
+            .into_iter()
+            .filter(|r| r.loc.end.line - r.loc.start.line == narrowest)
+            .collect(),
+        None => refs.iter().collect(),
+    };
+
+    let mut owners: Vec<ElementType> = Vec::new();
+    for field_ref in candidates {
+        if !owners.contains(&field_ref.owner) {
+            owners.push(field_ref.owner.clone());
+        }
+    }
+    owners
+}
+
+/// Hover text for a field of `owner`: its type, index status, default and the
+/// schema version declaring it
+pub fn describe(source: &Source, owner: &ElementType, name: &str) -> Option<String> {
+    let item = owner.schema_item()?;
+    if let Some((version, field)) = infer::field_declaration(source, owner, name) {
+        let index = match field.prefix {
+            FieldPrefix::Index => "`INDEX`",
+            FieldPrefix::UniqueIndex => "`UNIQUE INDEX`",
+            _ => "not indexed",
+        };
+        let mut text = format!(
+            "**{}**: {} (from {})\n\n- Index: {}",
+            name,
+            crate::Backend::field_type_to_string(&field.field_type),
+            item,
+            index
+        );
+        if let Some(default) = field
+            .defaults
+            .as_ref()
+            .filter(|d| **d != DefaultValue::Empty)
+        {
+            text.push_str(&format!(
+                "\n- Default: `{}`",
+                migration::default_literal(default)
+            ));
+        }
+        text.push_str(&format!("\n- Declared in `schema::{}`", version));
+        return Some(text);
+    }
+    let ty = infer::builtin_field_type(owner, name)?;
+    Some(format!(
+        "**{}**: {} (from {})\n\n- Built-in property",
+        name,
+        crate::Backend::field_type_to_string(&ty),
+        item
+    ))
+}
@@ -0,0 +1,275 @@
+use pest::Parser;
+use tower_lsp::lsp_types::{FormattingOptions, TextEdit};
+
+use crate::syntax::{HqlParser, LineIndex, Rule, ITEM_STARTS};
+
+/// Line width used when a folder sets no `formatWidth`
+pub const DEFAULT_WIDTH: usize = 100;
+
+/// What the formatter needs to know about one line
+struct Scan {
+    /// Closing brackets before anything else on the line
+    leading_closers: usize,
+    /// Bracket depth at the end of the line, relative to its start
+    depth_change: i32,
+    /// Offsets of `::` outside any bracket and string, where a long traversal can be broken
+    splits: Vec<usize>,
+    /// Whether the line has `=>` outside all brackets, which starts a query body
+    arrow: bool,
+    /// Whether a `//` comment ends the line
+    comment: bool,
+    /// Whether the line ends inside a string literal
+    in_string: bool,
+}
+
+fn scan(line: &str, depth: usize, mut in_string: bool) -> Scan {
+    let bytes = line.as_bytes();
+    let mut scan = Scan {
+        leading_closers: 0,
+        depth_change: 0,
+        splits: Vec::new(),
+        arrow: false,
+        comment: false,
+        in_string,
+    };
+    let mut leading = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        i += 1;
+        if in_string {
+            // Strings have no escapes, so the next quote always ends them
+            in_string = b != b'"';
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'/' if bytes.get(i) == Some(&b'/') => {
+                scan.comment = true;
+                break;
+            }
+            b'{' | b'(' | b'[' => scan.depth_change += 1,
+            b'}' | b')' | b']' => {
+                scan.depth_change -= 1;
+                if leading {
+                    scan.leading_closers += 1;
+                }
+                continue;
+            }
+            b':' if bytes.get(i) == Some(&b':') => {
+                if scan.depth_change == 0 && i > 1 {
+                    scan.splits.push(i - 1);
+                }
+                i += 1;
+            }
+            b'=' if bytes.get(i) == Some(&b'>') => {
+                scan.arrow |= depth as i32 + scan.depth_change == 0;
+            }
+            _ => {}
+        }
+        leading &= b.is_ascii_whitespace();
+    }
+    scan.in_string = in_string;
+    scan
+}
+
+/// Close the brackets a line closes and open the ones it opens at its `level`
+fn nest(open: &mut Vec<usize>, scan: &Scan, level: usize) {
+    open.truncate(open.len().saturating_sub(scan.leading_closers));
+    let change = scan.depth_change + scan.leading_closers as i32;
+    if change < 0 {
+        open.truncate(open.len().saturating_sub(change.unsigned_abs() as usize));
+    } else {
+        open.extend(std::iter::repeat_n(level, change as usize));
+    }
+}
+
+/// Re-indent `text` by nesting, one level per bracket and one for query bodies and
+/// continued traversals, and break traversals longer than `width` before their `::`
+/// steps. Blank lines are collapsed and trailing whitespace removed.
+///
+/// Only whitespace outside strings changes. Returns `None` when `text` does not parse,
+/// so half-typed documents are left alone.
+pub fn format(text: &str, width: usize, options: &FormattingOptions) -> Option<String> {
+    HqlParser::parse(Rule::source, text).ok()?;
+    let unit = if options.insert_spaces {
+        " ".repeat(options.tab_size as usize)
+    } else {
+        "\t".to_string()
+    };
+    let unit_width = options.tab_size as usize;
+
+    let mut out = String::new();
+    // Indent level of the line that opened each unclosed bracket
+    let mut open: Vec<usize> = Vec::new();
+    let mut in_query = false;
+    let mut in_body = false;
+    // Bracket depth and level of the last statement line, and of the statement whose
+    // traversal continues on `::` lines
+    let mut statement = (0, 0);
+    let mut chain: Option<(usize, usize)> = None;
+    let mut in_string = false;
+    let mut blank = false;
+    for raw in text.lines() {
+        if in_string {
+            // The line continues a string literal, so it is kept as written
+            let scan = scan(raw, open.len(), true);
+            let level = open.last().map_or(0, |level| level + 1);
+            nest(&mut open, &scan, level);
+            in_string = scan.in_string;
+            out.push_str(raw);
+            out.push('\n');
+            continue;
+        }
+        let line = raw.trim_start();
+        if line.trim_end().is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+
+        let scan = scan(line, open.len(), false);
+        // Trailing whitespace of a line that opens a string belongs to the string
+        let line = if scan.in_string {
+            line
+        } else {
+            line.trim_end()
+        };
+        let depth = open.len().saturating_sub(scan.leading_closers);
+        let mut level = match (scan.leading_closers, depth.checked_sub(1)) {
+            (0, Some(inner)) => open[inner] + 1,
+            (0, None) => usize::from(in_body),
+            _ => open.get(depth).copied().unwrap_or_default(),
+        };
+        if depth == 0 && ITEM_STARTS.iter().any(|start| line.starts_with(start)) {
+            in_query = line.starts_with("QUERY") || line.starts_with("#[");
+            in_body = false;
+            chain = None;
+            level = 0;
+        }
+        if line.starts_with("::") {
+            let (_, start) = *chain.get_or_insert(statement);
+            level = start + 1;
+        } else if !line.starts_with("//") {
+            if chain.is_some_and(|(start, _)| depth <= start) {
+                chain = None;
+            }
+            if scan.leading_closers == 0 {
+                statement = (depth, level);
+            }
+        }
+        let indent = unit.repeat(level);
+
+        let long = level * unit_width + line.chars().count() > width;
+        if in_body && long && !scan.comment && !scan.in_string && !scan.splits.is_empty() {
+            let continued = match chain {
+                Some(_) => level,
+                None => level + 1,
+            };
+            let continued_indent = unit.repeat(continued);
+            let mut start = 0;
+            for &split in &scan.splits {
+                out.push_str(if start == 0 {
+                    &indent
+                } else {
+                    &continued_indent
+                });
+                out.push_str(line[start..split].trim_end());
+                out.push('\n');
+                start = split;
+            }
+            out.push_str(&continued_indent);
+            out.push_str(&line[start..]);
+            chain.get_or_insert((depth, continued - 1));
+            level = continued;
+        } else {
+            out.push_str(&indent);
+            out.push_str(line);
+        }
+        out.push('\n');
+
+        nest(&mut open, &scan, level);
+        in_string = scan.in_string;
+        in_body |= in_query && scan.arrow;
+    }
+
+    HqlParser::parse(Rule::source, &out).ok()?;
+    Some(out)
+}
+
+/// Edit replacing the whole document with its formatted text, if that differs
+pub fn edits(text: &str, width: usize, options: &FormattingOptions) -> Vec<TextEdit> {
+    match format(text, width, options) {
+        Some(formatted) if formatted != text => vec![TextEdit {
+            range: LineIndex::new(text).range(0, text.len()),
+            new_text: formatted,
+        }],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(text: &str, width: usize) -> Option<String> {
+        let options = FormattingOptions {
+            tab_size: 4,
+            insert_spaces: true,
+            ..Default::default()
+        };
+        format(text, width, &options)
+    }
+
+    #[test]
+    fn indents_schema_bodies_and_queries() {
+        let text = "N::User {\nname: String,\n      INDEX email: String\n}\n\n\n\nE::Follows {\n  From: User,\n  To: User,\n  Properties: {\nsince: Date\n  }\n}\nQUERY GetUser(id: ID) =>\nuser <- N<User>(id)   \n        RETURN user\n";
+        let expected = "N::User {\n    name: String,\n    INDEX email: String\n}\n\nE::Follows {\n    From: User,\n    To: User,\n    Properties: {\n        since: Date\n    }\n}\nQUERY GetUser(id: ID) =>\n    user <- N<User>(id)\n    RETURN user\n";
+        assert_eq!(fmt(text, 100).unwrap(), expected);
+    }
+
+    #[test]
+    fn indents_loops_parameters_and_objects() {
+        let text = "QUERY AddUsers(\nusers: [{name: String}],\nlabel: String\n) =>\nFOR {name} IN users {\nAddN<User>({\nname: name,\n})\n}\nRETURN {\nlabel: label\n}\n";
+        let expected = "QUERY AddUsers(\n    users: [{name: String}],\n    label: String\n) =>\n    FOR {name} IN users {\n        AddN<User>({\n            name: name,\n        })\n    }\n    RETURN {\n        label: label\n    }\n";
+        assert_eq!(fmt(text, 100).unwrap(), expected);
+    }
+
+    #[test]
+    fn breaks_long_traversals_before_steps() {
+        let text = "QUERY Top(min: I32) =>\n    users <- N<User>::WHERE(_::{age}::GT(min))::ORDER<Desc>(_::{age})::RANGE(0, 10)\n    RETURN users\n";
+        let expected = "QUERY Top(min: I32) =>\n    users <- N<User>\n        ::WHERE(_::{age}::GT(min))\n        ::ORDER<Desc>(_::{age})\n        ::RANGE(0, 10)\n    RETURN users\n";
+        let formatted = fmt(text, 60).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(fmt(&formatted, 60).unwrap(), expected);
+        assert_eq!(fmt(text, 100).unwrap(), text);
+    }
+
+    #[test]
+    fn indents_brackets_opened_on_continued_lines() {
+        let text = "QUERY Rename(id: ID, name: String) =>\nuser <- N<User>(id)\n::UPDATE({\nname: name\n})\nRETURN user\n";
+        let expected = "QUERY Rename(id: ID, name: String) =>\n    user <- N<User>(id)\n        ::UPDATE({\n            name: name\n        })\n    RETURN user\n";
+        assert_eq!(fmt(text, 100).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_comments_and_strings() {
+        let text = "// Users\nN::User {\n// display name\n  name: String, // shown in lists\n}\n#[mcp]\nQUERY Find() =>\n  users <- N<User>::WHERE(_::{name}::EQ(\"a  //  b\"))\n  RETURN users\n";
+        let expected = "// Users\nN::User {\n    // display name\n    name: String, // shown in lists\n}\n#[mcp]\nQUERY Find() =>\n    users <- N<User>::WHERE(_::{name}::EQ(\"a  //  b\"))\n    RETURN users\n";
+        assert_eq!(
+            fmt(text, 20).unwrap(),
+            expected.replace(
+                "users <- N<User>::WHERE",
+                "users <- N<User>\n        ::WHERE"
+            )
+        );
+    }
+
+    #[test]
+    fn leaves_documents_with_syntax_errors_alone() {
+        assert_eq!(fmt("QUERY Broken( =>\n  RETURN x\n", 100), None);
+    }
+}
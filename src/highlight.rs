@@ -0,0 +1,138 @@
+use pest::iterators::Pair;
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind, Position};
+
+use crate::syntax::{self, LineIndex, Rule};
+
+/// What an identifier token refers to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Symbol {
+    /// A parameter or variable, scoped to the top-level item it appears in
+    Variable(usize),
+    /// A field or property. Tokens carry no owner type, so fields match by name.
+    Field,
+    /// A node, edge or vector type
+    Type,
+}
+
+#[derive(Debug)]
+struct Token<'a> {
+    start: usize,
+    end: usize,
+    name: &'a str,
+    symbol: Symbol,
+    kind: DocumentHighlightKind,
+}
+
+/// Symbol and access of an `identifier` from the rule it appears in; `index`
+/// is its position among that rule's children
+fn classify(
+    parent: Rule,
+    grandparent: Option<Rule>,
+    index: usize,
+    item: usize,
+) -> Option<(Symbol, DocumentHighlightKind)> {
+    use DocumentHighlightKind as Kind;
+    let variable = Symbol::Variable(item);
+    Some(match parent {
+        // Query names and output keys are not symbols we track
+        Rule::query_def | Rule::object_inner | Rule::model_name => return None,
+        Rule::param_type => (Symbol::Type, Kind::READ),
+        Rule::param_def => (variable, Kind::TEXT),
+        Rule::get_stmt | Rule::for_argument | Rule::object_destructuring | Rule::closure_step => {
+            (variable, Kind::WRITE)
+        }
+        Rule::object_access if index == 0 => (variable, Kind::WRITE),
+        Rule::object_access => (Symbol::Field, Kind::READ),
+        Rule::field_def => (Symbol::Field, Kind::TEXT),
+        Rule::update_field | Rule::new_field => (Symbol::Field, Kind::WRITE),
+        Rule::mapping_field | Rule::exclude_field | Rule::aggregate | Rule::group_by => {
+            (Symbol::Field, Kind::READ)
+        }
+        Rule::field_migration if index == 0 => (Symbol::Field, Kind::TEXT),
+        Rule::field_migration => (Symbol::Field, Kind::READ),
+        Rule::id_arg if grandparent == Some(Rule::by_index) => (Symbol::Field, Kind::READ),
+        _ => (variable, Kind::READ),
+    })
+}
+
+/// Collect the identifier tokens below `pair`, classified by the rules around them
+fn collect<'a>(
+    pair: Pair<'a, Rule>,
+    parent: Option<Rule>,
+    base: usize,
+    item: usize,
+    tokens: &mut Vec<Token<'a>>,
+) {
+    let rule = pair.as_rule();
+    for (index, child) in pair.into_inner().enumerate() {
+        let span = child.as_span();
+        let classified = match child.as_rule() {
+            Rule::identifier => classify(rule, parent, index, item),
+            Rule::identifier_upper => Some(match rule {
+                Rule::node_def | Rule::edge_def | Rule::vector_def => {
+                    (Symbol::Type, DocumentHighlightKind::TEXT)
+                }
+                _ => (Symbol::Type, DocumentHighlightKind::READ),
+            }),
+            _ => {
+                collect(child, Some(rule), base, item, tokens);
+                continue;
+            }
+        };
+        if let Some((symbol, kind)) = classified {
+            tokens.push(Token {
+                start: base + span.start(),
+                end: base + span.end(),
+                name: span.as_str(),
+                symbol,
+                kind,
+            });
+        }
+    }
+}
+
+/// Classified identifier tokens of every item that parses
+fn tokens(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for (item, (base, pairs)) in syntax::parse_items(text).into_iter().enumerate() {
+        for pair in pairs {
+            collect(pair, None, base, item, &mut tokens);
+        }
+    }
+    tokens
+}
+
+/// Name of the field or property the cursor is on, at a use site in a query
+pub fn field_at(text: &str, position: Position) -> Option<&str> {
+    let cursor = LineIndex::new(text).offset(position);
+    tokens(text)
+        .into_iter()
+        .find(|token| token.start <= cursor && cursor <= token.end)
+        .filter(|token| token.symbol == Symbol::Field && token.kind != DocumentHighlightKind::TEXT)
+        .map(|token| token.name)
+}
+
+/// Every occurrence of the variable, parameter, field or type under the cursor,
+/// with writes (assignments, loop and closure bindings, `UPDATE` and creation
+/// fields) told apart from reads. Declarations are plain text highlights.
+pub fn document_highlights(text: &str, position: Position) -> Vec<DocumentHighlight> {
+    let index = LineIndex::new(text);
+    let cursor = index.offset(position);
+
+    let tokens = tokens(text);
+    let Some(target) = tokens
+        .iter()
+        .find(|token| token.start <= cursor && cursor <= token.end)
+    else {
+        return Vec::new();
+    };
+
+    tokens
+        .iter()
+        .filter(|token| token.symbol == target.symbol && token.name == target.name)
+        .map(|token| DocumentHighlight {
+            range: index.range(token.start, token.end),
+            kind: Some(token.kind),
+        })
+        .collect()
+}
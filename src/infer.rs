@@ -0,0 +1,548 @@
+use std::collections::HashMap;
+
+use helix_db::helixc::parser::types::{
+    EdgeSchema, Expression, ExpressionType, Field, FieldType, FieldValueType, ForLoopVars,
+    GraphStepType, Loc, NodeSchema, Query, Source, StartNode, Statement, StatementType, Step,
+    StepType, Traversal, VectorSchema,
+};
+
+use crate::walk::{self, Visitor};
+
+/// The kind of value a variable or traversal position holds
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementType {
+    Node(String),
+    Edge(String),
+    Vector(String),
+    Scalar(FieldType),
+    Unknown,
+}
+
+impl ElementType {
+    /// Schema item this element belongs to, as written in schema files (`N::User`)
+    pub fn schema_item(&self) -> Option<String> {
+        match self {
+            ElementType::Node(name) => Some(format!("N::{}", name)),
+            ElementType::Edge(name) => Some(format!("E::{}", name)),
+            ElementType::Vector(name) => Some(format!("V::{}", name)),
+            _ => None,
+        }
+    }
+
+    /// Type as shown to users: `Node<User>`, `Edge<Follows>`, `String`
+    pub fn label(&self) -> String {
+        match self {
+            ElementType::Node(name) => format!("Node<{}>", name),
+            ElementType::Edge(name) => format!("Edge<{}>", name),
+            ElementType::Vector(name) => format!("Vector<{}>", name),
+            ElementType::Scalar(ty) => type_label(ty),
+            ElementType::Unknown => "unknown".to_string(),
+        }
+    }
+}
+
+/// A scalar type as shown in hovers and messages. Unlike in schema listings, objects
+/// such as the groups `GROUP_BY` returns show their keys: `{count: U64, name: String}`
+pub fn type_label(ty: &FieldType) -> String {
+    match ty {
+        FieldType::Array(inner) => format!("[{}]", type_label(inner)),
+        FieldType::Object(fields) => {
+            let mut fields: Vec<_> = fields
+                .iter()
+                .map(|(name, ty)| format!("{}: {}", name, type_label(ty)))
+                .collect();
+            fields.sort();
+            format!("{{{}}}", fields.join(", "))
+        }
+        ty => crate::Backend::field_type_to_string(ty),
+    }
+}
+
+/// Variable name to inferred type, in scope at some point of a query
+pub type Vars = HashMap<String, ElementType>;
+
+/// Schema versions from newest to oldest, so lookups resolve against the latest definition
+fn schemas_newest_first(source: &Source) -> Vec<&helix_db::helixc::parser::types::Schema> {
+    let mut versions: Vec<_> = source.schema.iter().collect();
+    versions.sort_by(|a, b| b.0.cmp(a.0));
+    versions.into_iter().map(|(_, schema)| schema).collect()
+}
+
+pub fn node_schema<'a>(source: &'a Source, name: &str) -> Option<&'a NodeSchema> {
+    schemas_newest_first(source)
+        .into_iter()
+        .find_map(|schema| schema.node_schemas.iter().find(|n| n.name.1 == name))
+}
+
+pub fn edge_schema<'a>(source: &'a Source, name: &str) -> Option<&'a EdgeSchema> {
+    schemas_newest_first(source)
+        .into_iter()
+        .find_map(|schema| schema.edge_schemas.iter().find(|e| e.name.1 == name))
+}
+
+pub fn vector_schema<'a>(source: &'a Source, name: &str) -> Option<&'a VectorSchema> {
+    schemas_newest_first(source)
+        .into_iter()
+        .find_map(|schema| schema.vector_schemas.iter().find(|v| v.name == name))
+}
+
+/// The newest declaration of field `name` on `owner`, with the schema version declaring it
+pub fn field_declaration<'a>(
+    source: &'a Source,
+    owner: &ElementType,
+    name: &str,
+) -> Option<(usize, &'a Field)> {
+    let mut versions: Vec<_> = source.schema.iter().collect();
+    versions.sort_by(|a, b| b.0.cmp(a.0));
+    versions.into_iter().find_map(|(version, schema)| {
+        let fields = match owner {
+            ElementType::Node(t) => &schema.node_schemas.iter().find(|n| n.name.1 == *t)?.fields,
+            ElementType::Edge(t) => schema
+                .edge_schemas
+                .iter()
+                .find(|e| e.name.1 == *t)?
+                .properties
+                .as_ref()?,
+            ElementType::Vector(t) => &schema.vector_schemas.iter().find(|v| v.name == *t)?.fields,
+            _ => return None,
+        };
+        Some((*version, fields.iter().find(|f| f.name == name)?))
+    })
+}
+
+/// Whether values of `ty` can be used in arithmetic, weights and ordering by magnitude
+pub fn is_numeric(ty: &FieldType) -> bool {
+    matches!(
+        ty,
+        FieldType::F32
+            | FieldType::F64
+            | FieldType::I8
+            | FieldType::I16
+            | FieldType::I32
+            | FieldType::I64
+            | FieldType::U8
+            | FieldType::U16
+            | FieldType::U32
+            | FieldType::U64
+            | FieldType::U128
+    )
+}
+
+/// Properties every element has without declaring them
+pub fn builtin_field_type(owner: &ElementType, name: &str) -> Option<FieldType> {
+    Some(match (owner, name) {
+        (_, "id") => FieldType::Uuid,
+        (_, "label") => FieldType::String,
+        (ElementType::Edge(_), "from_node" | "to_node") => FieldType::Uuid,
+        (ElementType::Vector(_), "data") => FieldType::Array(Box::new(FieldType::F64)),
+        (ElementType::Vector(_), "score") => FieldType::F64,
+        _ => return None,
+    })
+}
+
+/// Type of property `name` of `owner`, declared or built in. For objects, such as
+/// the groups `GROUP_BY` returns, this is the type of key `name`.
+pub fn property_type(source: &Source, owner: &ElementType, name: &str) -> Option<FieldType> {
+    if let ElementType::Scalar(FieldType::Object(fields)) = owner {
+        return fields.get(name).cloned();
+    }
+    field_declaration(source, owner, name)
+        .map(|(_, field)| field.field_type.clone())
+        .or_else(|| builtin_field_type(owner, name))
+}
+
+/// Whether results can be sorted by values of `ty`; arrays and objects have no order
+pub fn is_orderable(ty: &FieldType) -> bool {
+    !matches!(
+        ty,
+        FieldType::Array(_) | FieldType::Object(_) | FieldType::Identifier(_)
+    )
+}
+
+/// Object each group of `GROUP_BY(properties)` is returned as: the key properties and
+/// a `count`. With `elements`, as for `AGGREGATE_BY`, the grouped elements are
+/// included under `data`. Properties `owner` does not have are left out.
+pub fn group_shape(
+    source: &Source,
+    owner: &ElementType,
+    properties: &[String],
+    elements: bool,
+) -> FieldType {
+    let mut fields: HashMap<String, FieldType> = properties
+        .iter()
+        .filter_map(|name| Some((name.clone(), property_type(source, owner, name)?)))
+        .collect();
+    fields.insert("count".to_string(), FieldType::U64);
+    if elements {
+        let element = match owner {
+            ElementType::Node(name) | ElementType::Edge(name) | ElementType::Vector(name) => {
+                FieldType::Identifier(name.clone())
+            }
+            ElementType::Scalar(ty) => ty.clone(),
+            ElementType::Unknown => FieldType::Object(HashMap::new()),
+        };
+        fields.insert("data".to_string(), FieldType::Array(Box::new(element)));
+    }
+    FieldType::Object(fields)
+}
+
+/// Edges may point at nodes or vectors, so resolve an endpoint name to whichever exists
+fn endpoint(source: &Source, name: &str) -> ElementType {
+    if vector_schema(source, name).is_some() {
+        ElementType::Vector(name.to_string())
+    } else {
+        ElementType::Node(name.to_string())
+    }
+}
+
+/// A ShortestPath step with what its variant takes: the edge type, weight
+/// expression and heuristic property
+pub struct PathStep<'a> {
+    pub loc: &'a Loc,
+    pub edge: Option<&'a str>,
+    pub weight: Option<&'a Expression>,
+    pub heuristic: Option<&'a str>,
+}
+
+/// The pathfinding step a graph step is, if any
+pub fn path_step(step: &GraphStepType) -> Option<PathStep<'_>> {
+    Some(match step {
+        GraphStepType::ShortestPath(sp) => PathStep {
+            loc: &sp.loc,
+            edge: sp.type_arg.as_deref(),
+            weight: None,
+            heuristic: None,
+        },
+        GraphStepType::ShortestPathBFS(sp) => PathStep {
+            loc: &sp.loc,
+            edge: sp.type_arg.as_deref(),
+            weight: None,
+            heuristic: None,
+        },
+        GraphStepType::ShortestPathDijkstras(sp) => PathStep {
+            loc: &sp.loc,
+            edge: sp.type_arg.as_deref(),
+            weight: sp.weight_expression.as_deref(),
+            heuristic: None,
+        },
+        GraphStepType::ShortestPathAStar(sp) => PathStep {
+            loc: &sp.loc,
+            edge: sp.type_arg.as_deref(),
+            weight: sp.weight_expression.as_deref(),
+            heuristic: Some(&sp.heuristic_property),
+        },
+        _ => return None,
+    })
+}
+
+/// The node type an A* heuristic is read from: the `To` endpoint of the edge
+pub fn heuristic_owner(source: &Source, edge: &str) -> Option<ElementType> {
+    edge_schema(source, edge).map(|schema| endpoint(source, &schema.to.1))
+}
+
+/// Describes the ShortestPath steps written on one line
+struct PathDescriptions<'a> {
+    source: &'a Source,
+    vars: Vars,
+    line: usize,
+    lines: Vec<String>,
+}
+
+impl Visitor for PathDescriptions<'_> {
+    fn step(&mut self, step: &Step) {
+        let (StepType::Node(graph_step) | StepType::Edge(graph_step)) = &step.step else {
+            return;
+        };
+        let Some(path) = path_step(&graph_step.step) else {
+            return;
+        };
+        if !(path.loc.start.line <= self.line && self.line <= path.loc.end.line) {
+            return;
+        }
+        let Some(edge) = path.edge else {
+            return;
+        };
+        let edge_type = ElementType::Edge(edge.to_string());
+        if let Some(weight) = path.weight {
+            let ty = value_type(self.source, weight, &self.vars, &edge_type);
+            self.lines
+                .push(format!("**Weight**: `{}` per E::{} edge", ty.label(), edge));
+        }
+        if let (Some(heuristic), Some(owner)) = (path.heuristic, heuristic_owner(self.source, edge))
+        {
+            let ty = property_type(self.source, &owner, heuristic)
+                .map(ElementType::Scalar)
+                .unwrap_or(ElementType::Unknown);
+            self.lines.push(format!(
+                "**Heuristic**: `{}` of {} is `{}`",
+                heuristic,
+                owner.schema_item().unwrap_or_default(),
+                ty.label()
+            ));
+        }
+    }
+}
+
+/// Resolved weight and heuristic types of the ShortestPath steps on `line` (1-based)
+pub fn describe_paths(source: &Source, query: &Query, line: usize) -> Vec<String> {
+    let mut paths = PathDescriptions {
+        source,
+        vars: scope_at(source, query, line),
+        line,
+        lines: Vec::new(),
+    };
+    walk::walk_query(&mut paths, query);
+    paths.lines
+}
+
+/// Query parameters as the initial variable scope
+pub fn parameters(query: &Query) -> Vars {
+    query
+        .parameters
+        .iter()
+        .map(|p| {
+            (
+                p.name.1.clone(),
+                ElementType::Scalar(p.param_type.1.clone()),
+            )
+        })
+        .collect()
+}
+
+/// Record the variables a statement introduces. FOR loop bodies are not entered;
+/// callers walking nested statements bind those as they reach them.
+pub fn bind(source: &Source, stmt: &Statement, vars: &mut Vars) {
+    bind_loop(stmt, vars);
+    bind_assignment(source, stmt, vars);
+}
+
+/// Record the variable an assignment introduces. Walkers call this once the assigned
+/// value has been walked, so in `u <- u::Out<Follows>` the value still reads the earlier `u`.
+pub fn bind_assignment(source: &Source, stmt: &Statement, vars: &mut Vars) {
+    if let StatementType::Assignment(assignment) = &stmt.statement {
+        let ty = expression_type(source, &assignment.value, vars);
+        vars.insert(assignment.variable.clone(), ty);
+    }
+}
+
+/// Record the variables a FOR loop introduces, before its body is walked
+pub fn bind_loop(stmt: &Statement, vars: &mut Vars) {
+    let StatementType::ForLoop(for_loop) = &stmt.statement else {
+        return;
+    };
+    let item = match vars.get(&for_loop.in_variable.1) {
+        Some(ElementType::Scalar(FieldType::Array(inner))) => {
+            ElementType::Scalar((**inner).clone())
+        }
+        Some(other @ (ElementType::Node(_) | ElementType::Edge(_) | ElementType::Vector(_))) => {
+            other.clone()
+        }
+        _ => ElementType::Unknown,
+    };
+    match &for_loop.variable {
+        ForLoopVars::Identifier { name, .. } | ForLoopVars::ObjectAccess { name, .. } => {
+            vars.insert(name.clone(), item);
+        }
+        ForLoopVars::ObjectDestructuring { fields, .. } => {
+            for (_, field) in fields {
+                let ty = match &item {
+                    ElementType::Scalar(FieldType::Object(map)) => map
+                        .get(field)
+                        .cloned()
+                        .map(ElementType::Scalar)
+                        .unwrap_or(ElementType::Unknown),
+                    _ => ElementType::Unknown,
+                };
+                vars.insert(field.clone(), ty);
+            }
+        }
+    }
+}
+
+/// Parameters and variables in scope at `line` (1-based) of a query: everything
+/// assigned by statements ending before that line, plus the bindings of enclosing FOR loops
+pub fn scope_at(source: &Source, query: &Query, line: usize) -> Vars {
+    let mut vars = parameters(query);
+    bind_before(source, &query.statements, line, &mut vars);
+    vars
+}
+
+fn bind_before(source: &Source, statements: &[Statement], line: usize, vars: &mut Vars) {
+    for stmt in statements {
+        if stmt.loc.end.line < line {
+            // A finished loop's binding is out of scope again
+            if !matches!(stmt.statement, StatementType::ForLoop(_)) {
+                bind(source, stmt, vars);
+            }
+        } else if stmt.loc.start.line <= line {
+            if let StatementType::ForLoop(for_loop) = &stmt.statement {
+                bind(source, stmt, vars);
+                bind_before(source, &for_loop.statements, line, vars);
+            }
+        }
+    }
+}
+
+/// Infer the type an expression evaluates to
+pub fn expression_type(source: &Source, expr: &Expression, vars: &Vars) -> ElementType {
+    match &expr.expr {
+        ExpressionType::Traversal(traversal) => {
+            traversal_types(source, traversal, vars, &ElementType::Unknown)
+                .pop()
+                .unwrap_or(ElementType::Unknown)
+        }
+        ExpressionType::Identifier(name) => vars.get(name).cloned().unwrap_or(ElementType::Unknown),
+        ExpressionType::AddNode(add) => add
+            .node_type
+            .clone()
+            .map(ElementType::Node)
+            .unwrap_or(ElementType::Unknown),
+        ExpressionType::AddEdge(add) => add
+            .edge_type
+            .clone()
+            .map(ElementType::Edge)
+            .unwrap_or(ElementType::Unknown),
+        ExpressionType::AddVector(add) => add
+            .vector_type
+            .clone()
+            .map(ElementType::Vector)
+            .unwrap_or(ElementType::Unknown),
+        ExpressionType::BatchAddVector(add) => add
+            .vector_type
+            .clone()
+            .map(ElementType::Vector)
+            .unwrap_or(ElementType::Unknown),
+        ExpressionType::SearchVector(sv) => sv
+            .vector_type
+            .clone()
+            .map(ElementType::Vector)
+            .unwrap_or(ElementType::Unknown),
+        ExpressionType::BM25Search(bm25) => bm25
+            .type_arg
+            .clone()
+            .map(ElementType::Node)
+            .unwrap_or(ElementType::Unknown),
+        ExpressionType::StringLiteral(_) => ElementType::Scalar(FieldType::String),
+        ExpressionType::IntegerLiteral(_) => ElementType::Scalar(FieldType::I32),
+        ExpressionType::FloatLiteral(_) => ElementType::Scalar(FieldType::F64),
+        ExpressionType::BooleanLiteral(_)
+        | ExpressionType::Exists(_)
+        | ExpressionType::Not(_)
+        | ExpressionType::And(_)
+        | ExpressionType::Or(_) => ElementType::Scalar(FieldType::Boolean),
+        ExpressionType::MathFunctionCall(_) => ElementType::Scalar(FieldType::F64),
+        _ => ElementType::Unknown,
+    }
+}
+
+/// Scalar an expression evaluates to against `context`, the element `_` stands for:
+/// `_::{distance}` has the type of that property and `_::ToN::{lat}` the type of
+/// the node's. Other expressions are typed as by [`expression_type`].
+pub fn value_type(
+    source: &Source,
+    expr: &Expression,
+    vars: &Vars,
+    context: &ElementType,
+) -> ElementType {
+    let ExpressionType::Traversal(traversal) = &expr.expr else {
+        return expression_type(source, expr, vars);
+    };
+    let types = traversal_types(source, traversal, vars, context);
+    match traversal.steps.last().map(|step| &step.step) {
+        Some(StepType::Object(object)) if object.fields.len() == 1 => {
+            let field = &object.fields[0];
+            // `{alias: name}` reads `name`
+            let name = match &field.value.value {
+                FieldValueType::Identifier(name) => name,
+                _ => &field.key,
+            };
+            property_type(source, &types[types.len() - 2], name)
+                .map(ElementType::Scalar)
+                .unwrap_or(ElementType::Unknown)
+        }
+        _ => types.last().cloned().unwrap_or(ElementType::Unknown),
+    }
+}
+
+/// Element type at the start of a traversal and after each of its steps.
+///
+/// `anonymous` is the element an `_` traversal starts from, i.e. the element
+/// being filtered or mapped by the enclosing step.
+pub fn traversal_types(
+    source: &Source,
+    traversal: &Traversal,
+    vars: &Vars,
+    anonymous: &ElementType,
+) -> Vec<ElementType> {
+    let start = match &traversal.start {
+        StartNode::Node { node_type, .. } => ElementType::Node(node_type.clone()),
+        StartNode::Edge { edge_type, .. } => ElementType::Edge(edge_type.clone()),
+        StartNode::Vector { vector_type, .. } => ElementType::Vector(vector_type.clone()),
+        StartNode::SearchVector(sv) => sv
+            .vector_type
+            .clone()
+            .map(ElementType::Vector)
+            .unwrap_or(ElementType::Unknown),
+        StartNode::Identifier(name) => vars.get(name).cloned().unwrap_or(ElementType::Unknown),
+        StartNode::Anonymous => anonymous.clone(),
+    };
+
+    let mut types = vec![start];
+    for step in &traversal.steps {
+        let current = types.last().cloned().unwrap_or(ElementType::Unknown);
+        types.push(step_type(source, &current, step));
+    }
+    types
+}
+
+/// Element type produced by applying `step` to an element of type `current`
+pub fn step_type(source: &Source, current: &ElementType, step: &Step) -> ElementType {
+    match &step.step {
+        StepType::Node(graph_step) | StepType::Edge(graph_step) => match &graph_step.step {
+            GraphStepType::Out(edge) => edge_schema(source, edge)
+                .map(|e| endpoint(source, &e.to.1))
+                .unwrap_or(ElementType::Unknown),
+            GraphStepType::In(edge) => edge_schema(source, edge)
+                .map(|e| endpoint(source, &e.from.1))
+                .unwrap_or(ElementType::Unknown),
+            GraphStepType::OutE(edge) | GraphStepType::InE(edge) => ElementType::Edge(edge.clone()),
+            GraphStepType::FromN | GraphStepType::FromV => match current {
+                ElementType::Edge(edge) => edge_schema(source, edge)
+                    .map(|e| endpoint(source, &e.from.1))
+                    .unwrap_or(ElementType::Unknown),
+                _ => ElementType::Unknown,
+            },
+            GraphStepType::ToN | GraphStepType::ToV => match current {
+                ElementType::Edge(edge) => edge_schema(source, edge)
+                    .map(|e| endpoint(source, &e.to.1))
+                    .unwrap_or(ElementType::Unknown),
+                _ => ElementType::Unknown,
+            },
+            GraphStepType::SearchVector(sv) => sv
+                .vector_type
+                .clone()
+                .map(ElementType::Vector)
+                .unwrap_or(ElementType::Unknown),
+            _ => ElementType::Unknown,
+        },
+        StepType::AddEdge(add) => add
+            .edge_type
+            .clone()
+            .map(ElementType::Edge)
+            .unwrap_or(ElementType::Unknown),
+        StepType::SearchVector(sv) => sv
+            .vector_type
+            .clone()
+            .map(ElementType::Vector)
+            .unwrap_or(ElementType::Unknown),
+        StepType::Count => ElementType::Scalar(FieldType::U64),
+        StepType::BooleanOperation(_) => ElementType::Scalar(FieldType::Boolean),
+        StepType::GroupBy(group) => {
+            ElementType::Scalar(group_shape(source, current, &group.properties, false))
+        }
+        StepType::Aggregate(aggregate) => {
+            ElementType::Scalar(group_shape(source, current, &aggregate.properties, true))
+        }
+        _ => current.clone(),
+    }
+}
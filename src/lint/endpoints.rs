@@ -0,0 +1,198 @@
+use helix_db::helixc::parser::types::{
+    EdgeConnection, Expression, ExpressionType, GraphStepType, IdType, Loc, Query, Statement,
+    StepType, Traversal,
+};
+
+use super::{Finding, LintContext, Rule, Severity};
+use crate::infer::{self, ElementType, Vars};
+use crate::walk::{self, Visitor};
+
+pub static EDGE_ENDPOINT_TYPE: Rule = Rule {
+    code: "HQL010",
+    name: "edge-endpoint-type",
+    default_severity: Severity::Error,
+    summary: "An edge is created or searched between elements that are not its From/To types",
+};
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    for query in &ctx.source.queries {
+        check_query(ctx, query, out);
+    }
+}
+
+/// Which end of an edge an argument connects
+#[derive(Clone, Copy)]
+enum End {
+    From,
+    To,
+}
+
+impl End {
+    fn label(self) -> &'static str {
+        match self {
+            End::From => "From",
+            End::To => "To",
+        }
+    }
+}
+
+struct EndpointChecker<'a, 'b> {
+    ctx: &'a LintContext<'a>,
+    vars: Vars,
+    out: &'b mut Vec<Finding>,
+}
+
+impl EndpointChecker<'_, '_> {
+    /// Check an element of type `actual` connected at `end` of edge `edge`.
+    /// `argument` names it in the message; `loc` is where it is written.
+    fn endpoint(&mut self, edge: &str, end: End, actual: &ElementType, argument: &str, loc: &Loc) {
+        let Some(schema) = infer::edge_schema(self.ctx.source, edge) else {
+            return;
+        };
+        let expected = match end {
+            End::From => &schema.from,
+            End::To => &schema.to,
+        };
+        let matches = match actual {
+            ElementType::Node(name) | ElementType::Vector(name) => *name == expected.1,
+            ElementType::Edge(_) => false,
+            // IDs and values of unknown type could be any element
+            ElementType::Scalar(_) | ElementType::Unknown => return,
+        };
+        if matches {
+            return;
+        }
+        let mut finding = Finding::new(
+            &EDGE_ENDPOINT_TYPE,
+            loc,
+            format!(
+                "{} of E::{} expects a `{}`, but {} is {}",
+                end.label(),
+                edge,
+                expected.1,
+                argument,
+                actual.label()
+            ),
+        );
+        finding.related.push((
+            expected.0.clone(),
+            format!("{} of E::{} is declared here", end.label(), edge),
+        ));
+        self.out.push(finding);
+    }
+
+    fn id(&mut self, edge: &str, end: End, id: &IdType) {
+        if let IdType::Identifier { value, loc } = id {
+            let actual = self
+                .vars
+                .get(value)
+                .cloned()
+                .unwrap_or(ElementType::Unknown);
+            self.endpoint(edge, end, &actual, &format!("`{}`", value), loc);
+        }
+    }
+
+    /// Check the `From`/`To` arguments of a connection. A missing `From` is the
+    /// element the step is applied to, when there is one.
+    fn connection(
+        &mut self,
+        edge: &str,
+        from: Option<&IdType>,
+        to: Option<&IdType>,
+        current: Option<(&ElementType, &Loc)>,
+    ) {
+        match (from, current) {
+            (Some(from), _) => self.id(edge, End::From, from),
+            (None, Some((current, loc))) => {
+                self.endpoint(edge, End::From, current, "the traversal source", loc)
+            }
+            (None, None) => {}
+        }
+        if let Some(to) = to {
+            self.id(edge, End::To, to);
+        }
+    }
+
+    fn edge_connection(&mut self, edge: &str, connection: &EdgeConnection) {
+        self.connection(
+            edge,
+            connection.from_id.as_ref(),
+            connection.to_id.as_ref(),
+            None,
+        );
+    }
+}
+
+impl Visitor for EndpointChecker<'_, '_> {
+    // FOR loop variables are bound before the loop body is walked, assignments only
+    // after their value is, so a rebinding such as `u <- u::Out<Follows>` reads the old `u`
+    fn statement(&mut self, stmt: &Statement) {
+        infer::bind_loop(stmt, &mut self.vars);
+    }
+
+    fn statement_end(&mut self, stmt: &Statement) {
+        infer::bind_assignment(self.ctx.source, stmt, &mut self.vars);
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        if let ExpressionType::AddEdge(add) = &expr.expr {
+            if let Some(edge) = &add.edge_type {
+                self.edge_connection(edge, &add.connection);
+            }
+        }
+    }
+
+    fn traversal(&mut self, traversal: &Traversal) {
+        let types = infer::traversal_types(
+            self.ctx.source,
+            traversal,
+            &self.vars,
+            &ElementType::Unknown,
+        );
+        for (i, step) in traversal.steps.iter().enumerate() {
+            let current = Some((&types[i], &step.loc));
+            match &step.step {
+                StepType::AddEdge(add) => {
+                    if let Some(edge) = &add.edge_type {
+                        self.connection(
+                            edge,
+                            add.connection.from_id.as_ref(),
+                            add.connection.to_id.as_ref(),
+                            current,
+                        );
+                    }
+                }
+                // Upserts apply to an `E<T>` traversal, which names the edge type
+                StepType::UpsertE(upsert) => {
+                    if let ElementType::Edge(edge) = &types[i] {
+                        self.edge_connection(edge, &upsert.connection);
+                    }
+                }
+                StepType::Node(graph_step) | StepType::Edge(graph_step) => {
+                    let (edge, from, to) = match &graph_step.step {
+                        GraphStepType::ShortestPath(sp) => (&sp.type_arg, &sp.from, &sp.to),
+                        GraphStepType::ShortestPathBFS(sp) => (&sp.type_arg, &sp.from, &sp.to),
+                        GraphStepType::ShortestPathDijkstras(sp) => {
+                            (&sp.type_arg, &sp.from, &sp.to)
+                        }
+                        GraphStepType::ShortestPathAStar(sp) => (&sp.type_arg, &sp.from, &sp.to),
+                        _ => continue,
+                    };
+                    if let Some(edge) = edge {
+                        self.connection(edge, from.as_ref(), to.as_ref(), current);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn check_query(ctx: &LintContext, query: &Query, out: &mut Vec<Finding>) {
+    let mut checker = EndpointChecker {
+        ctx,
+        vars: infer::parameters(query),
+        out,
+    };
+    walk::walk_query(&mut checker, query);
+}
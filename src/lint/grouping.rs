@@ -0,0 +1,154 @@
+use helix_db::helixc::parser::types::{
+    Expression, ExpressionType, FieldType, FieldValueType, Loc, OrderBy, StartNode, Statement,
+    StepType, Traversal,
+};
+
+use super::{find_loc, Finding, LintContext, Rule, Severity};
+use crate::infer::{self, ElementType, Vars};
+use crate::walk::{self, Visitor};
+
+pub static UNKNOWN_GROUP_PROPERTY: Rule = Rule {
+    code: "HQL023",
+    name: "unknown-group-property",
+    default_severity: Severity::Error,
+    summary: "AGGREGATE_BY, GROUP_BY or ORDER uses a property the element type does not have",
+};
+
+pub static UNORDERABLE_PROPERTY: Rule = Rule {
+    code: "HQL024",
+    name: "unorderable-property",
+    default_severity: Severity::Error,
+    summary: "ORDER sorts by an array or object, which has no order",
+};
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    for query in &ctx.source.queries {
+        let mut checker = GroupingChecker {
+            ctx,
+            vars: infer::parameters(query),
+            out,
+        };
+        walk::walk_query(&mut checker, query);
+    }
+}
+
+struct GroupingChecker<'a, 'b> {
+    ctx: &'a LintContext<'a>,
+    vars: Vars,
+    out: &'b mut Vec<Finding>,
+}
+
+impl GroupingChecker<'_, '_> {
+    /// Report `name`, used by `step`, unless it is a property of `owner`
+    fn property(&mut self, owner: &ElementType, name: &str, step: &str, loc: &Loc) {
+        let Some(item) = describe(owner) else {
+            return;
+        };
+        if infer::property_type(self.ctx.source, owner, name).is_none() {
+            self.out.push(Finding::new(
+                &UNKNOWN_GROUP_PROPERTY,
+                loc,
+                format!(
+                    "{} uses `{}`, which is not a property of {}",
+                    step, name, item
+                ),
+            ));
+        }
+    }
+
+    /// `ORDER<Asc|Desc>(_::{field})` sorts by `field` of the ordered elements
+    fn order(&mut self, owner: &ElementType, order: &OrderBy) {
+        let expr = &order.expression;
+        if let ExpressionType::Traversal(traversal) = &expr.expr {
+            if matches!(traversal.start, StartNode::Anonymous) {
+                self.order_traversal(owner, traversal, expr);
+                return;
+            }
+        }
+        if let ElementType::Scalar(ty) = infer::value_type(self.ctx.source, expr, &self.vars, owner)
+        {
+            self.orderable(&ty, "This value", &expr.loc);
+        }
+    }
+
+    fn order_traversal(&mut self, owner: &ElementType, traversal: &Traversal, expr: &Expression) {
+        let types = infer::traversal_types(self.ctx.source, traversal, &self.vars, owner);
+        let Some(StepType::Object(object)) = traversal.steps.last().map(|s| &s.step) else {
+            if let Some(ElementType::Scalar(ty)) = types.last() {
+                self.orderable(ty, "This value", &expr.loc);
+            }
+            return;
+        };
+        let owner = &types[types.len() - 2];
+        for field in &object.fields {
+            // `{alias: name}` reads `name`
+            let name = match &field.value.value {
+                FieldValueType::Identifier(name) => name,
+                _ => &field.key,
+            };
+            match infer::property_type(self.ctx.source, owner, name) {
+                Some(ty) => self.orderable(&ty, &format!("`{}`", name), &field.loc),
+                None => self.property(owner, name, "ORDER", &field.loc),
+            }
+        }
+    }
+
+    fn orderable(&mut self, ty: &FieldType, what: &str, loc: &Loc) {
+        if !infer::is_orderable(ty) {
+            self.out.push(Finding::new(
+                &UNORDERABLE_PROPERTY,
+                loc,
+                format!(
+                    "{} is {}, which cannot be ordered; order by a scalar property",
+                    what,
+                    infer::type_label(ty)
+                ),
+            ));
+        }
+    }
+}
+
+/// How `owner` is named in messages, or `None` when its properties are not known
+fn describe(owner: &ElementType) -> Option<String> {
+    match owner {
+        ElementType::Scalar(FieldType::Object(_)) => Some("the grouped results".to_string()),
+        _ => owner.schema_item(),
+    }
+}
+
+impl Visitor for GroupingChecker<'_, '_> {
+    fn statement(&mut self, stmt: &Statement) {
+        infer::bind_loop(stmt, &mut self.vars);
+    }
+
+    fn statement_end(&mut self, stmt: &Statement) {
+        infer::bind_assignment(self.ctx.source, stmt, &mut self.vars);
+    }
+
+    fn traversal(&mut self, traversal: &Traversal) {
+        let types = infer::traversal_types(
+            self.ctx.source,
+            traversal,
+            &self.vars,
+            &ElementType::Unknown,
+        );
+        for (i, step) in traversal.steps.iter().enumerate() {
+            match &step.step {
+                StepType::GroupBy(group) => {
+                    for name in &group.properties {
+                        let loc = find_loc(&group.loc, name);
+                        self.property(&types[i], name, "GROUP_BY", &loc);
+                    }
+                }
+                StepType::Aggregate(aggregate) => {
+                    for name in &aggregate.properties {
+                        let loc = find_loc(&aggregate.loc, name);
+                        self.property(&types[i], name, "AGGREGATE_BY", &loc);
+                    }
+                }
+                StepType::OrderBy(order) => self.order(&types[i], order),
+                _ => {}
+            }
+        }
+    }
+}
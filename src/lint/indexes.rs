@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+
+use helix_db::helixc::parser::types::{
+    Expression, ExpressionType, FieldPrefix, IdType, Loc, Source, StartNode, Statement, StepType,
+    Traversal,
+};
+
+use super::{Finding, Fix, LintContext, Rule, Severity};
+use crate::infer::{self, ElementType, Vars};
+use crate::walk::{self, Visitor};
+
+pub static UNINDEXED_LOOKUP: Rule = Rule {
+    code: "HQL013",
+    name: "unindexed-lookup",
+    default_severity: Severity::Error,
+    summary: "A by-index lookup such as N<T>({field: value}) uses a field that is not INDEX",
+};
+
+pub static NON_UNIQUE_LOOKUP: Rule = Rule {
+    code: "HQL014",
+    name: "non-unique-lookup",
+    default_severity: Severity::Warning,
+    summary: "A by-index lookup returns one element, but its index is not UNIQUE",
+};
+
+pub static UNUSED_INDEX: Rule = Rule {
+    code: "HQL015",
+    name: "unused-index",
+    default_severity: Severity::Hint,
+    summary: "An indexed field is never looked up or filtered on by any query in the project",
+};
+
+/// A by-index lookup: the element searched, the field and where the lookup is written
+struct Lookup {
+    owner: ElementType,
+    field: String,
+    loc: Loc,
+}
+
+/// Collects by-index lookups, and fields that lookups or `WHERE` filters may
+/// serve from an index, across every query
+struct IndexUses<'a> {
+    source: &'a Source,
+    vars: Vars,
+    lookups: Vec<Lookup>,
+    /// `(schema item, field)` pairs an index can serve
+    used: HashSet<(String, String)>,
+}
+
+impl IndexUses<'_> {
+    /// Record the fields a `WHERE` condition reads from `owner`, e.g. `age` in `_::{age}::GT(18)`
+    fn filter(&mut self, owner: &ElementType, expr: &Expression) {
+        let Some(item) = owner.schema_item() else {
+            return;
+        };
+        match &expr.expr {
+            ExpressionType::Traversal(traversal)
+                if matches!(traversal.start, StartNode::Anonymous) =>
+            {
+                if let Some(StepType::Object(object)) = traversal.steps.first().map(|s| &s.step) {
+                    for field in &object.fields {
+                        self.used.insert((item.clone(), field.key.clone()));
+                    }
+                }
+            }
+            ExpressionType::And(items) | ExpressionType::Or(items) => {
+                for part in items {
+                    self.filter(owner, part);
+                }
+            }
+            ExpressionType::Not(inner) => self.filter(owner, inner),
+            _ => {}
+        }
+    }
+}
+
+impl Visitor for IndexUses<'_> {
+    fn statement(&mut self, stmt: &Statement) {
+        infer::bind_loop(stmt, &mut self.vars);
+    }
+
+    fn statement_end(&mut self, stmt: &Statement) {
+        infer::bind_assignment(self.source, stmt, &mut self.vars);
+    }
+
+    fn traversal(&mut self, traversal: &Traversal) {
+        let types =
+            infer::traversal_types(self.source, traversal, &self.vars, &ElementType::Unknown);
+
+        if let StartNode::Node { ids, .. }
+        | StartNode::Edge { ids, .. }
+        | StartNode::Vector { ids, .. } = &traversal.start
+        {
+            for id in ids.iter().flatten() {
+                if let IdType::ByIndex { index, loc, .. } = id {
+                    if let IdType::Identifier { value, .. } = index.as_ref() {
+                        if let Some(item) = types[0].schema_item() {
+                            self.used.insert((item, value.clone()));
+                        }
+                        self.lookups.push(Lookup {
+                            owner: types[0].clone(),
+                            field: value.clone(),
+                            loc: loc.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        for (i, step) in traversal.steps.iter().enumerate() {
+            if let StepType::Where(expr) = &step.step {
+                self.filter(&types[i], expr);
+            }
+        }
+    }
+}
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    let source = ctx.source;
+    let mut uses = IndexUses {
+        source,
+        vars: Vars::new(),
+        lookups: Vec::new(),
+        used: HashSet::new(),
+    };
+    for query in &source.queries {
+        uses.vars = infer::parameters(query);
+        walk::walk_query(&mut uses, query);
+    }
+
+    for lookup in &uses.lookups {
+        let (Some(item), Some((_, field))) = (
+            lookup.owner.schema_item(),
+            infer::field_declaration(source, &lookup.owner, &lookup.field),
+        ) else {
+            continue;
+        };
+        match field.prefix {
+            FieldPrefix::UniqueIndex => {}
+            FieldPrefix::Index => out.push(
+                Finding::new(
+                    &NON_UNIQUE_LOOKUP,
+                    &lookup.loc,
+                    format!(
+                        "`{}` of {} is not UNIQUE, so this lookup returns one of possibly many matches",
+                        lookup.field, item
+                    ),
+                )
+                .with_fix(Fix::insert_before(
+                    format!("Make the index on `{}` UNIQUE", lookup.field),
+                    &field.loc,
+                    "UNIQUE ",
+                )),
+            ),
+            _ => {
+                let mut finding = Finding::new(
+                    &UNINDEXED_LOOKUP,
+                    &lookup.loc,
+                    format!(
+                        "`{}` of {} is not indexed; declare it `INDEX` to look it up",
+                        lookup.field, item
+                    ),
+                )
+                .with_fix(Fix::insert_before(
+                    format!("Add INDEX to `{}`", lookup.field),
+                    &field.loc,
+                    "INDEX ",
+                ));
+                finding
+                    .related
+                    .push((field.loc.clone(), format!("`{}` is declared here", lookup.field)));
+                out.push(finding);
+            }
+        }
+    }
+
+    check_unused_indexes(ctx, &uses.used, out);
+}
+
+/// Indexed fields of the latest schema that no lookup or filter can use.
+/// Like the other usage rules, a schema-only project reports nothing.
+fn check_unused_indexes(
+    ctx: &LintContext,
+    used: &HashSet<(String, String)>,
+    out: &mut Vec<Finding>,
+) {
+    let source = ctx.source;
+    if source.queries.is_empty() {
+        return;
+    }
+    let Some(schema) = source
+        .schema
+        .keys()
+        .max()
+        .and_then(|v| source.schema.get(v))
+    else {
+        return;
+    };
+
+    let items = schema
+        .node_schemas
+        .iter()
+        .map(|n| (format!("N::{}", n.name.1), &n.fields))
+        .chain(
+            schema
+                .edge_schemas
+                .iter()
+                .filter_map(|e| Some((format!("E::{}", e.name.1), e.properties.as_ref()?))),
+        )
+        .chain(
+            schema
+                .vector_schemas
+                .iter()
+                .map(|v| (format!("V::{}", v.name), &v.fields)),
+        );
+    for (item, fields) in items {
+        for field in fields.iter().filter(|f| f.is_indexed()) {
+            if !used.contains(&(item.clone(), field.name.clone())) {
+                out.push(Finding::new(
+                    &UNUSED_INDEX,
+                    &field.loc,
+                    format!(
+                        "`{}` of {} is indexed, but no query looks it up or filters on it",
+                        field.name, item
+                    ),
+                ));
+            }
+        }
+    }
+}
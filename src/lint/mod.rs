@@ -0,0 +1,453 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{DiagnosticSeverity, Range, TextDocumentIdentifier, Url};
+
+use helix_db::helixc::parser::types::{Loc, Source, Span};
+
+use crate::syntax::LineIndex;
+
+mod endpoints;
+mod grouping;
+mod indexes;
+mod optional;
+mod paths;
+mod rules;
+mod schema;
+mod unused;
+mod values;
+mod vectors;
+
+/// Name of the per-project config file, looked up next to the .hx files
+pub const CONFIG_FILE: &str = "helixql.json";
+
+/// Where diagnostic codes link to unless a project sets `docsBaseUrl`
+pub const DOCS_BASE: &str = "https://docs.helix-db.com/lsp";
+
+/// Severity a rule is reported with. `Off` disables the rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Hint,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn to_lsp(self) -> Option<DiagnosticSeverity> {
+        match self {
+            Severity::Off => None,
+            Severity::Hint => Some(DiagnosticSeverity::HINT),
+            Severity::Info => Some(DiagnosticSeverity::INFORMATION),
+            Severity::Warning => Some(DiagnosticSeverity::WARNING),
+            Severity::Error => Some(DiagnosticSeverity::ERROR),
+        }
+    }
+}
+
+/// Static description of a lint rule
+#[derive(Debug)]
+pub struct Rule {
+    /// Stable code shown in diagnostics, e.g. `HQL001`
+    pub code: &'static str,
+    /// Kebab-case name, usable instead of the code in config
+    pub name: &'static str,
+    pub default_severity: Severity,
+    pub summary: &'static str,
+}
+
+impl Rule {
+    /// Reference page of the rule under the docs site at `base`
+    pub fn doc_url(&self, base: &str) -> String {
+        format!("{}/lints#{}", base.trim_end_matches('/'), self.name)
+    }
+}
+
+static RULES: &[&Rule] = &[
+    &rules::UNINDEXED_SCAN,
+    &rules::SEARCH_K_TOO_LARGE,
+    &rules::UNUSED_VARIABLE,
+    &rules::UNRETURNED_ASSIGNMENT,
+    &rules::DUPLICATE_QUERY,
+    &rules::MCP_MUTATION,
+    &unused::UNUSED_PARAMETER,
+    &unused::UNUSED_TYPE,
+    &unused::UNUSED_EDGE_PROPERTY,
+    &endpoints::EDGE_ENDPOINT_TYPE,
+    &values::VALUE_TYPE,
+    &values::READ_ONLY_FIELD,
+    &indexes::UNINDEXED_LOOKUP,
+    &indexes::NON_UNIQUE_LOOKUP,
+    &indexes::UNUSED_INDEX,
+    &paths::PATH_EDGE_TYPE,
+    &paths::PATH_WEIGHT,
+    &vectors::VECTOR_DIMENSION,
+    &vectors::EMBED_WITHOUT_MODEL,
+    &vectors::SEARCH_K_TYPE,
+    &vectors::BATCH_VECTORS,
+    &vectors::MMR_DISTANCE,
+    &grouping::UNKNOWN_GROUP_PROPERTY,
+    &grouping::UNORDERABLE_PROPERTY,
+    &optional::OPTIONAL_PARAMETER,
+    &schema::DUPLICATE_TYPE,
+    &schema::DUPLICATE_FIELD,
+    &schema::BUILTIN_FIELD,
+    &schema::UNKNOWN_ENDPOINT,
+    &schema::UNIQUE_INDEX_TYPE,
+    &schema::UNKNOWN_PARAMETER_TYPE,
+    &schema::EMPTY_SCHEMA,
+];
+
+/// Every registered rule, in code order
+pub fn all_rules() -> &'static [&'static Rule] {
+    RULES
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintRulesParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// One entry of the `helixql/lintRules` response
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleInfo {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub doc_url: String,
+    pub default_severity: Severity,
+    /// Severity after applying the project's overrides
+    pub severity: Severity,
+}
+
+/// Describe every rule with the severity it has under `config`
+pub fn describe_rules(config: &LintConfig) -> Vec<RuleInfo> {
+    all_rules()
+        .iter()
+        .map(|rule| RuleInfo {
+            code: rule.code,
+            name: rule.name,
+            summary: rule.summary,
+            doc_url: rule.doc_url(config.docs_base()),
+            default_severity: rule.default_severity,
+            severity: config.severity(rule),
+        })
+        .collect()
+}
+
+/// An edit that resolves a finding
+#[derive(Debug, Clone)]
+pub struct Fix {
+    pub title: String,
+    pub loc: Loc,
+    pub new_text: String,
+}
+
+impl Fix {
+    /// Delete the lines `loc` spans, including the trailing newline
+    pub fn remove_lines(title: impl Into<String>, loc: &Loc) -> Self {
+        let mut loc = loc.clone();
+        loc.start = Span {
+            line: loc.start.line,
+            column: 1,
+        };
+        loc.end = Span {
+            line: loc.end.line + 1,
+            column: 1,
+        };
+        Self {
+            title: title.into(),
+            loc,
+            new_text: String::new(),
+        }
+    }
+
+    /// Delete item `index` of a comma-separated list whose items are at `items`, along
+    /// with the comma and whitespace separating it from the next item, or from the
+    /// previous one when it is last
+    pub fn remove_item(title: impl Into<String>, items: &[&Loc], index: usize) -> Self {
+        let mut loc = items[index].clone();
+        if let Some(next) = items.get(index + 1) {
+            loc.end = next.start.clone();
+        } else if let Some(previous) = index.checked_sub(1).map(|i| items[i]) {
+            loc.start = previous.end.clone();
+        }
+        Self {
+            title: title.into(),
+            loc,
+            new_text: String::new(),
+        }
+    }
+
+    /// Replace the text `loc` covers with `text`
+    pub fn replace(title: impl Into<String>, loc: &Loc, text: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            loc: loc.clone(),
+            new_text: text.into(),
+        }
+    }
+
+    /// Insert `text` where `loc` starts
+    pub fn insert_before(title: impl Into<String>, loc: &Loc, text: impl Into<String>) -> Self {
+        let mut loc = loc.clone();
+        loc.end = loc.start.clone();
+        Self {
+            title: title.into(),
+            loc,
+            new_text: text.into(),
+        }
+    }
+}
+
+/// The first occurrence of `text` within `loc` that is not part of a longer identifier,
+/// or all of `loc` when the parser kept no text for it or `text` does not occur
+pub fn find_loc(loc: &Loc, text: &str) -> Loc {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let Some(offset) = loc.span.match_indices(text).map(|(i, _)| i).find(|&i| {
+        let before = loc.span[..i].chars().next_back();
+        let after = loc.span[i + text.len()..].chars().next();
+        !(text.starts_with(is_word) && before.is_some_and(is_word)
+            || text.ends_with(is_word) && after.is_some_and(is_word))
+    }) else {
+        return loc.clone();
+    };
+    let mut found = loc.clone();
+    found.start = span_at(loc, offset);
+    found.end = span_at(loc, offset + text.len());
+    found.span = text.to_string();
+    found
+}
+
+/// Line and column of byte `offset` of the text `loc` covers. Columns are counted in
+/// UTF-16 code units, like the LSP positions they become.
+fn span_at(loc: &Loc, offset: usize) -> Span {
+    let position = LineIndex::new(&loc.span).position(offset);
+    let column = position.character as usize;
+    match position.line {
+        0 => Span {
+            line: loc.start.line,
+            column: loc.start.column + column,
+        },
+        line => Span {
+            line: loc.start.line + line as usize,
+            column: column + 1,
+        },
+    }
+}
+
+/// Fix as carried in a diagnostic's `data`, so code actions can apply it without re-linting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixData {
+    pub title: String,
+    pub range: Range,
+    pub new_text: String,
+    /// File the edit applies to, when it is not the one the diagnostic is in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<Url>,
+}
+
+/// A single problem found by a rule
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub rule: &'static Rule,
+    pub loc: Loc,
+    pub message: String,
+    /// Other locations that explain the finding, with a short message each
+    pub related: Vec<(Loc, String)>,
+    /// Dead code; reported with the `Unnecessary` tag so editors fade it out
+    pub unnecessary: bool,
+    pub fix: Option<Fix>,
+}
+
+impl Finding {
+    pub fn new(rule: &'static Rule, loc: &Loc, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            loc: loc.clone(),
+            message: message.into(),
+            related: Vec::new(),
+            unnecessary: false,
+            fix: None,
+        }
+    }
+
+    pub fn unnecessary(mut self) -> Self {
+        self.unnecessary = true;
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// Lint settings from `helixql.json`:
+///
+/// ```json
+/// {
+///   "lints": { "HQL001": "off", "unused-variable": "error" },
+///   "searchKThreshold": 200,
+///   "docsBaseUrl": "https://docs.example.com/helixql"
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintConfig {
+    /// Severity overrides keyed by rule code or rule name
+    #[serde(default)]
+    pub lints: HashMap<String, Severity>,
+    /// Largest literal `k` a `SearchV` may use before HQL002 fires
+    pub search_k_threshold: Option<u64>,
+    /// Docs site that diagnostic codes link to, for mirrors or unpublished versions
+    pub docs_base_url: Option<String>,
+}
+
+impl LintConfig {
+    /// Read the project config next to the .hx files, falling back to defaults
+    pub fn load(dir: &Path) -> Self {
+        fs::read_to_string(dir.join(CONFIG_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn docs_base(&self) -> &str {
+        self.docs_base_url.as_deref().unwrap_or(DOCS_BASE)
+    }
+
+    pub fn severity(&self, rule: &Rule) -> Severity {
+        self.lints
+            .get(rule.code)
+            .or_else(|| self.lints.get(rule.name))
+            .copied()
+            .unwrap_or(rule.default_severity)
+    }
+}
+
+/// Shared inputs for every rule
+pub struct LintContext<'a> {
+    pub source: &'a Source,
+    pub config: &'a LintConfig,
+}
+
+/// Run all enabled rules, returning each finding with its effective severity
+pub fn run(source: &Source, config: &LintConfig) -> Vec<(Finding, Severity)> {
+    let ctx = LintContext { source, config };
+    let mut findings = Vec::new();
+    rules::check(&ctx, &mut findings);
+    unused::check(&ctx, &mut findings);
+    endpoints::check(&ctx, &mut findings);
+    values::check(&ctx, &mut findings);
+    indexes::check(&ctx, &mut findings);
+    paths::check(&ctx, &mut findings);
+    vectors::check(&ctx, &mut findings);
+    grouping::check(&ctx, &mut findings);
+    optional::check(&ctx, &mut findings);
+    schema::check(&ctx, &mut findings);
+
+    findings
+        .into_iter()
+        .filter_map(|finding| {
+            let severity = config.severity(finding.rule);
+            (severity != Severity::Off).then_some((finding, severity))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loc(line: usize, column: usize, span: &str) -> Loc {
+        Loc {
+            filepath: None,
+            start: Span { line, column },
+            end: Span { line, column },
+            span: span.to_string(),
+        }
+    }
+
+    fn position(span: &Span) -> (usize, usize) {
+        (span.line, span.column)
+    }
+
+    #[test]
+    fn span_at_counts_columns_on_the_first_line_from_the_start() {
+        let loc = loc(3, 5, "N<User>(id)");
+        assert_eq!(position(&span_at(&loc, 0)), (3, 5));
+        assert_eq!(position(&span_at(&loc, 2)), (3, 7));
+        assert_eq!(position(&span_at(&loc, 11)), (3, 16));
+    }
+
+    #[test]
+    fn span_at_restarts_columns_after_each_newline() {
+        let loc = loc(3, 5, "N<User>\n  ::Out<Follows>\n  ::ID");
+        assert_eq!(position(&span_at(&loc, 8)), (4, 1));
+        assert_eq!(position(&span_at(&loc, 16)), (4, 9));
+        assert_eq!(position(&span_at(&loc, 29)), (5, 5));
+    }
+
+    #[test]
+    fn span_at_counts_utf16_columns() {
+        let loc = loc(2, 3, "\"héllo 🦀\"::ID\n  ::{é}");
+        assert_eq!(position(&span_at(&loc, 8)), (2, 10));
+        assert_eq!(position(&span_at(&loc, 13)), (2, 13));
+        let found = find_loc(&loc, "ID");
+        assert_eq!(position(&found.start), (2, 15));
+        assert_eq!(position(&found.end), (2, 17));
+        assert_eq!(position(&span_at(&loc, 25)), (3, 7));
+    }
+
+    #[test]
+    fn find_loc_narrows_to_the_text() {
+        let found = find_loc(&loc(3, 5, "N<User>\n  ::Out<Follows>"), "Follows");
+        assert_eq!(position(&found.start), (4, 9));
+        assert_eq!(position(&found.end), (4, 16));
+        assert_eq!(found.span, "Follows");
+    }
+
+    #[test]
+    fn find_loc_skips_matches_inside_identifiers() {
+        let found = find_loc(&loc(1, 1, "ShortestPath<Path>"), "Path");
+        assert_eq!(position(&found.start), (1, 14));
+        let found = find_loc(&loc(1, 1, "name_id: String,\n  id: ID"), "id");
+        assert_eq!(position(&found.start), (2, 3));
+        let found = find_loc(&loc(1, 1, "UNIQUE INDEX name: String"), "UNIQUE ");
+        assert_eq!(position(&found.start), (1, 1));
+    }
+
+    #[test]
+    fn remove_item_takes_the_separating_comma() {
+        let item = |column: usize, span: &str| {
+            let mut item = loc(1, column, span);
+            item.end.column = column + span.len();
+            item
+        };
+        // QUERY Q(a: ID, b: String, c: I32) =>
+        let (a, b, c) = (item(9, "a: ID"), item(16, "b: String"), item(27, "c: I32"));
+        let items = [&a, &b, &c];
+        let range = |fix: Fix| (position(&fix.loc.start), position(&fix.loc.end));
+        assert_eq!(range(Fix::remove_item("", &items, 0)), ((1, 9), (1, 16)));
+        assert_eq!(range(Fix::remove_item("", &items, 1)), ((1, 16), (1, 27)));
+        assert_eq!(range(Fix::remove_item("", &items, 2)), ((1, 25), (1, 33)));
+        assert_eq!(range(Fix::remove_item("", &[&b], 0)), ((1, 16), (1, 25)));
+    }
+
+    #[test]
+    fn find_loc_falls_back_to_the_whole_loc() {
+        for (span, text) in [("N<User>", "Post"), ("N<User>", "Use"), ("", "User")] {
+            let found = find_loc(&loc(2, 4, span), text);
+            assert_eq!(position(&found.start), (2, 4));
+            assert_eq!(found.span, span);
+        }
+    }
+}
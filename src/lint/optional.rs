@@ -0,0 +1,484 @@
+use std::collections::HashMap;
+
+use helix_db::helixc::parser::types::{
+    BooleanOpType, EdgeConnection, Expression, ExpressionType, IdType, Loc, Query, StartNode,
+    Statement, StatementType, Step, StepType, Traversal, ValueType,
+};
+
+use super::{Finding, LintContext, Rule, Severity};
+use crate::diagnostics::within;
+use crate::infer::{self, ElementType};
+use crate::walk::{self, Visitor};
+
+pub static OPTIONAL_PARAMETER: Rule = Rule {
+    code: "HQL025",
+    name: "optional-parameter",
+    default_severity: Severity::Warning,
+    summary: "An optional parameter is used where a value is required without checking it first",
+};
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    for query in &ctx.source.queries {
+        let mut checker = OptionalChecker {
+            ctx,
+            optional: optional_parameters(query),
+            guards: Vec::new(),
+            out,
+        };
+        if checker.optional.is_empty() {
+            continue;
+        }
+        walk::walk_query(&mut checker, query);
+    }
+}
+
+/// Optional parameters of `query`, with where each is declared
+fn optional_parameters(query: &Query) -> HashMap<String, Loc> {
+    query
+        .parameters
+        .iter()
+        .filter(|p| p.is_optional)
+        .map(|p| (p.name.1.clone(), p.name.0.clone()))
+        .collect()
+}
+
+/// Follows optional parameters through a query. Names assigned from one stay
+/// optional; a guard such as `EXISTS(name)` or `name::NEQ(NONE)` in an `AND`
+/// protects the conditions after it, and `name::EQ(NONE)` or `!EXISTS(name)` in
+/// an `OR` protects the alternatives after it.
+struct OptionalChecker<'a, 'b> {
+    ctx: &'a LintContext<'a>,
+    /// Names that may be NONE, with the declaration of the parameter they come from
+    optional: HashMap<String, Loc>,
+    /// Regions of the query in which a name is known to hold a value
+    guards: Vec<(String, Loc)>,
+    out: &'b mut Vec<Finding>,
+}
+
+impl OptionalChecker<'_, '_> {
+    /// Report `name` if it may be NONE at `loc`, where `what` needs a value
+    fn require(&mut self, name: &str, loc: &Loc, what: &str) {
+        let Some(declared) = self.optional.get(name) else {
+            return;
+        };
+        if self
+            .guards
+            .iter()
+            .any(|(guarded, region)| guarded == name && within(loc, region))
+        {
+            return;
+        }
+        let mut finding = Finding::new(
+            &OPTIONAL_PARAMETER,
+            loc,
+            format!(
+                "{} needs a value, but `{}` is optional; guard it with EXISTS({}) or {}::NEQ(NONE)",
+                what, name, name, name
+            ),
+        );
+        finding.related.push((
+            declared.clone(),
+            format!("`{}` is declared optional here", name),
+        ));
+        self.out.push(finding);
+    }
+
+    fn id(&mut self, id: &IdType, what: &str) {
+        match id {
+            IdType::Identifier { value, loc } => self.require(value, loc, what),
+            IdType::ByIndex { value, .. } => {
+                if let ValueType::Identifier { value, loc } = value.as_ref() {
+                    self.require(value, loc, what);
+                }
+            }
+            IdType::Literal { .. } => {}
+        }
+    }
+
+    fn connection(&mut self, connection: &EdgeConnection) {
+        for id in connection.from_id.iter().chain(connection.to_id.iter()) {
+            self.id(id, "The edge endpoint");
+        }
+    }
+
+    /// Fields of `AddN`, `AddE` and `AddV`; fields with a `DEFAULT` may be left out
+    fn fields(&mut self, owner: ElementType, fields: Option<&HashMap<String, ValueType>>) {
+        for (key, value) in fields.into_iter().flatten() {
+            let ValueType::Identifier { value, loc } = value else {
+                continue;
+            };
+            let has_default = infer::field_declaration(self.ctx.source, &owner, key)
+                .is_some_and(|(_, field)| field.defaults.is_some());
+            if !has_default {
+                self.require(value, loc, &format!("Field `{}`", key));
+            }
+        }
+    }
+
+    /// Record the regions `AND` and `OR` conditions guard
+    fn conditions(&mut self, items: &[Expression], conjunction: bool) {
+        for (i, item) in items.iter().enumerate() {
+            let Some((name, present)) = presence(item) else {
+                continue;
+            };
+            // AND goes on while the guard holds, OR while it fails
+            if present == conjunction && self.optional.contains_key(name) {
+                for later in &items[i + 1..] {
+                    self.guards.push((name.to_string(), later.loc.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// The name a condition checks for NONE, and whether the condition holds when it has a value
+fn presence(expr: &Expression) -> Option<(&str, bool)> {
+    match &expr.expr {
+        ExpressionType::Exists(exists) => match &exists.expr.expr {
+            ExpressionType::Identifier(name) => Some((name, true)),
+            ExpressionType::Traversal(traversal) if traversal.steps.is_empty() => {
+                match &traversal.start {
+                    StartNode::Identifier(name) => Some((name, true)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        ExpressionType::Not(inner) => presence(inner).map(|(name, present)| (name, !present)),
+        ExpressionType::Traversal(traversal) => {
+            let (StartNode::Identifier(name), [step]) = (&traversal.start, &traversal.steps[..])
+            else {
+                return None;
+            };
+            match &step.step {
+                StepType::BooleanOperation(op) => match &op.op {
+                    BooleanOpType::Equal(value) if is_none(value) => Some((name, false)),
+                    BooleanOpType::NotEqual(value) if is_none(value) => Some((name, true)),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_none(expr: &Expression) -> bool {
+    matches!(expr.expr, ExpressionType::Empty)
+}
+
+impl Visitor for OptionalChecker<'_, '_> {
+    fn statement_end(&mut self, stmt: &Statement) {
+        // `alias <- name` carries the NONE along; any other assignment replaces it.
+        // Applied after the value is walked, so `name <- name::...` still checks `name`.
+        if let StatementType::Assignment(assignment) = &stmt.statement {
+            let from = match &assignment.value.expr {
+                ExpressionType::Identifier(name) => self.optional.get(name).cloned(),
+                _ => None,
+            };
+            match from {
+                Some(declared) => {
+                    self.optional.insert(assignment.variable.clone(), declared);
+                }
+                None => {
+                    self.optional.remove(&assignment.variable);
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        match &expr.expr {
+            ExpressionType::And(items) => self.conditions(items, true),
+            ExpressionType::Or(items) => self.conditions(items, false),
+            ExpressionType::AddNode(add) => {
+                if let Some(node_type) = &add.node_type {
+                    self.fields(ElementType::Node(node_type.clone()), add.fields.as_ref());
+                }
+            }
+            ExpressionType::AddEdge(add) => {
+                self.connection(&add.connection);
+                if let Some(edge_type) = &add.edge_type {
+                    self.fields(ElementType::Edge(edge_type.clone()), add.fields.as_ref());
+                }
+            }
+            ExpressionType::AddVector(add) => {
+                if let Some(vector_type) = &add.vector_type {
+                    self.fields(
+                        ElementType::Vector(vector_type.clone()),
+                        add.fields.as_ref(),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn traversal(&mut self, traversal: &Traversal) {
+        if let StartNode::Node { ids, .. }
+        | StartNode::Edge { ids, .. }
+        | StartNode::Vector { ids, .. } = &traversal.start
+        {
+            for id in ids.iter().flatten() {
+                self.id(id, "This lookup");
+            }
+        }
+    }
+
+    fn step(&mut self, step: &Step) {
+        match &step.step {
+            StepType::BooleanOperation(op) => match &op.op {
+                BooleanOpType::And(items) => self.conditions(items, true),
+                BooleanOpType::Or(items) => self.conditions(items, false),
+                BooleanOpType::GreaterThan(value)
+                | BooleanOpType::GreaterThanOrEqual(value)
+                | BooleanOpType::LessThan(value)
+                | BooleanOpType::LessThanOrEqual(value)
+                | BooleanOpType::Equal(value)
+                | BooleanOpType::NotEqual(value)
+                | BooleanOpType::Contains(value)
+                | BooleanOpType::IsIn(value) => {
+                    if let ExpressionType::Identifier(name) = &value.expr {
+                        self.require(name, &value.loc, "This comparison");
+                    }
+                }
+            },
+            StepType::Range((start, end)) => {
+                for bound in [start, end] {
+                    if let ExpressionType::Identifier(name) = &bound.expr {
+                        self.require(name, &bound.loc, "RANGE");
+                    }
+                }
+            }
+            StepType::AddEdge(add) => {
+                self.connection(&add.connection);
+                if let Some(edge_type) = &add.edge_type {
+                    self.fields(ElementType::Edge(edge_type.clone()), add.fields.as_ref());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_text;
+    use crate::lint::LintConfig;
+    use helix_db::helixc::parser::types::{BooleanOp, ExistsExpression, Source, Span};
+
+    /// Location covering one line
+    fn line(line: usize) -> Loc {
+        Loc {
+            filepath: None,
+            start: Span { line, column: 1 },
+            end: Span { line, column: 80 },
+            span: String::new(),
+        }
+    }
+
+    fn loc() -> Loc {
+        line(1)
+    }
+
+    fn expr(expr: ExpressionType) -> Expression {
+        Expression { loc: loc(), expr }
+    }
+
+    fn exists(inner: ExpressionType) -> Expression {
+        expr(ExpressionType::Exists(ExistsExpression {
+            loc: loc(),
+            expr: Box::new(expr(inner)),
+        }))
+    }
+
+    fn identifier(name: &str) -> ExpressionType {
+        ExpressionType::Identifier(name.to_string())
+    }
+
+    /// `name` followed by `steps`, e.g. `name::EQ(NONE)`
+    fn traversal(name: &str, steps: Vec<BooleanOpType>) -> ExpressionType {
+        ExpressionType::Traversal(Box::new(Traversal {
+            start: StartNode::Identifier(name.to_string()),
+            steps: steps
+                .into_iter()
+                .map(|op| Step {
+                    loc: loc(),
+                    step: StepType::BooleanOperation(BooleanOp { loc: loc(), op }),
+                })
+                .collect(),
+            loc: loc(),
+        }))
+    }
+
+    fn none() -> Box<Expression> {
+        Box::new(expr(ExpressionType::Empty))
+    }
+
+    #[test]
+    fn presence_recognizes_exists_and_none_comparisons() {
+        assert_eq!(presence(&exists(identifier("a"))), Some(("a", true)));
+        assert_eq!(
+            presence(&exists(traversal("a", Vec::new()))),
+            Some(("a", true))
+        );
+        assert_eq!(
+            presence(&expr(ExpressionType::Not(Box::new(exists(identifier(
+                "a"
+            )))))),
+            Some(("a", false))
+        );
+        assert_eq!(
+            presence(&expr(traversal("a", vec![BooleanOpType::NotEqual(none())]))),
+            Some(("a", true))
+        );
+        assert_eq!(
+            presence(&expr(traversal("a", vec![BooleanOpType::Equal(none())]))),
+            Some(("a", false))
+        );
+    }
+
+    #[test]
+    fn presence_ignores_other_conditions() {
+        assert_eq!(presence(&expr(identifier("a"))), None);
+        let value = Box::new(expr(identifier("b")));
+        assert_eq!(
+            presence(&expr(traversal("a", vec![BooleanOpType::Equal(value)]))),
+            None
+        );
+        assert_eq!(
+            presence(&expr(traversal(
+                "a",
+                vec![
+                    BooleanOpType::NotEqual(none()),
+                    BooleanOpType::NotEqual(none())
+                ]
+            ))),
+            None
+        );
+    }
+
+    /// Messages reported for `query`, parsed below a `User` schema
+    fn check_query(query: &str) -> Vec<String> {
+        let source = parse_text(&format!("N::User {{ name: String }}\n{}", query));
+        let config = LintConfig::default();
+        let mut out = Vec::new();
+        check(
+            &LintContext {
+                source: &source,
+                config: &config,
+            },
+            &mut out,
+        );
+        out.into_iter().map(|finding| finding.message).collect()
+    }
+
+    /// Messages reported for an `AND` or `OR` of `items`, with `name` optional. Item
+    /// `i` is on line `i + 1`, so guards cover exactly the items after them.
+    fn check_conditions(conjunction: bool, items: Vec<ExpressionType>) -> Vec<String> {
+        let items = items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| Expression {
+                loc: line(i + 1),
+                expr: item,
+            })
+            .collect();
+        let condition = expr(if conjunction {
+            ExpressionType::And(items)
+        } else {
+            ExpressionType::Or(items)
+        });
+        let source = Source::default();
+        let config = LintConfig::default();
+        let mut out = Vec::new();
+        let mut checker = OptionalChecker {
+            ctx: &LintContext {
+                source: &source,
+                config: &config,
+            },
+            optional: HashMap::from([("name".to_string(), loc())]),
+            guards: Vec::new(),
+            out: &mut out,
+        };
+        walk::walk_expression(&mut checker, &condition);
+        out.into_iter().map(|finding| finding.message).collect()
+    }
+
+    /// `_::{name}::EQ(name)` on line `at`
+    fn compares_name(at: usize) -> ExpressionType {
+        let value = Expression {
+            loc: line(at),
+            expr: identifier("name"),
+        };
+        ExpressionType::Traversal(Box::new(Traversal {
+            start: StartNode::Anonymous,
+            steps: vec![Step {
+                loc: line(at),
+                step: StepType::BooleanOperation(BooleanOp {
+                    loc: line(at),
+                    op: BooleanOpType::Equal(Box::new(value)),
+                }),
+            }],
+            loc: line(at),
+        }))
+    }
+
+    fn exists_name() -> ExpressionType {
+        exists(identifier("name")).expr
+    }
+
+    fn not(inner: ExpressionType) -> ExpressionType {
+        ExpressionType::Not(Box::new(expr(inner)))
+    }
+
+    fn unguarded(name: &str) -> String {
+        format!(
+            "This comparison needs a value, but `{}` is optional; guard it with EXISTS({}) or {}::NEQ(NONE)",
+            name, name, name
+        )
+    }
+
+    #[test]
+    fn unguarded_comparisons_are_reported() {
+        let query = "QUERY Find(name?: String) =>\n    users <- N<User>::WHERE(_::{name}::EQ(name))\n    RETURN users\n";
+        assert_eq!(check_query(query), [unguarded("name")]);
+    }
+
+    #[test]
+    fn aliases_of_optional_parameters_stay_optional() {
+        let query = "QUERY Find(name?: String) =>\n    alias <- name\n    users <- N<User>::WHERE(_::{name}::EQ(alias))\n    RETURN users\n";
+        assert_eq!(check_query(query), [unguarded("alias")]);
+    }
+
+    #[test]
+    fn exists_and_neq_none_guard_later_conditions_of_an_and() {
+        assert!(check_conditions(true, vec![exists_name(), compares_name(2)]).is_empty());
+        let neq_none = traversal("name", vec![BooleanOpType::NotEqual(none())]);
+        assert!(check_conditions(true, vec![neq_none, compares_name(2)]).is_empty());
+    }
+
+    #[test]
+    fn guards_only_cover_conditions_after_them() {
+        assert_eq!(
+            check_conditions(true, vec![compares_name(1), exists_name()]),
+            [unguarded("name")]
+        );
+    }
+
+    #[test]
+    fn negated_guards_cover_later_alternatives_of_an_or() {
+        assert!(check_conditions(false, vec![not(exists_name()), compares_name(2)]).is_empty());
+        let eq_none = traversal("name", vec![BooleanOpType::Equal(none())]);
+        assert!(check_conditions(false, vec![eq_none, compares_name(2)]).is_empty());
+        assert_eq!(
+            check_conditions(false, vec![exists_name(), compares_name(2)]),
+            [unguarded("name")]
+        );
+        assert_eq!(
+            check_conditions(true, vec![not(exists_name()), compares_name(2)]),
+            [unguarded("name")]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use helix_db::helixc::parser::types::{
+    Expression, ExpressionType, FieldValueType, Loc, StartNode, Statement, StepType, Traversal,
+};
+
+use super::{find_loc, Finding, LintContext, Rule, Severity};
+use crate::infer::{self, ElementType, Vars};
+use crate::walk::{self, Visitor};
+use crate::Backend;
+
+pub static PATH_EDGE_TYPE: Rule = Rule {
+    code: "HQL016",
+    name: "path-edge-type",
+    default_severity: Severity::Error,
+    summary: "The type argument of a ShortestPath step is not an edge type",
+};
+
+pub static PATH_WEIGHT: Rule = Rule {
+    code: "HQL017",
+    name: "path-weight",
+    default_severity: Severity::Error,
+    summary: "A path weight or A* heuristic does not resolve to a numeric property",
+};
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    for query in &ctx.source.queries {
+        let mut checker = PathChecker {
+            ctx,
+            vars: infer::parameters(query),
+            out,
+        };
+        walk::walk_query(&mut checker, query);
+    }
+}
+
+struct PathChecker<'a, 'b> {
+    ctx: &'a LintContext<'a>,
+    vars: Vars,
+    out: &'b mut Vec<Finding>,
+}
+
+impl PathChecker<'_, '_> {
+    fn error(&mut self, loc: &Loc, message: String) {
+        self.out.push(Finding::new(&PATH_WEIGHT, loc, message));
+    }
+
+    /// Check that `name` is a numeric property of `owner`; `what` introduces it in messages
+    fn numeric_property(&mut self, owner: &ElementType, name: &str, what: &str, loc: &Loc) {
+        let Some(item) = owner.schema_item() else {
+            return;
+        };
+        match infer::property_type(self.ctx.source, owner, name) {
+            None => self.error(
+                loc,
+                format!("{} `{}` is not a property of {}", what, name, item),
+            ),
+            Some(ty) if !infer::is_numeric(&ty) => self.error(
+                loc,
+                format!(
+                    "{} `{}` of {} is {}, but it must be numeric",
+                    what,
+                    name,
+                    item,
+                    Backend::field_type_to_string(&ty)
+                ),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    /// Check the properties a weight expression reads from the traversed edge (or the
+    /// nodes reached from it) and any constants it uses
+    fn weight(&mut self, expr: &Expression, edge: &ElementType) {
+        match &expr.expr {
+            ExpressionType::Traversal(traversal)
+                if matches!(traversal.start, StartNode::Anonymous) =>
+            {
+                let types = infer::traversal_types(self.ctx.source, traversal, &self.vars, edge);
+                let Some(StepType::Object(object)) = traversal.steps.last().map(|s| &s.step) else {
+                    return;
+                };
+                let owner = &types[types.len() - 2];
+                for field in &object.fields {
+                    let (name, loc) = match &field.value.value {
+                        FieldValueType::Identifier(name) => (name, &field.value.loc),
+                        _ => (&field.key, &field.loc),
+                    };
+                    self.numeric_property(owner, name, "Weight", &find_loc(loc, name));
+                }
+            }
+            ExpressionType::MathFunctionCall(call) => {
+                for arg in &call.args {
+                    self.weight(arg, edge);
+                }
+            }
+            ExpressionType::StringLiteral(_) | ExpressionType::BooleanLiteral(_) => {
+                self.error(&expr.loc, "Weights must be numeric".to_string())
+            }
+            ExpressionType::Identifier(name) => {
+                if let Some(ElementType::Scalar(ty)) = self.vars.get(name) {
+                    if !infer::is_numeric(ty) {
+                        let message = format!(
+                            "Weight `{}` is {}, but it must be numeric",
+                            name,
+                            Backend::field_type_to_string(ty)
+                        );
+                        self.error(&expr.loc, message);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn path(&mut self, path: infer::PathStep) {
+        let Some(edge) = path.edge else {
+            return;
+        };
+        if infer::edge_schema(self.ctx.source, edge).is_none() {
+            let kind = if infer::node_schema(self.ctx.source, edge).is_some() {
+                "a node type"
+            } else if infer::vector_schema(self.ctx.source, edge).is_some() {
+                "a vector type"
+            } else {
+                "not a declared type"
+            };
+            self.out.push(Finding::new(
+                &PATH_EDGE_TYPE,
+                &find_loc(path.loc, edge),
+                format!(
+                    "`{}` is {}; ShortestPath steps traverse an edge type",
+                    edge, kind
+                ),
+            ));
+            return;
+        }
+        if let Some(weight) = path.weight {
+            self.weight(weight, &ElementType::Edge(edge.to_string()));
+        }
+        if let (Some(heuristic), Some(owner)) = (
+            path.heuristic,
+            infer::heuristic_owner(self.ctx.source, edge),
+        ) {
+            let loc = find_loc(path.loc, &format!("\"{}\"", heuristic));
+            self.numeric_property(&owner, heuristic, "Heuristic", &loc);
+        }
+    }
+}
+
+impl Visitor for PathChecker<'_, '_> {
+    fn statement(&mut self, stmt: &Statement) {
+        infer::bind_loop(stmt, &mut self.vars);
+    }
+
+    fn statement_end(&mut self, stmt: &Statement) {
+        infer::bind_assignment(self.ctx.source, stmt, &mut self.vars);
+    }
+
+    fn traversal(&mut self, traversal: &Traversal) {
+        for step in &traversal.steps {
+            if let StepType::Node(graph_step) | StepType::Edge(graph_step) = &step.step {
+                if let Some(path) = infer::path_step(&graph_step.step) {
+                    self.path(path);
+                }
+            }
+        }
+    }
+}
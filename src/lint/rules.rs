@@ -0,0 +1,411 @@
+use std::collections::{HashMap, HashSet};
+
+use helix_db::helixc::parser::types::{
+    BuiltInMacro, EvaluatesToNumber, EvaluatesToNumberType, Expression, ExpressionType,
+    GraphStepType, Loc, Query, SearchVector, StartNode, Statement, StatementType, Step, StepType,
+    Traversal,
+};
+
+use super::{Finding, Fix, LintContext, Rule, Severity};
+use crate::deps::{self, Access};
+use crate::infer;
+use crate::walk::{self, Visitor};
+
+/// Default for HQL002 when `searchKThreshold` is not configured
+const DEFAULT_SEARCH_K_THRESHOLD: u64 = 100;
+
+pub static UNINDEXED_SCAN: Rule = Rule {
+    code: "HQL001",
+    name: "unindexed-scan",
+    default_severity: Severity::Info,
+    summary: "N<T> scans every node without an index lookup or a WHERE on an indexed field",
+};
+
+pub static SEARCH_K_TOO_LARGE: Rule = Rule {
+    code: "HQL002",
+    name: "search-k-too-large",
+    default_severity: Severity::Warning,
+    summary: "SearchV uses a literal k above the configured threshold",
+};
+
+pub static UNUSED_VARIABLE: Rule = Rule {
+    code: "HQL003",
+    name: "unused-variable",
+    default_severity: Severity::Hint,
+    summary: "A variable is assigned but never referenced",
+};
+
+pub static UNRETURNED_ASSIGNMENT: Rule = Rule {
+    code: "HQL004",
+    name: "unreturned-assignment",
+    default_severity: Severity::Warning,
+    summary: "A read-only assignment never contributes to the RETURN value",
+};
+
+pub static DUPLICATE_QUERY: Rule = Rule {
+    code: "HQL005",
+    name: "duplicate-query",
+    default_severity: Severity::Warning,
+    summary: "Two queries have identical bodies",
+};
+
+pub static MCP_MUTATION: Rule = Rule {
+    code: "HQL006",
+    name: "mcp-mutation",
+    default_severity: Severity::Warning,
+    summary: "A #[mcp] query creates, updates or drops data",
+};
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    for query in &ctx.source.queries {
+        check_scans_and_search(ctx, query, out);
+        check_assignments(query, out);
+        check_mcp_mutations(ctx, query, out);
+    }
+    check_duplicate_queries(ctx, out);
+}
+
+/// Field names a WHERE condition reads from the element, e.g. `age` in `_::{age}::GT(18)`
+#[derive(Default)]
+struct FilterFields(HashSet<String>);
+
+impl Visitor for FilterFields {
+    fn traversal(&mut self, traversal: &Traversal) {
+        if !matches!(traversal.start, StartNode::Anonymous) {
+            return;
+        }
+        if let Some(StepType::Object(object)) = traversal.steps.first().map(|s| &s.step) {
+            self.0.extend(object.fields.iter().map(|f| f.key.clone()));
+        }
+    }
+}
+
+pub fn literal_k(k: &EvaluatesToNumber) -> Option<u128> {
+    match &k.value {
+        EvaluatesToNumberType::I8(v) => u128::try_from(*v).ok(),
+        EvaluatesToNumberType::I16(v) => u128::try_from(*v).ok(),
+        EvaluatesToNumberType::I32(v) => u128::try_from(*v).ok(),
+        EvaluatesToNumberType::I64(v) => u128::try_from(*v).ok(),
+        EvaluatesToNumberType::U8(v) => Some(*v as u128),
+        EvaluatesToNumberType::U16(v) => Some(*v as u128),
+        EvaluatesToNumberType::U32(v) => Some(*v as u128),
+        EvaluatesToNumberType::U64(v) => Some(*v as u128),
+        EvaluatesToNumberType::U128(v) => Some(*v),
+        _ => None,
+    }
+}
+
+struct ScanChecker<'a, 'b> {
+    ctx: &'a LintContext<'a>,
+    out: &'b mut Vec<Finding>,
+}
+
+impl ScanChecker<'_, '_> {
+    fn search(&mut self, sv: &SearchVector) {
+        let threshold = self
+            .ctx
+            .config
+            .search_k_threshold
+            .unwrap_or(DEFAULT_SEARCH_K_THRESHOLD);
+        let Some(k) = &sv.k else {
+            return;
+        };
+        if let Some(value) = literal_k(k) {
+            if value > threshold as u128 {
+                self.out.push(Finding::new(
+                    &SEARCH_K_TOO_LARGE,
+                    &k.loc,
+                    format!(
+                        "SearchV returns up to {} results; the threshold is {}",
+                        value, threshold
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+impl Visitor for ScanChecker<'_, '_> {
+    fn expression(&mut self, expr: &Expression) {
+        if let ExpressionType::SearchVector(sv) = &expr.expr {
+            self.search(sv);
+        }
+    }
+
+    fn traversal(&mut self, traversal: &Traversal) {
+        match &traversal.start {
+            StartNode::SearchVector(sv) => self.search(sv),
+            StartNode::Node {
+                node_type,
+                ids: None,
+            } => {
+                let Some(schema) = infer::node_schema(self.ctx.source, node_type) else {
+                    return;
+                };
+
+                let mut filters = FilterFields::default();
+                for step in &traversal.steps {
+                    if let StepType::Where(expr) = &step.step {
+                        walk::walk_expression(&mut filters, expr);
+                    }
+                }
+                let filtered_on_index = schema
+                    .fields
+                    .iter()
+                    .any(|f| f.is_indexed() && filters.0.contains(&f.name));
+
+                if !filtered_on_index {
+                    self.out.push(Finding::new(
+                        &UNINDEXED_SCAN,
+                        &traversal.loc,
+                        format!(
+                            "N<{}> scans every {} node; look it up by ID or an INDEX field, or filter on an indexed field",
+                            node_type, node_type
+                        ),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, step: &Step) {
+        match &step.step {
+            StepType::SearchVector(sv) => self.search(sv),
+            StepType::Node(graph_step) | StepType::Edge(graph_step) => {
+                if let GraphStepType::SearchVector(sv) = &graph_step.step {
+                    self.search(sv);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_scans_and_search(ctx: &LintContext, query: &Query, out: &mut Vec<Finding>) {
+    let mut checker = ScanChecker { ctx, out };
+    walk::walk_query(&mut checker, query);
+}
+
+/// Identifiers referenced anywhere below a node
+#[derive(Default)]
+struct References(HashSet<String>);
+
+impl Visitor for References {
+    fn identifier(&mut self, name: &str, _loc: &Loc) {
+        self.0.insert(name.to_string());
+    }
+}
+
+/// Whether an expression creates, updates or removes data
+#[derive(Default)]
+struct Mutates(bool);
+
+impl Visitor for Mutates {
+    fn expression(&mut self, expr: &Expression) {
+        if matches!(
+            expr.expr,
+            ExpressionType::AddNode(_)
+                | ExpressionType::AddEdge(_)
+                | ExpressionType::AddVector(_)
+                | ExpressionType::BatchAddVector(_)
+        ) {
+            self.0 = true;
+        }
+    }
+
+    fn step(&mut self, step: &Step) {
+        if matches!(
+            step.step,
+            StepType::Update(_)
+                | StepType::UpsertN(_)
+                | StepType::UpsertE(_)
+                | StepType::UpsertV(_)
+                | StepType::AddEdge(_)
+        ) {
+            self.0 = true;
+        }
+    }
+}
+
+/// Statements of a query in source order, including those nested in FOR loops
+pub fn flatten(statements: &[Statement]) -> Vec<&Statement> {
+    let mut all = Vec::new();
+    for stmt in statements {
+        all.push(stmt);
+        if let StatementType::ForLoop(for_loop) = &stmt.statement {
+            all.extend(flatten(&for_loop.statements));
+        }
+    }
+    all
+}
+
+/// Identifiers a single statement reads. A FOR loop only reads its collection here;
+/// its body is listed separately by `flatten`.
+pub fn statement_references(stmt: &Statement) -> HashSet<String> {
+    let mut refs = References::default();
+    match &stmt.statement {
+        StatementType::Assignment(assignment) => {
+            walk::walk_expression(&mut refs, &assignment.value)
+        }
+        StatementType::Expression(expr) | StatementType::Drop(expr) => {
+            walk::walk_expression(&mut refs, expr)
+        }
+        StatementType::ForLoop(for_loop) => {
+            refs.0.insert(for_loop.in_variable.1.clone());
+        }
+    }
+    refs.0
+}
+
+fn statement_mutates(stmt: &Statement) -> bool {
+    let mut mutates = Mutates::default();
+    match &stmt.statement {
+        StatementType::Assignment(assignment) => {
+            walk::walk_expression(&mut mutates, &assignment.value)
+        }
+        StatementType::Expression(expr) => walk::walk_expression(&mut mutates, expr),
+        StatementType::Drop(_) => return true,
+        StatementType::ForLoop(for_loop) => {
+            return flatten(&for_loop.statements)
+                .into_iter()
+                .any(statement_mutates)
+        }
+    }
+    mutates.0
+}
+
+/// Identifiers referenced by the RETURN values of a query
+pub fn return_references(query: &Query) -> HashSet<String> {
+    let mut refs = References::default();
+    for ret in &query.return_values {
+        walk::walk_return(&mut refs, ret);
+    }
+    refs.0
+}
+
+fn check_assignments(query: &Query, out: &mut Vec<Finding>) {
+    let statements = flatten(&query.statements);
+
+    let mut referenced: HashSet<String> = return_references(query);
+    let mut live: Vec<String> = referenced.iter().cloned().collect();
+    let mut definitions: HashMap<&str, (bool, HashSet<String>)> = HashMap::new();
+
+    for stmt in &statements {
+        let refs = statement_references(stmt);
+        let mutates = statement_mutates(stmt);
+        referenced.extend(refs.iter().cloned());
+
+        // Statements with side effects keep everything they read alive
+        if mutates {
+            live.extend(refs.iter().cloned());
+        }
+        if let StatementType::Assignment(assignment) = &stmt.statement {
+            definitions.insert(assignment.variable.as_str(), (mutates, refs));
+        }
+    }
+
+    let mut reaches_return: HashSet<String> = HashSet::new();
+    while let Some(name) = live.pop() {
+        if reaches_return.insert(name.clone()) {
+            if let Some((_, refs)) = definitions.get(name.as_str()) {
+                live.extend(refs.iter().cloned());
+            }
+        }
+    }
+
+    for stmt in &statements {
+        let StatementType::Assignment(assignment) = &stmt.statement else {
+            continue;
+        };
+        let name = &assignment.variable;
+        let mutates = definitions
+            .get(name.as_str())
+            .map(|(mutates, _)| *mutates)
+            .unwrap_or(false);
+
+        if !referenced.contains(name) {
+            let finding = Finding::new(
+                &UNUSED_VARIABLE,
+                &stmt.loc,
+                format!("`{}` is assigned but never used", name),
+            )
+            .unnecessary();
+            // Only read-only assignments can go without changing what the query does
+            out.push(if mutates {
+                finding
+            } else {
+                finding.with_fix(Fix::remove_lines(format!("Remove `{}`", name), &stmt.loc))
+            });
+        } else if !mutates && !reaches_return.contains(name) {
+            out.push(Finding::new(
+                &UNRETURNED_ASSIGNMENT,
+                &stmt.loc,
+                format!("`{}` is computed but never contributes to RETURN", name),
+            ));
+        }
+    }
+}
+
+fn check_mcp_mutations(ctx: &LintContext, query: &Query, out: &mut Vec<Finding>) {
+    if !matches!(query.built_in_macro, Some(BuiltInMacro::MCP)) {
+        return;
+    }
+
+    for usage in deps::query_usages(ctx.source, query) {
+        let verb = match usage.access {
+            Access::Read => continue,
+            Access::Create => "creates",
+            Access::Update => "updates",
+            Access::Drop => "drops",
+        };
+        out.push(Finding::new(
+            &MCP_MUTATION,
+            &usage.loc,
+            format!(
+                "#[mcp] query `{}` {} {}; MCP tools are expected to be read-only",
+                query.name, verb, usage.item
+            ),
+        ));
+    }
+}
+
+/// Query body with whitespace collapsed, so formatting differences don't matter
+fn normalized_body(query: &Query) -> String {
+    let body = query
+        .original_query
+        .split_once("=>")
+        .map(|(_, body)| body)
+        .unwrap_or(&query.original_query);
+    body.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn check_duplicate_queries(ctx: &LintContext, out: &mut Vec<Finding>) {
+    let mut seen: HashMap<String, &Query> = HashMap::new();
+    for query in &ctx.source.queries {
+        let body = normalized_body(query);
+        if body.is_empty() {
+            continue;
+        }
+        match seen.get(&body) {
+            Some(first) => {
+                let mut finding = Finding::new(
+                    &DUPLICATE_QUERY,
+                    &query.loc,
+                    format!(
+                        "Query `{}` has the same body as `{}`",
+                        query.name, first.name
+                    ),
+                );
+                finding.related.push((
+                    first.loc.clone(),
+                    format!("`{}` is defined here", first.name),
+                ));
+                out.push(finding);
+            }
+            None => {
+                seen.insert(body, query);
+            }
+        }
+    }
+}
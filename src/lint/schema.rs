@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+
+use helix_db::helixc::parser::types::{Field, FieldPrefix, FieldType, Loc, Schema};
+
+use super::{find_loc, Finding, Fix, LintContext, Rule, Severity};
+use crate::infer::{self, ElementType};
+use crate::Backend;
+
+pub static DUPLICATE_TYPE: Rule = Rule {
+    code: "HQL026",
+    name: "duplicate-type",
+    default_severity: Severity::Error,
+    summary: "A node, edge or vector type is defined more than once in a schema version",
+};
+
+pub static DUPLICATE_FIELD: Rule = Rule {
+    code: "HQL027",
+    name: "duplicate-field",
+    default_severity: Severity::Error,
+    summary: "A type declares the same field twice",
+};
+
+pub static BUILTIN_FIELD: Rule = Rule {
+    code: "HQL028",
+    name: "builtin-field",
+    default_severity: Severity::Error,
+    summary: "A field shadows an implicit property such as id, label, from_node or score",
+};
+
+pub static UNKNOWN_ENDPOINT: Rule = Rule {
+    code: "HQL029",
+    name: "unknown-endpoint",
+    default_severity: Severity::Error,
+    summary: "An edge's From or To names a type the schema does not define",
+};
+
+pub static UNIQUE_INDEX_TYPE: Rule = Rule {
+    code: "HQL030",
+    name: "unique-index-type",
+    default_severity: Severity::Error,
+    summary: "UNIQUE INDEX is declared on an array or object field",
+};
+
+pub static UNKNOWN_PARAMETER_TYPE: Rule = Rule {
+    code: "HQL031",
+    name: "unknown-parameter-type",
+    default_severity: Severity::Error,
+    summary: "A query parameter's type names a type the schema does not define",
+};
+
+pub static EMPTY_SCHEMA: Rule = Rule {
+    code: "HQL032",
+    name: "empty-schema",
+    default_severity: Severity::Warning,
+    summary: "A schema version block defines no types",
+};
+
+/// A type definition: its name as written in schema files (`N::User`), where
+/// the name is written, and its fields
+struct Item<'a> {
+    kind: &'static str,
+    name: &'a str,
+    name_loc: Loc,
+    owner: ElementType,
+    fields: &'a [Field],
+}
+
+impl Item<'_> {
+    fn label(&self) -> String {
+        format!("{}::{}", self.kind, self.name)
+    }
+}
+
+fn items(schema: &Schema) -> Vec<Item<'_>> {
+    let nodes = schema.node_schemas.iter().map(|n| Item {
+        kind: "N",
+        name: &n.name.1,
+        name_loc: n.name.0.clone(),
+        owner: ElementType::Node(n.name.1.clone()),
+        fields: &n.fields,
+    });
+    let edges = schema.edge_schemas.iter().map(|e| Item {
+        kind: "E",
+        name: &e.name.1,
+        name_loc: e.name.0.clone(),
+        owner: ElementType::Edge(e.name.1.clone()),
+        fields: e.properties.as_deref().unwrap_or_default(),
+    });
+    // Vector names carry no location of their own
+    let vectors = schema.vector_schemas.iter().map(|v| Item {
+        kind: "V",
+        name: &v.name,
+        name_loc: find_loc(&v.loc, &v.name),
+        owner: ElementType::Vector(v.name.clone()),
+        fields: &v.fields,
+    });
+    nodes.chain(edges).chain(vectors).collect()
+}
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    let mut versions: Vec<_> = ctx.source.schema.values().collect();
+    versions.sort_by_key(|schema| schema.version.1);
+    for schema in versions {
+        if check_empty(schema, out) {
+            continue;
+        }
+        let items = items(schema);
+        check_duplicate_types(&items, out);
+        for item in &items {
+            check_fields(item, out);
+        }
+        check_endpoints(schema, out);
+    }
+    check_parameter_types(ctx, out);
+}
+
+/// Report an explicit `schema::N { }` block with nothing in it; returns whether it is empty
+fn check_empty(schema: &Schema, out: &mut Vec<Finding>) -> bool {
+    let empty = schema.node_schemas.is_empty()
+        && schema.edge_schemas.is_empty()
+        && schema.vector_schemas.is_empty();
+    if empty && schema.loc.span.contains("schema::") {
+        out.push(
+            Finding::new(
+                &EMPTY_SCHEMA,
+                &schema.loc,
+                format!("schema::{} defines no types", schema.version.1),
+            )
+            .with_fix(Fix::remove_lines(
+                format!("Remove the empty schema::{}", schema.version.1),
+                &schema.loc,
+            )),
+        );
+    }
+    empty
+}
+
+/// Types share one namespace: parameters and edge endpoints refer to them by name alone
+fn check_duplicate_types(items: &[Item], out: &mut Vec<Finding>) {
+    let mut first: HashMap<&str, &Item> = HashMap::new();
+    for item in items {
+        let Some(original) = first.get(item.name) else {
+            first.insert(item.name, item);
+            continue;
+        };
+        let elsewhere = match (&original.name_loc.filepath, &item.name_loc.filepath) {
+            (Some(a), Some(b)) if a != b => format!(" in {}", a),
+            _ => String::new(),
+        };
+        let mut finding = Finding::new(
+            &DUPLICATE_TYPE,
+            &item.name_loc,
+            format!(
+                "`{}` is already defined as {}{}",
+                item.name,
+                original.label(),
+                elsewhere
+            ),
+        );
+        finding.related.push((
+            original.name_loc.clone(),
+            format!("{} is defined here", original.label()),
+        ));
+        out.push(finding);
+    }
+}
+
+fn check_fields(item: &Item, out: &mut Vec<Finding>) {
+    let mut seen: HashMap<&str, &Field> = HashMap::new();
+    for field in item.fields {
+        let name_loc = find_loc(&field.loc, &field.name);
+
+        if let Some(original) = seen.get(field.name.as_str()) {
+            let mut finding = Finding::new(
+                &DUPLICATE_FIELD,
+                &name_loc,
+                format!("{} already declares `{}`", item.label(), field.name),
+            );
+            finding.related.push((
+                original.loc.clone(),
+                format!("`{}` is declared here", field.name),
+            ));
+            // Removing the line is only safe when no other field shares it
+            let alone = item
+                .fields
+                .iter()
+                .filter(|f| f.loc.start.line <= field.loc.end.line)
+                .filter(|f| f.loc.end.line >= field.loc.start.line)
+                .count()
+                == 1;
+            if alone {
+                finding = finding.with_fix(Fix::remove_lines(
+                    format!("Remove the duplicate `{}`", field.name),
+                    &field.loc,
+                ));
+            }
+            out.push(finding);
+        } else {
+            seen.insert(&field.name, field);
+        }
+
+        if let Some(builtin) = infer::builtin_field_type(&item.owner, &field.name) {
+            out.push(Finding::new(
+                &BUILTIN_FIELD,
+                &name_loc,
+                format!(
+                    "`{}` is an implicit {} property of every {}; rename this field",
+                    field.name,
+                    Backend::field_type_to_string(&builtin),
+                    item.label()
+                ),
+            ));
+        }
+
+        if matches!(field.prefix, FieldPrefix::UniqueIndex)
+            && matches!(field.field_type, FieldType::Array(_) | FieldType::Object(_))
+        {
+            let unique = find_loc(&field.loc, "UNIQUE ");
+            let mut finding = Finding::new(
+                &UNIQUE_INDEX_TYPE,
+                &find_loc(&field.loc, "UNIQUE"),
+                format!(
+                    "`{}` is {}; array and object values cannot be unique keys",
+                    field.name,
+                    Backend::field_type_to_string(&field.field_type)
+                ),
+            );
+            if unique.span == "UNIQUE " {
+                finding = finding.with_fix(Fix::replace("Make the index non-unique", &unique, ""));
+            }
+            out.push(finding);
+        }
+    }
+}
+
+/// Endpoints must be defined in the same schema version as the edge
+fn check_endpoints(schema: &Schema, out: &mut Vec<Finding>) {
+    let targets: Vec<&str> = schema
+        .node_schemas
+        .iter()
+        .map(|n| n.name.1.as_str())
+        .chain(schema.vector_schemas.iter().map(|v| v.name.as_str()))
+        .collect();
+    for edge in &schema.edge_schemas {
+        for (side, (loc, name)) in [("From", &edge.from), ("To", &edge.to)] {
+            if targets.contains(&name.as_str()) {
+                continue;
+            }
+            let mut finding = Finding::new(
+                &UNKNOWN_ENDPOINT,
+                loc,
+                format!(
+                    "{} of E::{} is `{}`, but no node or vector type has that name",
+                    side, edge.name.1, name
+                ),
+            );
+            if let Some(suggestion) = closest(name, targets.iter().copied()) {
+                finding = finding.with_fix(Fix::replace(
+                    format!("Change to `{}`", suggestion),
+                    loc,
+                    suggestion,
+                ));
+            }
+            out.push(finding);
+        }
+    }
+}
+
+fn check_parameter_types(ctx: &LintContext, out: &mut Vec<Finding>) {
+    let source = ctx.source;
+    let mut known: Vec<&str> = Vec::new();
+    for schema in source.schema.values() {
+        for item in items(schema) {
+            known.push(item.name);
+        }
+    }
+    for query in &source.queries {
+        for param in &query.parameters {
+            let (loc, ty) = &param.param_type;
+            let mut names = Vec::new();
+            identifiers(ty, &mut names);
+            for name in names.into_iter().filter(|name| !known.contains(name)) {
+                let mut finding = Finding::new(
+                    &UNKNOWN_PARAMETER_TYPE,
+                    loc,
+                    format!(
+                        "Parameter `{}` has type `{}`, which the schema does not define",
+                        param.name.1, name
+                    ),
+                );
+                let name_loc = find_loc(loc, name);
+                if let (Some(suggestion), true) =
+                    (closest(name, known.iter().copied()), name_loc.span == name)
+                {
+                    finding = finding.with_fix(Fix::replace(
+                        format!("Change to `{}`", suggestion),
+                        &name_loc,
+                        suggestion,
+                    ));
+                }
+                out.push(finding);
+            }
+        }
+    }
+}
+
+/// Type names a parameter type refers to, including inside arrays and objects
+fn identifiers<'a>(ty: &'a FieldType, out: &mut Vec<&'a str>) {
+    match ty {
+        FieldType::Identifier(name) => out.push(name),
+        FieldType::Array(inner) => identifiers(inner, out),
+        FieldType::Object(fields) => {
+            for field in fields.values() {
+                identifiers(field, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The candidate `name` is most likely a typo of: equal ignoring case, or at
+/// most two edits away
+fn closest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .map(|candidate| {
+            let distance = if candidate.eq_ignore_ascii_case(name) {
+                0
+            } else {
+                edit_distance(&name.to_lowercase(), &candidate.to_lowercase())
+            };
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_text;
+    use crate::lint::LintConfig;
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("user", "user"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("usr", "user"), 1);
+        assert_eq!(edit_distance("users", "user"), 1);
+        assert_eq!(edit_distance("usar", "user"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("née", "nee"), 1);
+    }
+
+    #[test]
+    fn closest_prefers_case_matches_then_fewest_edits() {
+        let names = ["Follows", "Likes", "User"];
+        assert_eq!(closest("user", names.into_iter()), Some("User"));
+        assert_eq!(closest("Folows", names.into_iter()), Some("Follows"));
+        assert_eq!(closest("likez", names.into_iter()), Some("Likes"));
+        assert_eq!(closest("Post", names.into_iter()), None);
+        assert_eq!(
+            closest("Likes", ["Like", "Likes"].into_iter()),
+            Some("Likes")
+        );
+        assert_eq!(closest("Usr", std::iter::empty()), None);
+    }
+
+    fn check_schema(text: &str) -> Vec<Finding> {
+        let source = parse_text(text);
+        let config = LintConfig::default();
+        let mut out = Vec::new();
+        check(
+            &LintContext {
+                source: &source,
+                config: &config,
+            },
+            &mut out,
+        );
+        out
+    }
+
+    #[test]
+    fn types_may_not_share_a_name_across_kinds() {
+        let findings = check_schema(
+            "N::User { name: String }\nV::User {}\nE::User { From: User, To: User }\n",
+        );
+        let reported: Vec<_> = findings
+            .iter()
+            .map(|f| (f.rule.name, f.loc.start.line, f.message.as_str()))
+            .collect();
+        assert_eq!(
+            reported,
+            [
+                ("duplicate-type", 3, "`User` is already defined as N::User"),
+                ("duplicate-type", 2, "`User` is already defined as N::User"),
+            ]
+        );
+        for finding in &findings {
+            assert_eq!(finding.related.len(), 1);
+            assert_eq!(finding.related[0].0.start.line, 1);
+            assert_eq!(finding.related[0].1, "N::User is defined here");
+        }
+    }
+
+    #[test]
+    fn edge_endpoints_must_name_a_node_or_vector() {
+        let findings = check_schema(
+            "N::User { name: String }\nV::Post {}\nE::Wrote { From: Usr, To: Post }\nE::Likes { From: User, To: Comment }\n",
+        );
+        let reported: Vec<_> = findings
+            .iter()
+            .map(|f| {
+                (
+                    f.rule.name,
+                    f.message.as_str(),
+                    f.fix
+                        .as_ref()
+                        .map(|fix| (fix.title.as_str(), fix.new_text.as_str())),
+                )
+            })
+            .collect();
+        assert_eq!(
+            reported,
+            [
+                (
+                    "unknown-endpoint",
+                    "From of E::Wrote is `Usr`, but no node or vector type has that name",
+                    Some(("Change to `User`", "User")),
+                ),
+                (
+                    "unknown-endpoint",
+                    "To of E::Likes is `Comment`, but no node or vector type has that name",
+                    None,
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+
+use helix_db::helixc::parser::types::{Loc, Query};
+
+use super::rules::{flatten, return_references, statement_references};
+use super::{Finding, Fix, LintContext, Rule, Severity};
+use crate::deps;
+use crate::fields;
+
+pub static UNUSED_PARAMETER: Rule = Rule {
+    code: "HQL007",
+    name: "unused-parameter",
+    default_severity: Severity::Hint,
+    summary: "A query parameter is never referenced in the query body",
+};
+
+pub static UNUSED_TYPE: Rule = Rule {
+    code: "HQL008",
+    name: "unused-type",
+    default_severity: Severity::Hint,
+    summary: "A node or edge type is not used by any query in the project",
+};
+
+pub static UNUSED_EDGE_PROPERTY: Rule = Rule {
+    code: "HQL009",
+    name: "unused-edge-property",
+    default_severity: Severity::Hint,
+    summary: "An edge property is never read or written by any query in the project",
+};
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    for query in &ctx.source.queries {
+        check_parameters(query, out);
+    }
+    check_schema_usage(ctx, out);
+}
+
+fn check_parameters(query: &Query, out: &mut Vec<Finding>) {
+    let mut referenced: HashSet<String> = return_references(query);
+    for stmt in flatten(&query.statements) {
+        referenced.extend(statement_references(stmt));
+    }
+
+    let locs: Vec<&Loc> = query.parameters.iter().map(|param| &param.loc).collect();
+    for (i, param) in query.parameters.iter().enumerate() {
+        if !referenced.contains(&param.name.1) {
+            out.push(
+                Finding::new(
+                    &UNUSED_PARAMETER,
+                    &param.loc,
+                    format!(
+                        "Parameter `{}` of `{}` is never used",
+                        param.name.1, query.name
+                    ),
+                )
+                .unnecessary()
+                .with_fix(Fix::remove_item(
+                    format!("Remove parameter `{}`", param.name.1),
+                    &locs,
+                    i,
+                )),
+            );
+        }
+    }
+}
+
+/// Types and edge properties of the latest schema that no query touches.
+/// Usage is collected across every query in the project, so a schema-only
+/// project reports nothing.
+fn check_schema_usage(ctx: &LintContext, out: &mut Vec<Finding>) {
+    let source = ctx.source;
+    if source.queries.is_empty() {
+        return;
+    }
+    let Some(schema) = source
+        .schema
+        .keys()
+        .max()
+        .and_then(|v| source.schema.get(v))
+    else {
+        return;
+    };
+
+    let mut used_items: HashSet<String> = HashSet::new();
+    let mut used_fields: HashSet<(String, String)> = HashSet::new();
+    for query in &source.queries {
+        used_items.extend(
+            deps::query_usages(source, query)
+                .into_iter()
+                .map(|usage| usage.item),
+        );
+        for field_ref in fields::query_field_refs(source, query) {
+            if let Some(item) = field_ref.owner.schema_item() {
+                used_fields.insert((item, field_ref.name));
+            }
+        }
+    }
+
+    for node in &schema.node_schemas {
+        let item = format!("N::{}", node.name.1);
+        if !used_items.contains(&item) {
+            out.push(
+                Finding::new(
+                    &UNUSED_TYPE,
+                    &node.loc,
+                    format!("{} is not used by any query", item),
+                )
+                .unnecessary(),
+            );
+        }
+    }
+
+    for edge in &schema.edge_schemas {
+        let item = format!("E::{}", edge.name.1);
+        if !used_items.contains(&item) {
+            out.push(
+                Finding::new(
+                    &UNUSED_TYPE,
+                    &edge.loc,
+                    format!("{} is not used by any query", item),
+                )
+                .unnecessary(),
+            );
+            continue;
+        }
+
+        let properties = edge.properties.as_deref().unwrap_or_default();
+        let locs: Vec<&Loc> = properties.iter().map(|property| &property.loc).collect();
+        for (i, property) in properties.iter().enumerate() {
+            if !used_fields.contains(&(item.clone(), property.name.clone())) {
+                out.push(
+                    Finding::new(
+                        &UNUSED_EDGE_PROPERTY,
+                        &property.loc,
+                        format!(
+                            "Property `{}` of {} is never read or written",
+                            property.name, item
+                        ),
+                    )
+                    .unnecessary()
+                    .with_fix(Fix::remove_item(
+                        format!("Remove property `{}`", property.name),
+                        &locs,
+                        i,
+                    )),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,545 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate};
+use helix_db::helixc::parser::types::{
+    DefaultValue, Expression, ExpressionType, Field, FieldAddition, FieldType, FieldValueType,
+    IdType, Loc, StartNode, Statement, StepType, Traversal, ValueType,
+};
+use helix_db::protocol::value::Value;
+
+use super::{find_loc, Finding, LintContext, Rule, Severity};
+use crate::infer::{self, ElementType, Vars};
+use crate::walk::{self, Visitor};
+use crate::Backend;
+
+pub static VALUE_TYPE: Rule = Rule {
+    code: "HQL011",
+    name: "value-type",
+    default_severity: Severity::Error,
+    summary: "A value or DEFAULT does not fit the type of the field it is assigned to",
+};
+
+pub static READ_ONLY_FIELD: Rule = Rule {
+    code: "HQL012",
+    name: "read-only-field",
+    default_severity: Severity::Error,
+    summary: "A query sets a built-in property such as `id`, which the database assigns",
+};
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    check_defaults(ctx, out);
+    for query in &ctx.source.queries {
+        let mut checker = ValueChecker {
+            ctx,
+            vars: infer::parameters(query),
+            out,
+        };
+        walk::walk_query(&mut checker, query);
+    }
+}
+
+/// Smallest and largest value of an integer field type. `U128` is capped at
+/// `i128::MAX`, like the literals compared against it.
+fn integer_bounds(ty: &FieldType) -> Option<(i128, i128)> {
+    Some(match ty {
+        FieldType::I8 => (i8::MIN as i128, i8::MAX as i128),
+        FieldType::I16 => (i16::MIN as i128, i16::MAX as i128),
+        FieldType::I32 => (i32::MIN as i128, i32::MAX as i128),
+        FieldType::I64 => (i64::MIN as i128, i64::MAX as i128),
+        FieldType::U8 => (0, u8::MAX as i128),
+        FieldType::U16 => (0, u16::MAX as i128),
+        FieldType::U32 => (0, u32::MAX as i128),
+        FieldType::U64 => (0, u64::MAX as i128),
+        FieldType::U128 => (0, i128::MAX),
+        _ => return None,
+    })
+}
+
+fn integer(value: &Value) -> Option<i128> {
+    Some(match value {
+        Value::I8(v) => *v as i128,
+        Value::I16(v) => *v as i128,
+        Value::I32(v) => *v as i128,
+        Value::I64(v) => *v as i128,
+        Value::U8(v) => *v as i128,
+        Value::U16(v) => *v as i128,
+        Value::U32(v) => *v as i128,
+        Value::U64(v) => *v as i128,
+        Value::U128(v) => i128::try_from(*v).unwrap_or(i128::MAX),
+        _ => return None,
+    })
+}
+
+/// A literal as written in a query, for messages
+fn literal_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("\"{}\"", s),
+        Value::F32(v) => v.to_string(),
+        Value::F64(v) => v.to_string(),
+        Value::Boolean(v) => v.to_string(),
+        Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(literal_text)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Empty => "NONE".to_string(),
+        other => integer(other).unwrap_or_default().to_string(),
+    }
+}
+
+/// Dates are RFC 3339 timestamps or plain `YYYY-MM-DD` days
+fn is_date(text: &str) -> bool {
+    DateTime::parse_from_rfc3339(text).is_ok()
+        || NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok()
+}
+
+/// Why `value` cannot be stored in a field of type `ty`, if it can't
+fn literal_error(ty: &FieldType, value: &Value) -> Option<String> {
+    let text = literal_text(value);
+    let type_name = Backend::field_type_to_string(ty);
+    if let Some((min, max)) = integer_bounds(ty) {
+        return match integer(value) {
+            Some(v) if v < min || v > max => Some(format!(
+                "{} is out of range for {} ({} to {})",
+                text,
+                type_name,
+                min,
+                if *ty == FieldType::U128 {
+                    u128::MAX.to_string()
+                } else {
+                    max.to_string()
+                }
+            )),
+            Some(_) => None,
+            None => Some(format!("{} expects an integer, found {}", type_name, text)),
+        };
+    }
+    let fits = match (ty, value) {
+        (_, Value::Empty) => true,
+        (FieldType::F32 | FieldType::F64, Value::F32(_) | Value::F64(_)) => true,
+        (FieldType::F32 | FieldType::F64, other) => integer(other).is_some(),
+        (FieldType::String, Value::String(_)) => true,
+        (FieldType::Boolean, Value::Boolean(_)) => true,
+        (FieldType::Date, Value::String(s)) => {
+            if is_date(s) {
+                return None;
+            }
+            return Some(format!(
+                "{} is not a valid Date; use RFC 3339 such as \"2024-01-31T12:00:00Z\" or \"2024-01-31\"",
+                text
+            ));
+        }
+        (FieldType::Uuid, Value::String(s)) => {
+            if uuid::Uuid::parse_str(s).is_ok() {
+                return None;
+            }
+            return Some(format!("{} is not a valid Uuid", text));
+        }
+        (FieldType::Array(inner), Value::Array(items)) => {
+            return items
+                .iter()
+                .find_map(|item| literal_error(inner.as_ref(), item));
+        }
+        // Enum-like identifiers and objects are not checked
+        (FieldType::Identifier(_) | FieldType::Object(_), _) => true,
+        _ => false,
+    };
+    (!fits).then(|| format!("{} expects {}, found {}", type_name, kind_of(ty), text))
+}
+
+/// Broad kind of a field type, for telling apart values that can never match
+fn kind_of(ty: &FieldType) -> &'static str {
+    match ty {
+        FieldType::String => "a string",
+        FieldType::Boolean => "a boolean",
+        FieldType::Date => "a date",
+        FieldType::Uuid => "an ID",
+        FieldType::Array(_) => "an array",
+        FieldType::Identifier(_) | FieldType::Object(_) => "an object",
+        _ => "a number",
+    }
+}
+
+/// Whether a variable or parameter of type `actual` can be stored in a field of type
+/// `field`. Dates and IDs are also passed as strings.
+fn assignable(field: &FieldType, actual: &FieldType) -> bool {
+    match (field, actual) {
+        (FieldType::Date | FieldType::Uuid, FieldType::String) => true,
+        (FieldType::Array(f), FieldType::Array(a)) => assignable(f, a),
+        (FieldType::Identifier(_) | FieldType::Object(_), _)
+        | (_, FieldType::Identifier(_) | FieldType::Object(_)) => true,
+        _ => kind_of(field) == kind_of(actual),
+    }
+}
+
+fn default_error(ty: &FieldType, default: &DefaultValue) -> Option<String> {
+    let value = match default {
+        DefaultValue::Now if *ty == FieldType::Date => return None,
+        DefaultValue::Now => {
+            return Some(format!(
+                "NOW is only valid for Date fields, not {}",
+                Backend::field_type_to_string(ty)
+            ))
+        }
+        DefaultValue::Empty => return None,
+        DefaultValue::String(s) => Value::String(s.clone()),
+        DefaultValue::F32(v) => Value::F32(*v),
+        DefaultValue::F64(v) => Value::F64(*v),
+        DefaultValue::I8(v) => Value::I8(*v),
+        DefaultValue::I16(v) => Value::I16(*v),
+        DefaultValue::I32(v) => Value::I32(*v),
+        DefaultValue::I64(v) => Value::I64(*v),
+        DefaultValue::U8(v) => Value::U8(*v),
+        DefaultValue::U16(v) => Value::U16(*v),
+        DefaultValue::U32(v) => Value::U32(*v),
+        DefaultValue::U64(v) => Value::U64(*v),
+        DefaultValue::U128(v) => Value::U128(*v),
+        DefaultValue::Boolean(v) => Value::Boolean(*v),
+    };
+    literal_error(ty, &value)
+}
+
+/// The `DEFAULT ...` part of a field definition, or the whole field when the
+/// parser kept no text for it
+fn default_loc(field: &Field) -> Loc {
+    let mut loc = find_loc(&field.loc, "DEFAULT");
+    loc.end = field.loc.end.clone();
+    loc
+}
+
+fn check_defaults(ctx: &LintContext, out: &mut Vec<Finding>) {
+    for schema in ctx.source.schema.values() {
+        let fields = schema
+            .node_schemas
+            .iter()
+            .flat_map(|n| &n.fields)
+            .chain(
+                schema
+                    .edge_schemas
+                    .iter()
+                    .flat_map(|e| e.properties.iter().flatten()),
+            )
+            .chain(schema.vector_schemas.iter().flat_map(|v| &v.fields));
+        for field in fields {
+            let Some(default) = &field.defaults else {
+                continue;
+            };
+            if let Some(message) = default_error(&field.field_type, default) {
+                out.push(Finding::new(
+                    &VALUE_TYPE,
+                    &default_loc(field),
+                    format!("Default of `{}`: {}", field.name, message),
+                ));
+            }
+        }
+    }
+}
+
+/// A value assigned to a field, as the parser gives it in object literals and lookups
+enum Assigned<'a> {
+    Literal(&'a Value),
+    Variable(&'a str),
+}
+
+struct ValueChecker<'a, 'b> {
+    ctx: &'a LintContext<'a>,
+    vars: Vars,
+    out: &'b mut Vec<Finding>,
+}
+
+impl ValueChecker<'_, '_> {
+    /// Report a write to a built-in property, returning whether `name` is one
+    fn read_only(&mut self, owner: &ElementType, name: &str, loc: &Loc) -> bool {
+        let (Some(item), Some(_)) = (owner.schema_item(), infer::builtin_field_type(owner, name))
+        else {
+            return false;
+        };
+        self.out.push(Finding::new(
+            &READ_ONLY_FIELD,
+            loc,
+            format!(
+                "`{}` of {} is assigned by the database and cannot be set",
+                name, item
+            ),
+        ));
+        true
+    }
+
+    /// Check a value stored in field `name` of `owner`. Lookups by index may read
+    /// built-in properties; every other assignment writes them.
+    fn assign(&mut self, owner: &ElementType, name: &str, value: Assigned, loc: &Loc, write: bool) {
+        let Some(item) = owner.schema_item() else {
+            return;
+        };
+        if write && self.read_only(owner, name, loc) {
+            return;
+        }
+        let Some((_, field)) = infer::field_declaration(self.ctx.source, owner, name) else {
+            return;
+        };
+        let message = match value {
+            Assigned::Literal(value) => literal_error(&field.field_type, value),
+            Assigned::Variable(variable) => match self.vars.get(variable) {
+                Some(ElementType::Scalar(actual)) if !assignable(&field.field_type, actual) => {
+                    Some(format!(
+                        "{} expects {}, but `{}` is {}",
+                        Backend::field_type_to_string(&field.field_type),
+                        kind_of(&field.field_type),
+                        variable,
+                        Backend::field_type_to_string(actual)
+                    ))
+                }
+                _ => None,
+            },
+        };
+        if let Some(message) = message {
+            self.out.push(Finding::new(
+                &VALUE_TYPE,
+                loc,
+                format!("`{}` of {}: {}", name, item, message),
+            ));
+        }
+    }
+
+    fn value(&mut self, owner: &ElementType, name: &str, value: &ValueType, write: bool) {
+        match value {
+            ValueType::Literal { value, loc } => {
+                self.assign(owner, name, Assigned::Literal(value), loc, write)
+            }
+            ValueType::Identifier { value, loc } => {
+                self.assign(owner, name, Assigned::Variable(value), loc, write)
+            }
+            ValueType::Object { .. } => {}
+        }
+    }
+
+    /// Object literal of an `AddN`/`AddE`/`AddV`
+    fn created(&mut self, owner: ElementType, fields: &Option<HashMap<String, ValueType>>) {
+        for (name, value) in fields.iter().flatten() {
+            self.value(&owner, name, value, true);
+        }
+    }
+
+    /// Fields of an `UPDATE` or `Upsert*`
+    fn written(&mut self, owner: &ElementType, fields: &[FieldAddition]) {
+        for field in fields {
+            let value = match &field.value.value {
+                FieldValueType::Literal(value) => Assigned::Literal(value),
+                FieldValueType::Identifier(name) => Assigned::Variable(name),
+                // Computed values are not type checked, but still may not set built-ins
+                _ => {
+                    self.read_only(owner, &field.key, &field.loc);
+                    continue;
+                }
+            };
+            self.assign(owner, &field.key, value, &field.value.loc, true);
+        }
+    }
+}
+
+impl Visitor for ValueChecker<'_, '_> {
+    fn statement(&mut self, stmt: &Statement) {
+        infer::bind_loop(stmt, &mut self.vars);
+    }
+
+    fn statement_end(&mut self, stmt: &Statement) {
+        infer::bind_assignment(self.ctx.source, stmt, &mut self.vars);
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        match &expr.expr {
+            ExpressionType::AddNode(add) => {
+                if let Some(t) = &add.node_type {
+                    self.created(ElementType::Node(t.clone()), &add.fields);
+                }
+            }
+            ExpressionType::AddEdge(add) => {
+                if let Some(t) = &add.edge_type {
+                    self.created(ElementType::Edge(t.clone()), &add.fields);
+                }
+            }
+            ExpressionType::AddVector(add) => {
+                if let Some(t) = &add.vector_type {
+                    self.created(ElementType::Vector(t.clone()), &add.fields);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn traversal(&mut self, traversal: &Traversal) {
+        let types = infer::traversal_types(
+            self.ctx.source,
+            traversal,
+            &self.vars,
+            &ElementType::Unknown,
+        );
+
+        if let StartNode::Node { ids, .. }
+        | StartNode::Edge { ids, .. }
+        | StartNode::Vector { ids, .. } = &traversal.start
+        {
+            for id in ids.iter().flatten() {
+                if let IdType::ByIndex { index, value, .. } = id {
+                    if let IdType::Identifier { value: name, .. } = index.as_ref() {
+                        self.value(&types[0], name, value, false);
+                    }
+                }
+            }
+        }
+
+        for (i, step) in traversal.steps.iter().enumerate() {
+            match &step.step {
+                StepType::Update(update) => self.written(&types[i], &update.fields),
+                StepType::UpsertN(upsert) => self.written(&types[i], &upsert.fields),
+                StepType::UpsertE(upsert) => self.written(&types[i], &upsert.fields),
+                StepType::UpsertV(upsert) => self.written(&types[i], &upsert.fields),
+                StepType::AddEdge(add) => {
+                    if let Some(t) = &add.edge_type {
+                        self.created(ElementType::Edge(t.clone()), &add.fields);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_text;
+    use crate::lint::LintConfig;
+
+    #[test]
+    fn integer_bounds_cover_each_width() {
+        assert_eq!(integer_bounds(&FieldType::I8), Some((-128, 127)));
+        assert_eq!(integer_bounds(&FieldType::U8), Some((0, 255)));
+        assert_eq!(
+            integer_bounds(&FieldType::I64),
+            Some((i64::MIN as i128, i64::MAX as i128))
+        );
+        assert_eq!(integer_bounds(&FieldType::U128), Some((0, i128::MAX)));
+        assert_eq!(integer_bounds(&FieldType::F64), None);
+        assert_eq!(integer_bounds(&FieldType::String), None);
+    }
+
+    #[test]
+    fn integers_must_fit_the_field() {
+        assert_eq!(literal_error(&FieldType::U8, &Value::I32(255)), None);
+        assert_eq!(
+            literal_error(&FieldType::U8, &Value::I32(256)),
+            Some("256 is out of range for U8 (0 to 255)".to_string())
+        );
+        assert_eq!(
+            literal_error(&FieldType::U32, &Value::I32(-1)),
+            Some("-1 is out of range for U32 (0 to 4294967295)".to_string())
+        );
+        assert_eq!(literal_error(&FieldType::I8, &Value::I32(-128)), None);
+        assert_eq!(
+            literal_error(&FieldType::I32, &Value::String("1".to_string())),
+            Some("I32 expects an integer, found \"1\"".to_string())
+        );
+    }
+
+    #[test]
+    fn u128_accepts_values_past_i128() {
+        assert_eq!(
+            literal_error(&FieldType::U128, &Value::U128(u128::MAX)),
+            None
+        );
+        assert_eq!(
+            literal_error(&FieldType::U128, &Value::I64(-1)),
+            Some(format!("-1 is out of range for U128 (0 to {})", u128::MAX))
+        );
+    }
+
+    #[test]
+    fn dates_are_rfc3339_or_days() {
+        let date = |s: &str| literal_error(&FieldType::Date, &Value::String(s.to_string()));
+        assert_eq!(date("2024-01-31T12:00:00Z"), None);
+        assert_eq!(date("2024-01-31T12:00:00+02:00"), None);
+        assert_eq!(date("2024-01-31"), None);
+        assert!(date("2024-02-30").unwrap().contains("is not a valid Date"));
+        assert!(date("31/01/2024").is_some());
+    }
+
+    #[test]
+    fn uuids_must_parse() {
+        let uuid = |s: &str| literal_error(&FieldType::Uuid, &Value::String(s.to_string()));
+        assert_eq!(uuid("67e55044-10b1-426f-9247-bb680e5fe0c8"), None);
+        assert_eq!(
+            uuid("not-a-uuid"),
+            Some("\"not-a-uuid\" is not a valid Uuid".to_string())
+        );
+    }
+
+    #[test]
+    fn other_types_check_the_kind_of_literal() {
+        assert_eq!(literal_error(&FieldType::F64, &Value::I32(3)), None);
+        assert_eq!(literal_error(&FieldType::String, &Value::Empty), None);
+        assert!(literal_error(&FieldType::Boolean, &Value::String("true".to_string())).is_some());
+        assert_eq!(
+            literal_error(
+                &FieldType::Array(Box::new(FieldType::U8)),
+                &Value::Array(vec![Value::I32(1), Value::I32(300)])
+            ),
+            Some("300 is out of range for U8 (0 to 255)".to_string())
+        );
+    }
+
+    const SCHEMA: &str = "N::User { INDEX email: String, age: U8, joined: Date }\n";
+
+    /// Messages reported for `query`, parsed below a `User` schema
+    fn check_query(query: &str) -> Vec<String> {
+        let source = parse_text(&format!("{}{}", SCHEMA, query));
+        let config = LintConfig::default();
+        let mut out = Vec::new();
+        check(
+            &LintContext {
+                source: &source,
+                config: &config,
+            },
+            &mut out,
+        );
+        out.into_iter().map(|finding| finding.message).collect()
+    }
+
+    #[test]
+    fn lookups_by_index_check_the_indexed_field() {
+        let query = "QUERY Find(age: String) =>
+    by_email <- N<User>({email: 5})
+    by_age <- N<User>({age: age})
+    RETURN by_email, by_age
+";
+        assert_eq!(
+            check_query(query),
+            [
+                "`email` of N::User: String expects a string, found 5",
+                "`age` of N::User: U8 expects a number, but `age` is String",
+            ]
+        );
+    }
+
+    #[test]
+    fn updates_check_values_and_built_in_properties() {
+        let query = "QUERY Edit(id: ID, when: String) =>
+    user <- N<User>(id)::UPDATE({age: 300, joined: \"soon\", id: id})
+    other <- N<User>(id)::UPDATE({joined: when})
+    RETURN user, other
+";
+        let mut messages = check_query(query);
+        messages.sort();
+        assert_eq!(
+            messages,
+            [
+                "`age` of N::User: 300 is out of range for U8 (0 to 255)",
+                "`id` of N::User is assigned by the database and cannot be set",
+                "`joined` of N::User: \"soon\" is not a valid Date; use RFC 3339 such as \"2024-01-31T12:00:00Z\" or \"2024-01-31\"",
+            ]
+        );
+    }
+}
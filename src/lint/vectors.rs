@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+
+use helix_db::helixc::parser::types::{
+    BuiltInMacro, EvaluatesToNumber, EvaluatesToNumberType, Expression, ExpressionType, FieldType,
+    GraphStepType, Loc, MMRDistance, SearchVector, StartNode, Statement, StepType, Traversal,
+    VectorData,
+};
+
+use super::rules::literal_k;
+use super::{Finding, LintContext, Rule, Severity};
+use crate::infer::{self, ElementType, Vars};
+use crate::walk::{self, Visitor};
+use crate::Backend;
+
+pub static VECTOR_DIMENSION: Rule = Rule {
+    code: "HQL018",
+    name: "vector-dimension",
+    default_severity: Severity::Error,
+    summary: "A vector literal's length differs from other literals of the same V:: type",
+};
+
+pub static EMBED_WITHOUT_MODEL: Rule = Rule {
+    code: "HQL019",
+    name: "embed-without-model",
+    default_severity: Severity::Warning,
+    summary: "Embed(...) is used, but no query in the project declares #[model(...)]",
+};
+
+pub static SEARCH_K_TYPE: Rule = Rule {
+    code: "HQL020",
+    name: "search-k-type",
+    default_severity: Severity::Error,
+    summary: "The k of a SearchV is not a positive integer",
+};
+
+pub static BATCH_VECTORS: Rule = Rule {
+    code: "HQL021",
+    name: "batch-vectors",
+    default_severity: Severity::Error,
+    summary: "BatchAddV is not given an array parameter of vector objects",
+};
+
+pub static MMR_DISTANCE: Rule = Rule {
+    code: "HQL022",
+    name: "mmr-distance",
+    default_severity: Severity::Error,
+    summary: "The distance of a RerankMMR is not one of the supported metrics",
+};
+
+/// Distances `RerankMMR` accepts, as written in queries
+const MMR_DISTANCES: &[&str] = &["cosine", "euclidean", "dotproduct"];
+
+/// Key holding the embedding in each object passed to `BatchAddV`
+const BATCH_VECTOR_KEY: &str = "vector";
+
+/// A `[...]` vector literal of a `V::` type
+struct VectorLiteral {
+    vector_type: String,
+    dimensions: usize,
+    loc: Loc,
+}
+
+struct VectorChecker<'a, 'b> {
+    ctx: &'a LintContext<'a>,
+    vars: Vars,
+    literals: Vec<VectorLiteral>,
+    embeds: Vec<Loc>,
+    out: &'b mut Vec<Finding>,
+}
+
+impl VectorChecker<'_, '_> {
+    /// Record the literal or `Embed` call a vector is given as
+    fn data(&mut self, vector_type: Option<&String>, data: Option<&VectorData>, loc: &Loc) {
+        match data {
+            Some(VectorData::Vector(values)) => {
+                if let Some(vector_type) = vector_type {
+                    self.literals.push(VectorLiteral {
+                        vector_type: vector_type.clone(),
+                        dimensions: values.len(),
+                        loc: loc.clone(),
+                    });
+                }
+            }
+            Some(VectorData::Embed(embed)) => self.embeds.push(embed.loc.clone()),
+            _ => {}
+        }
+    }
+
+    fn search(&mut self, sv: &SearchVector) {
+        self.data(sv.vector_type.as_ref(), sv.data.as_ref(), &sv.loc);
+        if let Some(k) = &sv.k {
+            self.k(k);
+        }
+    }
+
+    fn k(&mut self, k: &EvaluatesToNumber) {
+        let problem = match &k.value {
+            EvaluatesToNumberType::F32(_) | EvaluatesToNumberType::F64(_) => {
+                Some("k must be an integer, not a float".to_string())
+            }
+            EvaluatesToNumberType::Identifier(name) => match self.vars.get(name) {
+                Some(ElementType::Scalar(ty)) if !infer::is_numeric(ty) || is_float(ty) => {
+                    Some(format!(
+                        "k must be an integer, but `{}` is {}",
+                        name,
+                        Backend::field_type_to_string(ty)
+                    ))
+                }
+                Some(ElementType::Node(_) | ElementType::Edge(_) | ElementType::Vector(_)) => Some(
+                    format!("k must be an integer, but `{}` is an element", name),
+                ),
+                _ => None,
+            },
+            _ if !matches!(literal_k(k), Some(1..)) => {
+                Some("k must be a positive integer".to_string())
+            }
+            _ => None,
+        };
+        if let Some(message) = problem {
+            self.out.push(Finding::new(&SEARCH_K_TYPE, &k.loc, message));
+        }
+    }
+
+    /// `BatchAddV<T>(vectors)` takes a parameter `[{vector: [F64], field: ...}]` whose
+    /// other keys are fields of `V::T`
+    fn batch(&mut self, vector_type: Option<&String>, identifier: &str, loc: &Loc) {
+        let problem = match self.vars.get(identifier) {
+            None => Some(format!("`{}` is not a parameter or variable", identifier)),
+            Some(ElementType::Scalar(FieldType::Array(inner))) => match inner.as_ref() {
+                FieldType::Object(fields) => self.batch_object(vector_type, identifier, fields),
+                other => Some(format!(
+                    "`{}` holds {} values, but BatchAddV needs objects like {{{}: [F64], ...}}",
+                    identifier,
+                    Backend::field_type_to_string(other),
+                    BATCH_VECTOR_KEY
+                )),
+            },
+            Some(ElementType::Unknown) => None,
+            Some(other) => Some(format!(
+                "`{}` is {}, but BatchAddV needs an array of vector objects",
+                identifier,
+                other.label()
+            )),
+        };
+        if let Some(message) = problem {
+            self.out.push(Finding::new(&BATCH_VECTORS, loc, message));
+        }
+    }
+
+    fn batch_object(
+        &self,
+        vector_type: Option<&String>,
+        identifier: &str,
+        fields: &HashMap<String, FieldType>,
+    ) -> Option<String> {
+        match fields.get(BATCH_VECTOR_KEY) {
+            Some(FieldType::Array(inner)) if is_float(inner) => {}
+            Some(other) => {
+                return Some(format!(
+                    "`{}` of `{}` is {}, but it must be [F64]",
+                    BATCH_VECTOR_KEY,
+                    identifier,
+                    Backend::field_type_to_string(other)
+                ))
+            }
+            None => {
+                return Some(format!(
+                    "Objects of `{}` need a `{}: [F64]` key with the embedding",
+                    identifier, BATCH_VECTOR_KEY
+                ))
+            }
+        }
+        let vector_type = vector_type?;
+        infer::vector_schema(self.ctx.source, vector_type)?;
+        let owner = ElementType::Vector(vector_type.clone());
+        let mut unknown: Vec<&String> = fields
+            .keys()
+            .filter(|key| {
+                *key != BATCH_VECTOR_KEY
+                    && infer::field_declaration(self.ctx.source, &owner, key).is_none()
+            })
+            .collect();
+        unknown.sort();
+        let key = unknown.first()?;
+        Some(format!(
+            "`{}` of `{}` is not a field of {}",
+            key,
+            identifier,
+            owner.schema_item().unwrap_or_default()
+        ))
+    }
+
+    fn mmr_distance(&mut self, distance: &MMRDistance, loc: &Loc) {
+        let MMRDistance::Identifier(name) = distance else {
+            return;
+        };
+        let problem = match self.vars.get(name) {
+            None => Some(format!(
+                "`{}` is not a supported distance; use {}",
+                name,
+                supported_distances()
+            )),
+            Some(ElementType::Scalar(FieldType::String) | ElementType::Unknown) => None,
+            Some(other) => Some(format!(
+                "The distance must be a String of {}, but `{}` is {}",
+                supported_distances(),
+                name,
+                other.label()
+            )),
+        };
+        if let Some(message) = problem {
+            self.out.push(Finding::new(&MMR_DISTANCE, loc, message));
+        }
+    }
+}
+
+fn is_float(ty: &FieldType) -> bool {
+    matches!(ty, FieldType::F32 | FieldType::F64)
+}
+
+fn supported_distances() -> String {
+    MMR_DISTANCES
+        .iter()
+        .map(|d| format!("\"{}\"", d))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Visitor for VectorChecker<'_, '_> {
+    fn statement(&mut self, stmt: &Statement) {
+        infer::bind_loop(stmt, &mut self.vars);
+    }
+
+    fn statement_end(&mut self, stmt: &Statement) {
+        infer::bind_assignment(self.ctx.source, stmt, &mut self.vars);
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        match &expr.expr {
+            ExpressionType::AddVector(add) => {
+                self.data(add.vector_type.as_ref(), add.data.as_ref(), &add.loc)
+            }
+            ExpressionType::BatchAddVector(add) => {
+                if let Some(identifier) = &add.vec_identifier {
+                    self.batch(add.vector_type.as_ref(), identifier, &add.loc);
+                }
+            }
+            ExpressionType::SearchVector(sv) => self.search(sv),
+            _ => {}
+        }
+    }
+
+    fn traversal(&mut self, traversal: &Traversal) {
+        let types = infer::traversal_types(
+            self.ctx.source,
+            traversal,
+            &self.vars,
+            &ElementType::Unknown,
+        );
+        if let StartNode::SearchVector(sv) = &traversal.start {
+            self.search(sv);
+        }
+        for (i, step) in traversal.steps.iter().enumerate() {
+            match &step.step {
+                StepType::SearchVector(sv) => self.search(sv),
+                StepType::Node(graph_step) | StepType::Edge(graph_step) => {
+                    if let GraphStepType::SearchVector(sv) = &graph_step.step {
+                        self.search(sv);
+                    }
+                }
+                StepType::UpsertV(upsert) => {
+                    let vector_type = match &types[i] {
+                        ElementType::Vector(name) => Some(name),
+                        _ => None,
+                    };
+                    self.data(vector_type, upsert.data.as_ref(), &upsert.loc);
+                }
+                StepType::RerankMMR(mmr) => {
+                    if let Some(distance) = &mmr.distance {
+                        self.mmr_distance(distance, &mmr.loc);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn check(ctx: &LintContext, out: &mut Vec<Finding>) {
+    let source = ctx.source;
+    let mut checker = VectorChecker {
+        ctx,
+        vars: Vars::new(),
+        literals: Vec::new(),
+        embeds: Vec::new(),
+        out,
+    };
+    for query in &source.queries {
+        checker.vars = infer::parameters(query);
+        walk::walk_query(&mut checker, query);
+    }
+    let (literals, embeds) = (checker.literals, checker.embeds);
+
+    check_dimensions(&literals, out);
+
+    let has_model = source
+        .queries
+        .iter()
+        .any(|q| matches!(q.built_in_macro, Some(BuiltInMacro::Model(_))));
+    if !has_model {
+        for loc in &embeds {
+            out.push(Finding::new(
+                &EMBED_WITHOUT_MODEL,
+                loc,
+                "Embed needs an embedding model, but no query declares #[model(...)]",
+            ));
+        }
+    }
+}
+
+/// Vector literals whose length differs from the one most literals of their type use
+fn check_dimensions(literals: &[VectorLiteral], out: &mut Vec<Finding>) {
+    let mut by_type: HashMap<&str, Vec<&VectorLiteral>> = HashMap::new();
+    for literal in literals {
+        by_type
+            .entry(&literal.vector_type)
+            .or_default()
+            .push(literal);
+    }
+
+    for (vector_type, uses) in by_type {
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for literal in &uses {
+            *counts.entry(literal.dimensions).or_default() += 1;
+        }
+        if counts.len() < 2 {
+            continue;
+        }
+        // The most common length wins; ties go to the literal seen first
+        let mut reference = uses[0];
+        for literal in &uses {
+            if counts[&literal.dimensions] > counts[&reference.dimensions] {
+                reference = literal;
+            }
+        }
+        let expected = reference.dimensions;
+        for literal in uses.iter().filter(|literal| literal.dimensions != expected) {
+            let mut finding = Finding::new(
+                &VECTOR_DIMENSION,
+                &literal.loc,
+                format!(
+                    "This V::{} literal has {} dimensions, but {} of its uses have {}",
+                    vector_type, literal.dimensions, counts[&expected], expected
+                ),
+            );
+            finding.related.push((
+                reference.loc.clone(),
+                format!("A {}-dimensional V::{} literal", expected, vector_type),
+            ));
+            out.push(finding);
+        }
+    }
+}
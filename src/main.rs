@@ -1,17 +1,43 @@
 use dashmap::DashMap;
+use serde_json::json;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
-use helix_db::helixc::analyzer::{analyze, diagnostic::DiagnosticSeverity as HelixSeverity};
+use settings::Settings;
+
+use helix_db::helixc::analyzer::analyze;
 use helix_db::helixc::parser::types::{
-    Content, ExpressionType, FieldType, HxFile, Source, StatementType, StepType,
+    Content, ExpressionType, FieldType, HxFile, Loc, Query, Source, StatementType, StepType,
 };
 use helix_db::helixc::parser::HelixParser;
 
+mod cli;
+mod codegen;
+mod completion;
+mod deps;
+mod diagnostics;
+mod docs;
+mod fields;
+mod format;
+mod highlight;
+mod infer;
+mod lint;
+mod migration;
+mod ranges;
+mod schema_graph;
+mod settings;
+mod shapes;
+mod snippets;
+mod syntax;
+mod walk;
+mod workspace;
+
 /// Parse line and column from pest error messages like "--> 19:1"
 fn parse_error_location(error_msg: &str) -> (u32, u32) {
     // Look for pattern like "--> 19:1" or "at line 19, column 1"
@@ -30,11 +56,74 @@ fn parse_error_location(error_msg: &str) -> (u32, u32) {
     (0, 0)
 }
 
+/// Convert a parser location (1-based lines and columns) into an LSP range
+fn loc_to_range(loc: &Loc) -> Range {
+    Range {
+        start: Position {
+            line: loc.start.line.saturating_sub(1) as u32,
+            character: loc.start.column.saturating_sub(1) as u32,
+        },
+        end: Position {
+            line: loc.end.line.saturating_sub(1) as u32,
+            character: loc.end.column.saturating_sub(1) as u32,
+        },
+    }
+}
+
+/// Directory key used for `parsed_cache`: the parent directory of the document
+fn dir_key(uri: &Url) -> Option<String> {
+    uri.to_file_path()
+        .ok()
+        .and_then(|p| p.parent().map(|d| d.to_string_lossy().to_string()))
+}
+
+/// URI of the file a parser location points into, relative to the project directory
+fn loc_uri(dir_key: &str, loc: &Loc) -> Option<Url> {
+    let file_name = loc.filepath.as_deref().unwrap_or("");
+    Url::from_file_path(Path::new(dir_key).join(file_name)).ok()
+}
+
+/// The query of the file at `uri` around `line` (1-based), from the last successful
+/// parse. Its locations may lag behind the text, so take the last query starting before the line.
+fn query_at<'a>(source: &'a Source, dir_key: &str, uri: &Url, line: usize) -> Option<&'a Query> {
+    source
+        .queries
+        .iter()
+        .filter(|q| q.loc.start.line <= line && loc_uri(dir_key, &q.loc).as_ref() == Some(uri))
+        .max_by_key(|q| q.loc.start.line)
+}
+
+/// One project's diagnostics, every file it analyzed included, with the input key
+/// they were computed from
+#[derive(Debug, Clone)]
+struct ProjectReport {
+    key: u64,
+    diagnostics: HashMap<Url, Vec<Diagnostic>>,
+}
+
 #[derive(Debug)]
 struct Backend {
     client: Client,
     documents: DashMap<Url, String>,
     parsed_cache: DashMap<String, Source>,
+    /// Each project's last analysis
+    reports: DashMap<PathBuf, ProjectReport>,
+    /// Projects analyzing the files of each include directory alongside their own
+    includers: DashMap<PathBuf, Vec<PathBuf>>,
+    /// Workspace folders opened by the client, with each folder's settings
+    folders: DashMap<PathBuf, Settings>,
+    /// Settings outside any workspace folder, from `initializationOptions` or the global scope
+    global_settings: RwLock<Settings>,
+    /// The client answers `workspace/configuration`
+    configuration_support: AtomicBool,
+    /// The client lets us register for `workspace/didChangeConfiguration`
+    configuration_registration: AtomicBool,
+    /// The client pulls diagnostics, so they are not pushed on every change
+    pull_diagnostics: AtomicBool,
+    /// The client accepts `workspace/diagnostic/refresh`
+    diagnostic_refresh: AtomicBool,
+    /// Completion items may use snippet syntax
+    snippet_support: AtomicBool,
 }
 
 impl Backend {
@@ -43,43 +132,297 @@ impl Backend {
             client,
             documents: DashMap::new(),
             parsed_cache: DashMap::new(),
+            reports: DashMap::new(),
+            includers: DashMap::new(),
+            folders: DashMap::new(),
+            global_settings: RwLock::new(Settings::default()),
+            configuration_support: AtomicBool::new(false),
+            configuration_registration: AtomicBool::new(false),
+            pull_diagnostics: AtomicBool::new(false),
+            diagnostic_refresh: AtomicBool::new(false),
+            snippet_support: AtomicBool::new(false),
         }
     }
 
-    /// Get all .hx/.hql files in the same directory as the given file
-    fn get_sibling_files(&self, uri: &Url) -> Vec<std::path::PathBuf> {
-        let mut files = Vec::new();
-        if let Ok(path) = uri.to_file_path() {
-            if let Some(parent) = path.parent() {
-                if let Ok(entries) = fs::read_dir(parent) {
-                    for entry in entries.flatten() {
-                        let p = entry.path();
-                        if let Some(ext) = p.extension() {
-                            if ext == "hx" || ext == "hql" {
-                                files.push(p);
-                            }
-                        }
+    /// Settings of the workspace folder containing `dir`, with the folder root that
+    /// relative paths in them resolve against. Outside every folder the global
+    /// settings apply, relative to `dir` itself.
+    fn settings_for(&self, dir: &Path) -> (Settings, PathBuf) {
+        self.folders
+            .iter()
+            .filter(|entry| dir.starts_with(entry.key()))
+            .max_by_key(|entry| entry.key().components().count())
+            .map(|entry| (entry.value().clone(), entry.key().clone()))
+            .unwrap_or_else(|| {
+                let global = self
+                    .global_settings
+                    .read()
+                    .map(|s| s.clone())
+                    .unwrap_or_default();
+                (global, dir.to_path_buf())
+            })
+    }
+
+    /// Fetch the global and per-folder `helixql` settings through `workspace/configuration`
+    async fn refresh_settings(&self) {
+        let roots: Vec<PathBuf> = self.folders.iter().map(|e| e.key().clone()).collect();
+        if !self.configuration_support.load(Ordering::Relaxed) {
+            let global = self
+                .global_settings
+                .read()
+                .map(|s| s.clone())
+                .unwrap_or_default();
+            for root in roots {
+                self.folders.insert(root, global.clone());
+            }
+            return;
+        }
+
+        let mut items = vec![ConfigurationItem {
+            scope_uri: None,
+            section: Some(settings::SECTION.to_string()),
+        }];
+        items.extend(roots.iter().map(|root| ConfigurationItem {
+            scope_uri: Url::from_directory_path(root).ok(),
+            section: Some(settings::SECTION.to_string()),
+        }));
+
+        match self.client.configuration(items).await {
+            Ok(values) => {
+                let mut values = values.into_iter();
+                if let Some(global) = values.next().as_ref().and_then(Settings::from_value) {
+                    if let Ok(mut settings) = self.global_settings.write() {
+                        *settings = global;
                     }
                 }
+                let global = self
+                    .global_settings
+                    .read()
+                    .map(|s| s.clone())
+                    .unwrap_or_default();
+                for (root, value) in roots.into_iter().zip(values) {
+                    let folder = Settings::from_value(&value).unwrap_or_else(|| global.clone());
+                    self.folders.insert(root, folder);
+                }
+            }
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!("Failed to read helixql settings: {}", e),
+                    )
+                    .await;
             }
         }
-        files
     }
 
-    /// Parse all files in the directory and run analysis
+    /// Every Helix project in the workspace folders, plus those of open files outside them
+    fn known_projects(&self) -> Vec<PathBuf> {
+        let mut projects: Vec<PathBuf> = self
+            .folders
+            .iter()
+            .flat_map(|entry| workspace::find_projects(entry.key()))
+            .collect();
+        for entry in self.documents.iter() {
+            if let Some(dir) = entry
+                .key()
+                .to_file_path()
+                .ok()
+                .and_then(|p| p.parent().map(Path::to_path_buf))
+            {
+                projects.push(dir);
+            }
+        }
+        projects.sort();
+        projects.dedup();
+        projects
+    }
+
+    /// Analyze a set of projects, refreshing the cache and publishing their diagnostics
+    async fn analyze_projects(&self, projects: &[PathBuf]) {
+        let pull = self.pull_diagnostics.load(Ordering::Relaxed);
+        let mut files: Vec<Url> = Vec::new();
+        for dir in projects {
+            // Files the project analyzed before are republished too, in case it no longer does
+            if let Some(previous) = self.reports.get(dir) {
+                files.extend(previous.diagnostics.keys().cloned());
+            }
+            files.extend(self.compute_diagnostics(dir).into_keys());
+        }
+        if !pull {
+            files.sort();
+            files.dedup();
+            for file_uri in files {
+                let diagnostics = self.merged_diagnostics(&file_uri);
+                self.client
+                    .publish_diagnostics(file_uri, diagnostics, None)
+                    .await;
+            }
+        }
+        if pull && self.diagnostic_refresh.load(Ordering::Relaxed) {
+            let _ = self.client.workspace_diagnostic_refresh().await;
+        }
+    }
+
+    /// Diagnostics of a file from every project analyzing it. Files of an include
+    /// directory are analyzed by each project including them; a problem they all
+    /// find is reported once.
+    fn merged_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let mut reports: Vec<(PathBuf, Vec<Diagnostic>)> = self
+            .reports
+            .iter()
+            .filter_map(|entry| {
+                let diagnostics = entry.value().diagnostics.get(uri)?;
+                Some((entry.key().clone(), diagnostics.clone()))
+            })
+            .collect();
+        reports.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut merged: Vec<Diagnostic> = Vec::new();
+        for diagnostic in reports.into_iter().flat_map(|(_, diagnostics)| diagnostics) {
+            if !merged.contains(&diagnostic) {
+                merged.push(diagnostic);
+            }
+        }
+        merged
+    }
+
+    /// Result ID of a file's merged diagnostics, from the input keys of the projects analyzing it
+    fn result_id(&self, uri: &Url) -> String {
+        let mut keys: Vec<(PathBuf, u64)> = self
+            .reports
+            .iter()
+            .filter(|entry| entry.value().diagnostics.contains_key(uri))
+            .map(|entry| (entry.key().clone(), entry.value().key))
+            .collect();
+        keys.sort();
+        let keys: Vec<u64> = keys.into_iter().map(|(_, key)| key).collect();
+        workspace::result_id(&keys)
+    }
+
+    /// Record the include directories `project` analyzes, replacing those it used before
+    fn set_includes(&self, project: &Path, includes: &[PathBuf]) {
+        self.includers.retain(|include, projects| {
+            if !includes.contains(include) {
+                projects.retain(|p| p != project);
+            }
+            !projects.is_empty()
+        });
+        for include in includes {
+            let mut projects = self.includers.entry(include.clone()).or_default();
+            if !projects.iter().any(|p| p == project) {
+                projects.push(project.to_path_buf());
+            }
+        }
+    }
+
+    /// The project in `dir` and every project including its files
+    fn dependent_projects(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut projects = vec![dir.to_path_buf()];
+        if let Some(includers) = self.includers.get(dir) {
+            projects.extend(includers.iter().filter(|p| *p != dir).cloned());
+        }
+        projects
+    }
+
+    /// Forget a removed workspace folder and clear the diagnostics of its projects
+    async fn remove_folder(&self, root: &Path) {
+        self.folders.remove(root);
+        let removed: Vec<PathBuf> = self
+            .reports
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|dir| {
+                dir.starts_with(root) && !self.folders.iter().any(|f| dir.starts_with(f.key()))
+            })
+            .collect();
+
+        let removed_any = !removed.is_empty();
+        let mut files: Vec<Url> = Vec::new();
+        for dir in removed {
+            self.parsed_cache.remove(&*dir.to_string_lossy());
+            self.set_includes(&dir, &[]);
+            if let Some((_, report)) = self.reports.remove(&dir) {
+                files.extend(report.diagnostics.into_keys());
+            }
+        }
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            // The client only drops the removed projects' problems when it pulls again
+            if removed_any && self.diagnostic_refresh.load(Ordering::Relaxed) {
+                let _ = self.client.workspace_diagnostic_refresh().await;
+            }
+            return;
+        }
+        // Included files keep what the remaining projects report for them
+        files.sort();
+        files.dedup();
+        for file_uri in files {
+            let diagnostics = self.merged_diagnostics(&file_uri);
+            self.client
+                .publish_diagnostics(file_uri, diagnostics, None)
+                .await;
+        }
+    }
+
+    /// Re-analyze the project containing `uri` and the projects including its files,
+    /// pushing their diagnostics unless the client pulls them
     async fn analyze_workspace(&self, uri: &Url) {
-        let files = self.get_sibling_files(uri);
-        let dir_key = uri
+        let Some(dir) = uri
             .to_file_path()
             .ok()
-            .and_then(|p| p.parent().map(|d| d.to_string_lossy().to_string()))
-            .unwrap_or_default();
+            .and_then(|p| p.parent().map(Path::to_path_buf))
+        else {
+            return;
+        };
+        let projects = self.dependent_projects(&dir);
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            // Keep the cache current; the client pulls diagnostics after its own edits
+            for project in &projects {
+                self.compute_diagnostics(project);
+            }
+            return;
+        }
+        self.analyze_projects(&projects).await;
+    }
+
+    /// Parse all files in the directory and run analysis, caching the parsed source.
+    /// Every file of the project gets an entry, empty when it has no problems. While
+    /// the files and settings are unchanged, the previous analysis is reused.
+    fn compute_diagnostics(&self, dir: &Path) -> HashMap<Url, Vec<Diagnostic>> {
+        let (settings, folder) = self.settings_for(dir);
+        let includes: Vec<PathBuf> = settings
+            .include_dirs(&folder)
+            .into_iter()
+            .filter(|include| include != dir)
+            .collect();
+        self.set_includes(dir, &includes);
+        let mut files = workspace::project_files(dir);
+        for include in &includes {
+            files.extend(workspace::project_files(include));
+        }
+        let dir_key = dir.to_string_lossy().to_string();
+
+        // Files of the project are named relative to it; included files by their full
+        // path, which `loc_uri` resolves unchanged
+        let names: Vec<String> = files
+            .iter()
+            .map(|file_path| {
+                if file_path.parent() == Some(dir) {
+                    file_path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                } else {
+                    file_path.to_string_lossy().to_string()
+                }
+            })
+            .collect();
 
         let mut all_diagnostics: HashMap<Url, Vec<Diagnostic>> = HashMap::new();
 
         // Build Content structure with all files
         let mut hx_files = Vec::new();
-        for file_path in &files {
+        for (file_path, file_name) in files.iter().zip(&names) {
             let content = if let Ok(file_uri) = Url::from_file_path(file_path) {
                 if let Some(doc) = self.documents.get(&file_uri) {
                     doc.clone()
@@ -90,17 +433,31 @@ impl Backend {
                 fs::read_to_string(file_path).unwrap_or_default()
             };
 
-            let file_name = file_path
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_default();
-
             hx_files.push(HxFile {
-                name: file_name,
+                name: file_name.clone(),
                 content,
             });
         }
 
+        let lint_config = settings.lint_config(dir);
+        let mut lints: Vec<_> = lint_config.lints.iter().collect();
+        lints.sort_by(|a, b| a.0.cmp(b.0));
+        let key = workspace::input_key(
+            hx_files
+                .iter()
+                .map(|file| (file.name.as_str(), file.content.as_str())),
+            &format!(
+                "{:?} {:?} {:?} {}",
+                settings.features,
+                lints,
+                lint_config.search_k_threshold,
+                lint_config.docs_base()
+            ),
+        );
+        if let Some(report) = self.reports.get(dir).filter(|report| report.key == key) {
+            return report.diagnostics.clone();
+        }
+
         let content = Content {
             content: String::new(),
             files: hx_files,
@@ -111,103 +468,77 @@ impl Backend {
         match HelixParser::parse_source(&content) {
             Ok(source) => {
                 // Run analyzer
-                match analyze(&source) {
-                    Ok((diagnostics, _)) => {
-                        for diag in diagnostics {
-                            // Find file path for this diagnostic
-                            let file_name = diag
-                                .location
-                                .filepath
-                                .clone()
-                                .unwrap_or_default();
-
-                            // Find the file path that matches
-                            for file_path in &files {
-                                let name = file_path
-                                    .file_name()
-                                    .map(|n| n.to_string_lossy().to_string())
-                                    .unwrap_or_default();
-
-                                if name == file_name || file_name.is_empty() {
-                                    if let Ok(file_uri) = Url::from_file_path(file_path) {
-                                        let severity = match diag.severity {
-                                            HelixSeverity::Error => DiagnosticSeverity::ERROR,
-                                            HelixSeverity::Warning => DiagnosticSeverity::WARNING,
-                                            HelixSeverity::Info => DiagnosticSeverity::INFORMATION,
-                                            HelixSeverity::Hint => DiagnosticSeverity::HINT,
-                                            HelixSeverity::Empty => DiagnosticSeverity::INFORMATION,
-                                        };
-
-                                        let lsp_diag = Diagnostic {
-                                            range: Range {
-                                                start: Position {
-                                                    line: diag.location.start.line.saturating_sub(1)
-                                                        as u32,
-                                                    character: diag
-                                                        .location
-                                                        .start
-                                                        .column
-                                                        .saturating_sub(1)
-                                                        as u32,
-                                                },
-                                                end: Position {
-                                                    line: diag.location.end.line.saturating_sub(1)
-                                                        as u32,
-                                                    character: diag
-                                                        .location
-                                                        .end
-                                                        .column
-                                                        .saturating_sub(1)
-                                                        as u32,
-                                                },
-                                            },
-                                            severity: Some(severity),
-                                            source: Some("helixql".to_string()),
-                                            message: diag.message.clone(),
-                                            ..Default::default()
-                                        };
-                                        all_diagnostics
-                                            .entry(file_uri)
-                                            .or_default()
-                                            .push(lsp_diag);
-                                        break;
+                if settings.features.analyzer {
+                    match analyze(&source) {
+                        Ok((analyzer_diagnostics, _)) => {
+                            for diag in analyzer_diagnostics {
+                                // Find file path for this diagnostic
+                                let file_name = diag.location.filepath.clone().unwrap_or_default();
+
+                                // Find the file path that matches
+                                for (file_path, name) in files.iter().zip(&names) {
+                                    if *name == file_name || file_name.is_empty() {
+                                        if let Ok(file_uri) = Url::from_file_path(file_path) {
+                                            let lsp_diag = diagnostics::from_analyzer(
+                                                &dir_key,
+                                                &source,
+                                                &diag,
+                                                lint_config.docs_base(),
+                                            );
+                                            all_diagnostics
+                                                .entry(file_uri)
+                                                .or_default()
+                                                .push(lsp_diag);
+                                            break;
+                                        }
                                     }
                                 }
                             }
                         }
-                    }
-                    Err(e) => {
-                        // Analyzer error - try to extract location from error message
-                        let error_msg = format!("{}", e);
-                        let (line, col) = parse_error_location(&error_msg);
-
-                        if let Some(file_path) = files.first() {
-                            if let Ok(file_uri) = Url::from_file_path(file_path) {
-                                let diag = Diagnostic {
-                                    range: Range {
-                                        start: Position {
-                                            line,
-                                            character: col,
-                                        },
-                                        end: Position {
-                                            line,
-                                            character: col + 50,
+                        Err(e) => {
+                            // Analyzer error - try to extract location from error message
+                            let error_msg = format!("{}", e);
+                            let (line, col) = parse_error_location(&error_msg);
+
+                            if let Some(file_path) = files.first() {
+                                if let Ok(file_uri) = Url::from_file_path(file_path) {
+                                    let diag = Diagnostic {
+                                        range: Range {
+                                            start: Position {
+                                                line,
+                                                character: col,
+                                            },
+                                            end: Position {
+                                                line,
+                                                character: col + 50,
+                                            },
                                         },
-                                    },
-                                    severity: Some(DiagnosticSeverity::ERROR),
-                                    source: Some("helixql".to_string()),
-                                    message: format!("Analyzer error: {}", e),
-                                    ..Default::default()
-                                };
-                                all_diagnostics
-                                    .entry(file_uri)
-                                    .or_default()
-                                    .push(diag);
+                                        severity: Some(DiagnosticSeverity::ERROR),
+                                        source: Some("helixql".to_string()),
+                                        message: format!("Analyzer error: {}", e),
+                                        ..Default::default()
+                                    };
+                                    all_diagnostics.entry(file_uri).or_default().push(diag);
+                                }
                             }
                         }
                     }
                 }
 
+                // Run the server's own lint rules on top of the analyzer
+                if settings.features.lints {
+                    for (finding, severity) in lint::run(&source, &lint_config) {
+                        if let Some((file_uri, diag)) = Self::lint_diagnostic(
+                            &dir_key,
+                            &finding,
+                            severity,
+                            lint_config.docs_base(),
+                        ) {
+                            all_diagnostics.entry(file_uri).or_default().push(diag);
+                        }
+                    }
+                }
+
                 // Cache the parsed source
                 self.parsed_cache.insert(dir_key.clone(), source);
             }
@@ -234,24 +565,104 @@ impl Backend {
                             message: error_msg,
                             ..Default::default()
                         };
-                        all_diagnostics
-                            .entry(file_uri)
-                            .or_default()
-                            .push(diag);
+                        all_diagnostics.entry(file_uri).or_default().push(diag);
                     }
                 }
             }
         }
 
-        // Publish diagnostics for all files
         for file_path in &files {
             if let Ok(file_uri) = Url::from_file_path(file_path) {
-                let diagnostics = all_diagnostics.remove(&file_uri).unwrap_or_default();
-                self.client
-                    .publish_diagnostics(file_uri, diagnostics, None)
-                    .await;
+                all_diagnostics.entry(file_uri).or_default();
             }
         }
+        self.reports.insert(
+            dir.to_path_buf(),
+            ProjectReport {
+                key,
+                diagnostics: all_diagnostics.clone(),
+            },
+        );
+        all_diagnostics
+    }
+
+    /// Quick fixes carried in the `data` of lint and analyzer diagnostics
+    fn get_fix_actions(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeActionOrCommand> {
+        diagnostics
+            .iter()
+            .filter_map(|diag| {
+                let fix: lint::FixData =
+                    serde_json::from_value(diag.data.as_ref()?.get("fix")?.clone()).ok()?;
+                let mut changes = HashMap::new();
+                changes.insert(
+                    fix.uri.unwrap_or_else(|| uri.clone()),
+                    vec![TextEdit {
+                        range: fix.range,
+                        new_text: fix.new_text,
+                    }],
+                );
+                Some(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.title,
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diag.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(changes),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                }))
+            })
+            .collect()
+    }
+
+    /// Convert a lint finding into an LSP diagnostic for the file it points into
+    fn lint_diagnostic(
+        dir_key: &str,
+        finding: &lint::Finding,
+        severity: lint::Severity,
+        docs_base: &str,
+    ) -> Option<(Url, Diagnostic)> {
+        let file_uri = loc_uri(dir_key, &finding.loc)?;
+        let related: Vec<DiagnosticRelatedInformation> = finding
+            .related
+            .iter()
+            .filter_map(|(loc, message)| {
+                Some(DiagnosticRelatedInformation {
+                    location: Location {
+                        uri: loc_uri(dir_key, loc)?,
+                        range: loc_to_range(loc),
+                    },
+                    message: message.clone(),
+                })
+            })
+            .collect();
+
+        let diag = Diagnostic {
+            range: loc_to_range(&finding.loc),
+            severity: severity.to_lsp(),
+            code: Some(NumberOrString::String(finding.rule.code.to_string())),
+            code_description: Url::parse(&finding.rule.doc_url(docs_base))
+                .ok()
+                .map(|href| CodeDescription { href }),
+            source: Some("helixql".to_string()),
+            message: finding.message.clone(),
+            related_information: (!related.is_empty()).then_some(related),
+            tags: finding
+                .unnecessary
+                .then(|| vec![DiagnosticTag::UNNECESSARY]),
+            data: finding.fix.as_ref().map(|fix| {
+                json!({
+                    "fix": lint::FixData {
+                        title: fix.title.clone(),
+                        range: loc_to_range(&fix.loc),
+                        new_text: fix.new_text.clone(),
+                        uri: loc_uri(dir_key, &fix.loc).filter(|fix_uri| *fix_uri != file_uri),
+                    }
+                })
+            }),
+        };
+        Some((file_uri, diag))
     }
 
     /// Get word at position in document
@@ -375,169 +786,68 @@ impl Backend {
         }
     }
 
-    /// Get hover info for a field within an object access context (::{ })
-    fn get_field_context_hover(
-        &self,
-        uri: &Url,
-        position: Position,
-        field_name: &str,
-    ) -> Option<String> {
-        let doc = self.documents.get(uri)?;
-        let lines: Vec<&str> = doc.lines().collect();
-        let line = lines.get(position.line as usize)?;
-        let char_idx = position.character as usize;
-
-        if char_idx > line.len() {
+    /// Get the endpoint types an edge step expects, for hovers on `AddE`, `UpsertE`,
+    /// `ShortestPath*` and their `From`/`To` clauses
+    fn get_endpoint_hover(&self, uri: &Url, position: Position, word: &str) -> Option<String> {
+        let edge_step = matches!(word, "AddE" | "UpsertE" | "From" | "To");
+        if !edge_step && !word.starts_with("ShortestPath") {
             return None;
         }
-
-        let before_cursor = &line[..char_idx];
-
-        // Check if we're inside ::{ } by finding last "::{" before cursor
-        if let Some(brace_pos) = before_cursor.rfind("::{") {
-            // Check no unmatched "}" between "::{" and cursor
-            let between = &before_cursor[brace_pos + 3..];
-            if between.contains('}') {
-                return None; // We've exited the object access
-            }
-
-            // We're inside an object access - find the context type
-            // Search for N<Type>, E<Type>, or V<Type> pattern before the "::{"
-            let search_area = &before_cursor[..brace_pos];
-
-            // Use a simple reverse search for the pattern
-            if let Some((kind, type_name)) = self.find_context_type(search_area) {
-                // Look up the field in the schema
-                if let Some((field_type, full_type_path)) =
-                    self.lookup_field_in_schema(uri, &kind, &type_name, field_name)
-                {
-                    return Some(format!("**{}**: {} (from {})", field_name, field_type, full_type_path));
-                }
-            }
-        }
-
-        None
+        let text = self.documents.get(uri)?.clone();
+        let edge = syntax::edge_type_at(&text, position)?;
+        let source = self.parsed_cache.get(&dir_key(uri)?)?;
+        let schema = infer::edge_schema(&source, &edge)?;
+        Some(format!(
+            "**E::{}** expects `From: {}` and `To: {}`",
+            edge, schema.from.1, schema.to.1
+        ))
     }
 
-    /// Find the context type (N<Type>, E<Type>, or V<Type>) in a string
-    fn find_context_type(&self, text: &str) -> Option<(String, String)> {
-        // Search backwards for the most recent N<...>, E<...>, or V<...> pattern
-        let mut best_match: Option<(usize, String, String)> = None;
-
-        for pattern_char in ['N', 'E', 'V'] {
-            let pattern = format!("{}<", pattern_char);
-            if let Some(start) = text.rfind(&pattern) {
-                let after_bracket = &text[start + 2..];
-                if let Some(end) = after_bracket.find('>') {
-                    let type_name = &after_bracket[..end];
-                    // Only accept valid identifiers
-                    if !type_name.is_empty() && type_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                        if best_match.is_none() || start > best_match.as_ref().unwrap().0 {
-                            best_match = Some((start, pattern_char.to_string(), type_name.to_string()));
-                        }
-                    }
-                }
-            }
+    /// Get the resolved weight and heuristic types for hovers on weighted `ShortestPath*` steps
+    fn get_path_hover(&self, uri: &Url, position: Position, word: &str) -> Option<String> {
+        if !matches!(word, "ShortestPathDijkstras" | "ShortestPathAStar") {
+            return None;
         }
-
-        best_match.map(|(_, kind, name)| (kind, name))
+        let key = dir_key(uri)?;
+        let source = self.parsed_cache.get(&key)?;
+        let line = position.line as usize + 1;
+        let query = query_at(&source, &key, uri, line)?;
+        let lines = infer::describe_paths(&source, query, line);
+        (!lines.is_empty()).then(|| lines.join("\n\n"))
     }
 
-    /// Look up a field type from a schema type
-    fn lookup_field_in_schema(
-        &self,
-        uri: &Url,
-        type_kind: &str, // "N", "E", or "V"
-        type_name: &str,
-        field_name: &str,
-    ) -> Option<(String, String)> // Returns (field_type, full_type_path)
-    {
-        let dir_key = uri
-            .to_file_path()
-            .ok()
-            .and_then(|p| p.parent().map(|d| d.to_string_lossy().to_string()))?;
+    /// Get hover info for a schema field at a use site, resolving its owner through
+    /// the element types inferred along the query
+    fn get_field_hover(&self, uri: &Url, position: Position) -> Option<String> {
+        let text = self.documents.get(uri)?.clone();
+        let name = highlight::field_at(&text, position)?;
+        let key = dir_key(uri)?;
+        let source = self.parsed_cache.get(&key)?;
 
-        let source = self.parsed_cache.get(&dir_key)?;
+        let line = position.line as usize + 1; // Parser uses 1-based line numbers
+        let query = query_at(&source, &key, uri, line)?;
 
-        // Check for built-in fields first
-        match type_kind {
-            "N" => {
-                // Built-in node fields
-                match field_name {
-                    "id" => return Some(("Uuid".to_string(), format!("N::{}", type_name))),
-                    "label" => return Some(("String".to_string(), format!("N::{}", type_name))),
-                    _ => {}
-                }
-            }
-            "E" => {
-                // Built-in edge fields
-                match field_name {
-                    "id" => return Some(("Uuid".to_string(), format!("E::{}", type_name))),
-                    "label" => return Some(("String".to_string(), format!("E::{}", type_name))),
-                    "from_node" => return Some(("Uuid".to_string(), format!("E::{}", type_name))),
-                    "to_node" => return Some(("Uuid".to_string(), format!("E::{}", type_name))),
-                    _ => {}
-                }
-            }
-            "V" => {
-                // Built-in vector fields
-                match field_name {
-                    "id" => return Some(("Uuid".to_string(), format!("V::{}", type_name))),
-                    "label" => return Some(("String".to_string(), format!("V::{}", type_name))),
-                    "data" => return Some(("[F64]".to_string(), format!("V::{}", type_name))),
-                    "score" => return Some(("F64".to_string(), format!("V::{}", type_name))),
-                    _ => {}
-                }
-            }
-            _ => {}
+        let sections: Vec<String> = fields::owners_at(&source, query, name, line)
+            .iter()
+            .filter_map(|owner| fields::describe(&source, owner, name))
+            .collect();
+        if sections.is_empty() {
+            return None;
         }
+        Some(sections.join("\n\n---\n\n"))
+    }
 
-        // Search in schema definitions
-        for (_version, schema) in &source.schema {
-            match type_kind {
-                "N" => {
-                    for node in &schema.node_schemas {
-                        if node.name.1 == type_name {
-                            for field in &node.fields {
-                                if field.name == field_name {
-                                    let type_str = Self::field_type_to_string(&field.field_type);
-                                    return Some((type_str, format!("N::{}", type_name)));
-                                }
-                            }
-                        }
-                    }
-                }
-                "E" => {
-                    for edge in &schema.edge_schemas {
-                        if edge.name.1 == type_name {
-                            if let Some(props) = &edge.properties {
-                                for field in props {
-                                    if field.name == field_name {
-                                        let type_str = Self::field_type_to_string(&field.field_type);
-                                        return Some((type_str, format!("E::{}", type_name)));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                "V" => {
-                    for vector in &schema.vector_schemas {
-                        if vector.name == type_name {
-                            for field in &vector.fields {
-                                if field.name == field_name {
-                                    let type_str = Self::field_type_to_string(&field.field_type);
-                                    return Some((type_str, format!("V::{}", type_name)));
-                                }
-                            }
-                        }
-                    }
-                }
-                _ => {}
-            }
+    /// Get the response shape of the query under the cursor, for hovers on `RETURN`
+    /// and on the query's name
+    fn get_return_hover(&self, uri: &Url, position: Position, word: &str) -> Option<String> {
+        let key = dir_key(uri)?;
+        let source = self.parsed_cache.get(&key)?;
+        let line = position.line as usize + 1;
+        let query = query_at(&source, &key, uri, line)?;
+        if word != "RETURN" && word != query.name {
+            return None;
         }
-
-        None
+        Some(shapes::describe(&source, query))
     }
 
     /// Get variable type from assignment context
@@ -559,7 +869,7 @@ impl Backend {
 
             if line >= query_start && line <= query_end {
                 // Search statements for assignment
-                return self.find_variable_in_statements(&query.statements, word);
+                return self.find_variable_in_statements(&source, query, &query.statements, word);
             }
         }
 
@@ -599,6 +909,8 @@ impl Backend {
     /// Search statements for a variable assignment and infer its type
     fn find_variable_in_statements(
         &self,
+        source: &Source,
+        query: &Query,
         statements: &[helix_db::helixc::parser::types::Statement],
         word: &str,
     ) -> Option<String> {
@@ -606,13 +918,21 @@ impl Backend {
             match &stmt.statement {
                 StatementType::Assignment(assignment) => {
                     if assignment.variable == word {
+                        // Follow traversals step by step, so e.g. GROUP_BY shows its groups
+                        if let ExpressionType::Traversal(_) = &assignment.value.expr {
+                            let vars = infer::scope_at(source, query, stmt.loc.start.line);
+                            let ty = infer::expression_type(source, &assignment.value, &vars);
+                            if ty != infer::ElementType::Unknown {
+                                return Some(format!("**variable**: {}", ty.label()));
+                            }
+                        }
                         return self.infer_expression_type(&assignment.value.expr);
                     }
                 }
                 StatementType::ForLoop(for_loop) => {
                     // Check nested statements in for loop
                     if let Some(result) =
-                        self.find_variable_in_statements(&for_loop.statements, word)
+                        self.find_variable_in_statements(source, query, &for_loop.statements, word)
                     {
                         return Some(result);
                     }
@@ -694,181 +1014,6 @@ impl Backend {
         }
     }
 
-    /// Get documentation for a keyword/function
-    fn get_keyword_docs(&self, word: &str) -> Option<String> {
-        let docs: HashMap<&str, &str> = HashMap::from([
-            // Query keywords
-            (
-                "QUERY",
-                "Defines a new query function.\n\nSyntax: `QUERY name(param: Type) => { ... }`",
-            ),
-            ("RETURN", "Returns values from a query.\n\nSyntax: `RETURN expression`"),
-            ("WHERE", "Filters results based on a condition.\n\nSyntax: `::WHERE(condition)`"),
-            ("FOR", "Iterates over a collection.\n\nSyntax: `FOR item IN collection`"),
-            ("IN", "Used with FOR loops or set membership."),
-            ("EXISTS", "Checks if a value exists."),
-            ("AND", "Logical AND operator."),
-            ("OR", "Logical OR operator."),
-            ("NONE", "Represents no value or empty result."),
-            ("DROP", "Removes/deletes data."),
-            ("MIGRATION", "Defines a schema migration."),
-            ("FIRST", "Returns the first element of a collection."),
-            ("AS", "Alias or type casting operator."),
-            ("UNIQUE", "Ensures uniqueness constraint."),
-            // Schema types
-            (
-                "N",
-                "Node type definition.\n\nSyntax: `N::TypeName { field: Type }`",
-            ),
-            (
-                "E",
-                "Edge type definition.\n\nSyntax: `E::TypeName { From: NodeType, To: NodeType }`",
-            ),
-            ("V", "Vector type definition.\n\nSyntax: `V::TypeName { ... }`"),
-            // Traversal operators
-            (
-                "Out",
-                "Traverse outgoing edges.\n\nSyntax: `node::Out<EdgeType>`",
-            ),
-            (
-                "In",
-                "Traverse incoming edges.\n\nSyntax: `node::In<EdgeType>`",
-            ),
-            (
-                "OutE",
-                "Get outgoing edge objects.\n\nSyntax: `node::OutE<EdgeType>`",
-            ),
-            (
-                "InE",
-                "Get incoming edge objects.\n\nSyntax: `node::InE<EdgeType>`",
-            ),
-            ("FromN", "Get the source node of an edge."),
-            ("ToN", "Get the target node of an edge."),
-            ("FromV", "Get the source vertex of a vector edge."),
-            ("ToV", "Get the target vertex of a vector edge."),
-            ("ShortestPath", "Find shortest path between nodes."),
-            ("ShortestPathBFS", "Find shortest path using BFS algorithm."),
-            (
-                "ShortestPathDijkstras",
-                "Find shortest path using Dijkstra's algorithm.",
-            ),
-            ("ShortestPathAStar", "Find shortest path using A* algorithm."),
-            (
-                "COUNT",
-                "Count the number of elements.\n\nSyntax: `collection::COUNT`",
-            ),
-            (
-                "RANGE",
-                "Select a range of elements.\n\nSyntax: `::RANGE(start, end)`",
-            ),
-            (
-                "UPDATE",
-                "Update node/edge properties.\n\nSyntax: `::UPDATE { field: value }`",
-            ),
-            ("ID", "Get the unique identifier of a node/edge."),
-            ("PREFILTER", "Pre-filter results before main query execution."),
-            ("AGGREGATE_BY", "Aggregate results by a field."),
-            ("GROUP_BY", "Group results by a field."),
-            ("ORDER", "Order results.\n\nSyntax: `::ORDER(field, Asc|Desc)`"),
-            // Comparison operators
-            ("GT", "Greater than comparison."),
-            ("GTE", "Greater than or equal comparison."),
-            ("LT", "Less than comparison."),
-            ("LTE", "Less than or equal comparison."),
-            ("EQ", "Equality comparison."),
-            ("NEQ", "Not equal comparison."),
-            ("CONTAINS", "Check if collection contains value."),
-            ("IS_IN", "Check if value is in collection."),
-            // Creation operators
-            (
-                "AddN",
-                "Add a new node.\n\nSyntax: `AddN<NodeType> { field: value }`",
-            ),
-            (
-                "AddE",
-                "Add a new edge.\n\nSyntax: `AddE<EdgeType> { From: node1, To: node2 }`",
-            ),
-            (
-                "AddV",
-                "Add a new vector.\n\nSyntax: `AddV<VectorType> { ... }`",
-            ),
-            ("BatchAddV", "Add multiple vectors in batch."),
-            (
-                "SearchV",
-                "Search vectors by similarity.\n\nSyntax: `SearchV<VectorType>(query, k)`",
-            ),
-            ("SearchBM25", "Search using BM25 algorithm."),
-            ("Embed", "Generate embeddings for text."),
-            ("UpsertN", "Insert or update a node."),
-            ("UpsertE", "Insert or update an edge."),
-            ("UpsertV", "Insert or update a vector."),
-            ("RerankRRF", "Rerank results using Reciprocal Rank Fusion."),
-            (
-                "RerankMMR",
-                "Rerank results using Maximal Marginal Relevance.",
-            ),
-            // Properties
-            (
-                "From",
-                "Source node of an edge.\n\nSyntax: `From: NodeType`",
-            ),
-            ("To", "Target node of an edge.\n\nSyntax: `To: NodeType`"),
-            ("Properties", "Define edge properties."),
-            ("INDEX", "Create an index on a field."),
-            ("DEFAULT", "Set a default value for a field."),
-            ("NOW", "Current timestamp function."),
-            // Order direction
-            ("Asc", "Ascending order."),
-            ("Desc", "Descending order."),
-            // Math functions
-            ("ADD", "Addition operation."),
-            ("SUB", "Subtraction operation."),
-            ("MUL", "Multiplication operation."),
-            ("DIV", "Division operation."),
-            ("POW", "Power/exponentiation operation."),
-            ("MOD", "Modulo operation."),
-            ("ABS", "Absolute value."),
-            ("SQRT", "Square root."),
-            ("LN", "Natural logarithm."),
-            ("LOG10", "Base-10 logarithm."),
-            ("LOG", "Logarithm."),
-            ("EXP", "Exponential function."),
-            ("CEIL", "Ceiling function."),
-            ("FLOOR", "Floor function."),
-            ("ROUND", "Round to nearest integer."),
-            ("SIN", "Sine function."),
-            ("COS", "Cosine function."),
-            ("TAN", "Tangent function."),
-            ("ASIN", "Arc sine function."),
-            ("ACOS", "Arc cosine function."),
-            ("ATAN", "Arc tangent function."),
-            ("ATAN2", "Two-argument arc tangent."),
-            ("PI", "Mathematical constant pi."),
-            ("MIN", "Minimum value."),
-            ("MAX", "Maximum value."),
-            ("SUM", "Sum of values."),
-            ("AVG", "Average of values."),
-            // Types
-            ("String", "String/text type."),
-            ("Boolean", "Boolean (true/false) type."),
-            ("F32", "32-bit floating point number."),
-            ("F64", "64-bit floating point number."),
-            ("I8", "8-bit signed integer."),
-            ("I16", "16-bit signed integer."),
-            ("I32", "32-bit signed integer."),
-            ("I64", "64-bit signed integer."),
-            ("U8", "8-bit unsigned integer."),
-            ("U16", "16-bit unsigned integer."),
-            ("U32", "32-bit unsigned integer."),
-            ("U64", "64-bit unsigned integer."),
-            ("U128", "128-bit unsigned integer."),
-            ("Date", "Date/timestamp type."),
-            ("Uuid", "UUID type."),
-        ]);
-
-        docs.get(word).map(|s| s.to_string())
-    }
-
     /// Find definition location for a type reference
     fn find_definition(&self, uri: &Url, word: &str) -> Option<Location> {
         let dir_key = uri
@@ -953,181 +1098,272 @@ impl Backend {
         None
     }
 
+    /// Call hierarchy item for a query
+    fn query_hierarchy_item(dir_key: &str, query: &Query) -> Option<CallHierarchyItem> {
+        let range = loc_to_range(&query.loc);
+        Some(CallHierarchyItem {
+            name: query.name.clone(),
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            detail: Some("QUERY".to_string()),
+            uri: loc_uri(dir_key, &query.loc)?,
+            range,
+            selection_range: range,
+            data: Some(json!({ "query": query.name })),
+        })
+    }
+
+    /// Call hierarchy item for a schema item such as `N::Customer`
+    fn schema_hierarchy_item(&self, uri: &Url, item: &str) -> Option<CallHierarchyItem> {
+        let name = item.split("::").nth(1)?;
+        let location = self.find_definition(uri, name)?;
+        Some(CallHierarchyItem {
+            name: item.to_string(),
+            kind: SymbolKind::CLASS,
+            tags: None,
+            detail: Some("schema".to_string()),
+            uri: location.uri,
+            range: location.range,
+            selection_range: location.range,
+            data: Some(json!({ "item": item })),
+        })
+    }
+
+    /// Offer a MIGRATION stub for every pair of consecutive schema versions without one
+    fn get_migration_actions(&self, uri: &Url) -> Vec<CodeActionOrCommand> {
+        let mut actions = Vec::new();
+        let Some(dir_key) = uri
+            .to_file_path()
+            .ok()
+            .and_then(|p| p.parent().map(|d| d.to_string_lossy().to_string()))
+        else {
+            return actions;
+        };
+        let Some(source) = self.parsed_cache.get(&dir_key) else {
+            return actions;
+        };
+
+        for (from, to) in migration::missing_migrations(&source) {
+            let Some(stub) = migration::scaffold(&source, from, to) else {
+                continue;
+            };
+
+            // Append the stub to the file that declares the newer schema version
+            let file_name = source.schema[&to].loc.filepath.as_deref().unwrap_or("");
+            let file_path = Path::new(&dir_key).join(file_name);
+            let Ok(file_uri) = Url::from_file_path(&file_path) else {
+                continue;
+            };
+            let text = match self.documents.get(&file_uri) {
+                Some(doc) => doc.clone(),
+                None => fs::read_to_string(&file_path).unwrap_or_default(),
+            };
+            let line_count = text.lines().count() as u32;
+            let end = match text.lines().last() {
+                Some(last) if !text.ends_with('\n') => Position {
+                    line: line_count - 1,
+                    character: last.len() as u32,
+                },
+                _ => Position {
+                    line: line_count,
+                    character: 0,
+                },
+            };
+            let separator = if text.ends_with('\n') { "\n" } else { "\n\n" };
+
+            let mut changes = HashMap::new();
+            changes.insert(
+                file_uri,
+                vec![TextEdit {
+                    range: Range { start: end, end },
+                    new_text: format!("{}{}", separator, stub),
+                }],
+            );
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Generate MIGRATION schema::{} => schema::{}", from, to),
+                kind: Some(CodeActionKind::REFACTOR),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }));
+        }
+
+        actions
+    }
+
     /// Get completion items based on context
     fn get_completions(&self, uri: &Url, position: Position) -> Vec<CompletionItem> {
-        let mut items = Vec::new();
         let doc = match self.documents.get(uri) {
             Some(d) => d.clone(),
-            None => return items,
+            None => return Vec::new(),
         };
 
-        let lines: Vec<&str> = doc.lines().collect();
-        let line = match lines.get(position.line as usize) {
-            Some(l) => *l,
-            None => return items,
-        };
+        let key = dir_key(uri);
+        let source = key.as_ref().and_then(|key| self.parsed_cache.get(key));
+        let names = source
+            .as_ref()
+            .map(|source| snippets::SchemaNames::from_source(source))
+            .unwrap_or_default();
 
-        let char_idx = position.character as usize;
-        let prefix = if char_idx <= line.len() {
-            &line[..char_idx]
-        } else {
-            line
-        };
+        // Parameters and variables in scope, from the last successful parse
+        let line = position.line as usize + 1;
+        let scope = source
+            .as_ref()
+            .zip(key.as_ref())
+            .and_then(|(source, key)| {
+                let query = query_at(source, key, uri, line)?;
+                Some(infer::scope_at(source, query, line))
+            })
+            .unwrap_or_default();
 
-        // Check context
-        let after_n_bracket =
-            prefix.ends_with("N<") || prefix.contains("N<") && !prefix.contains('>');
-        let after_e_bracket =
-            prefix.ends_with("E<") || prefix.contains("E<") && !prefix.contains('>');
-        let after_v_bracket =
-            prefix.ends_with("V<") || prefix.contains("V<") && !prefix.contains('>');
-        let after_double_colon = prefix.ends_with("::");
+        // Ask the grammar what may follow the cursor
+        let context = completion::context_at(&doc, position);
+        let mut items = match &context {
+            Some(context) => completion::items(context, &names),
+            None => completion::keyword_items(),
+        };
+        if let Some(ty) = context
+            .as_ref()
+            .and_then(|context| context.variable())
+            .and_then(|name| scope.get(name))
+        {
+            completion::retain_steps(&mut items, ty);
+        }
+        if context
+            .as_ref()
+            .is_none_or(|context| context.accepts_variables())
+        {
+            items.splice(0..0, completion::variable_items(&scope));
+        }
 
-        let dir_key = uri
-            .to_file_path()
-            .ok()
-            .and_then(|p| p.parent().map(|d| d.to_string_lossy().to_string()));
-
-        // Type completions after N<, E<, V<
-        if let Some(ref key) = dir_key {
-            if let Some(source) = self.parsed_cache.get(key) {
-                if after_n_bracket {
-                    for (_version, schema) in &source.schema {
-                        for node in &schema.node_schemas {
-                            items.push(CompletionItem {
-                                label: node.name.1.clone(),
-                                kind: Some(CompletionItemKind::CLASS),
-                                detail: Some("Node type".to_string()),
-                                ..Default::default()
-                            });
-                        }
-                    }
-                    return items;
-                }
+        // Prefer snippets, with type choices from the parsed schema
+        if self.snippet_support.load(Ordering::Relaxed) {
+            let mut snippet_items = snippets::construct_snippets(&names);
+            snippet_items.extend(snippets::step_snippets(&names));
+            completion::with_snippets(items, &snippet_items)
+        } else {
+            items
+        }
+    }
+}
 
-                if after_e_bracket {
-                    for (_version, schema) in &source.schema {
-                        for edge in &schema.edge_schemas {
-                            items.push(CompletionItem {
-                                label: edge.name.1.clone(),
-                                kind: Some(CompletionItemKind::CLASS),
-                                detail: Some("Edge type".to_string()),
-                                ..Default::default()
-                            });
-                        }
-                    }
-                    return items;
-                }
+impl Backend {
+    /// `helixql/dependencyGraph`: which queries read, create, update or drop each schema item
+    async fn dependency_graph(
+        &self,
+        params: deps::DependencyGraphParams,
+    ) -> Result<deps::DependencyGraph> {
+        let graph = dir_key(&params.text_document.uri)
+            .and_then(|key| self.parsed_cache.get(&key))
+            .map(|source| deps::dependency_graph(&source))
+            .unwrap_or_default();
+        Ok(graph)
+    }
 
-                if after_v_bracket {
-                    for (_version, schema) in &source.schema {
-                        for vector in &schema.vector_schemas {
-                            items.push(CompletionItem {
-                                label: vector.name.clone(),
-                                kind: Some(CompletionItemKind::CLASS),
-                                detail: Some("Vector type".to_string()),
-                                ..Default::default()
-                            });
-                        }
-                    }
-                    return items;
-                }
-            }
-        }
+    /// `helixql/lintRules`: every lint rule with its code, docs and effective severity
+    async fn lint_rules(&self, params: lint::LintRulesParams) -> Result<Vec<lint::RuleInfo>> {
+        let config = dir_key(&params.text_document.uri)
+            .map(|key| {
+                let dir = Path::new(&key);
+                self.settings_for(dir).0.lint_config(dir)
+            })
+            .unwrap_or_default();
+        Ok(lint::describe_rules(&config))
+    }
 
-        // Traversal completions after ::
-        if after_double_colon {
-            let traversals = [
-                ("Out", "Traverse outgoing edges"),
-                ("In", "Traverse incoming edges"),
-                ("OutE", "Get outgoing edge objects"),
-                ("InE", "Get incoming edge objects"),
-                ("FromN", "Get source node"),
-                ("ToN", "Get target node"),
-                ("FromV", "Get source vertex"),
-                ("ToV", "Get target vertex"),
-                ("From", "Edge source (in AddE chain)"),
-                ("To", "Edge target (in AddE chain)"),
-                ("WHERE", "Filter by condition"),
-                ("COUNT", "Count elements"),
-                ("RANGE", "Select range"),
-                ("UPDATE", "Update properties"),
-                ("ID", "Get identifier"),
-                ("GT", "Greater than"),
-                ("GTE", "Greater than or equal"),
-                ("LT", "Less than"),
-                ("LTE", "Less than or equal"),
-                ("EQ", "Equal"),
-                ("NEQ", "Not equal"),
-                ("CONTAINS", "Contains value"),
-                ("IS_IN", "Value in set"),
-                ("PREFILTER", "Pre-filter results"),
-                ("AGGREGATE_BY", "Aggregate by field"),
-                ("GROUP_BY", "Group by field"),
-                ("ORDER", "Order results"),
-                ("ShortestPath", "Find shortest path"),
-                ("ShortestPathBFS", "Shortest path (BFS)"),
-                ("ShortestPathDijkstras", "Shortest path (Dijkstra)"),
-                ("ShortestPathAStar", "Shortest path (A*)"),
-            ];
-
-            for (name, detail) in traversals {
-                items.push(CompletionItem {
-                    label: name.to_string(),
-                    kind: Some(CompletionItemKind::METHOD),
-                    detail: Some(detail.to_string()),
-                    ..Default::default()
-                });
-            }
-            return items;
-        }
+    /// `helixql/returnShape`: the response of each query as a TypeScript type and a JSON Schema
+    async fn return_shape(
+        &self,
+        params: shapes::ReturnShapeParams,
+    ) -> Result<Vec<shapes::ReturnShapeInfo>> {
+        let shapes = dir_key(&params.text_document.uri)
+            .and_then(|key| self.parsed_cache.get(&key))
+            .map(|source| shapes::describe_queries(&source, params.query.as_deref()))
+            .unwrap_or_default();
+        Ok(shapes)
+    }
 
-        // Default keyword completions
-        let keywords = [
-            ("QUERY", "Define a query", CompletionItemKind::KEYWORD),
-            ("RETURN", "Return values", CompletionItemKind::KEYWORD),
-            ("WHERE", "Filter condition", CompletionItemKind::KEYWORD),
-            ("FOR", "Loop iteration", CompletionItemKind::KEYWORD),
-            ("IN", "In operator", CompletionItemKind::KEYWORD),
-            ("AND", "Logical AND", CompletionItemKind::KEYWORD),
-            ("OR", "Logical OR", CompletionItemKind::KEYWORD),
-            ("EXISTS", "Check existence", CompletionItemKind::KEYWORD),
-            ("NONE", "No value", CompletionItemKind::KEYWORD),
-            ("DROP", "Delete data", CompletionItemKind::KEYWORD),
-            ("MIGRATION", "Schema migration", CompletionItemKind::KEYWORD),
-            ("FIRST", "First element", CompletionItemKind::KEYWORD),
-            ("AS", "Alias/cast", CompletionItemKind::KEYWORD),
-            ("UNIQUE", "Uniqueness constraint", CompletionItemKind::KEYWORD),
-            ("AddN", "Add node", CompletionItemKind::FUNCTION),
-            ("AddE", "Add edge", CompletionItemKind::FUNCTION),
-            ("AddV", "Add vector", CompletionItemKind::FUNCTION),
-            ("BatchAddV", "Batch add vectors", CompletionItemKind::FUNCTION),
-            ("SearchV", "Search vectors", CompletionItemKind::FUNCTION),
-            ("SearchBM25", "BM25 search", CompletionItemKind::FUNCTION),
-            ("Embed", "Generate embeddings", CompletionItemKind::FUNCTION),
-            ("UpsertN", "Upsert node", CompletionItemKind::FUNCTION),
-            ("UpsertE", "Upsert edge", CompletionItemKind::FUNCTION),
-            ("UpsertV", "Upsert vector", CompletionItemKind::FUNCTION),
-            ("RerankRRF", "Rerank (RRF)", CompletionItemKind::FUNCTION),
-            ("RerankMMR", "Rerank (MMR)", CompletionItemKind::FUNCTION),
-        ];
-
-        for (name, detail, kind) in keywords {
-            items.push(CompletionItem {
-                label: name.to_string(),
-                kind: Some(kind),
-                detail: Some(detail.to_string()),
-                ..Default::default()
-            });
-        }
+    /// `helixql/schemaGraph`: render the schema as a Mermaid, Graphviz DOT or JSON diagram
+    async fn schema_graph(
+        &self,
+        params: schema_graph::SchemaGraphParams,
+    ) -> Result<Option<schema_graph::SchemaGraphResult>> {
+        let Some(source) =
+            dir_key(&params.text_document.uri).and_then(|key| self.parsed_cache.get(&key))
+        else {
+            return Ok(None);
+        };
+        let Some(graph) = schema_graph::build(&source, params.version) else {
+            return Ok(None);
+        };
 
-        items
+        Ok(Some(schema_graph::SchemaGraphResult {
+            format: params.format,
+            content: schema_graph::render(&graph, params.format),
+            graph,
+        }))
     }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        for root in workspace::roots_from_params(&params) {
+            self.folders.insert(root, Settings::default());
+        }
+        if let Some(global) = params
+            .initialization_options
+            .as_ref()
+            .and_then(Settings::from_value)
+        {
+            if let Ok(mut settings) = self.global_settings.write() {
+                *settings = global;
+            }
+        }
+
+        let capabilities = &params.capabilities;
+        self.snippet_support.store(
+            capabilities
+                .text_document
+                .as_ref()
+                .and_then(|t| t.completion.as_ref())
+                .and_then(|c| c.completion_item.as_ref())
+                .and_then(|i| i.snippet_support)
+                .unwrap_or(false),
+            Ordering::Relaxed,
+        );
+        let workspace_caps = capabilities.workspace.as_ref();
+        self.configuration_support.store(
+            workspace_caps
+                .and_then(|w| w.configuration)
+                .unwrap_or(false),
+            Ordering::Relaxed,
+        );
+        self.configuration_registration.store(
+            workspace_caps
+                .and_then(|w| w.did_change_configuration.as_ref())
+                .and_then(|d| d.dynamic_registration)
+                .unwrap_or(false),
+            Ordering::Relaxed,
+        );
+        self.pull_diagnostics.store(
+            capabilities
+                .text_document
+                .as_ref()
+                .and_then(|t| t.diagnostic.as_ref())
+                .is_some(),
+            Ordering::Relaxed,
+        );
+        self.diagnostic_refresh.store(
+            workspace_caps
+                .and_then(|w| w.diagnostic.as_ref())
+                .and_then(|d| d.refresh_support)
+                .unwrap_or(false),
+            Ordering::Relaxed,
+        );
+
         Ok(InitializeResult {
             server_info: Some(ServerInfo {
                 name: "HelixQL Language Server".to_string(),
@@ -1143,6 +1379,36 @@ impl LanguageServer for Backend {
                     ..Default::default()
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                document_highlight_provider: Some(OneOf::Left(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![codegen::COMMAND.to_string()],
+                    work_done_progress_options: Default::default(),
+                }),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
+                    DiagnosticOptions {
+                        identifier: Some("helixql".to_string()),
+                        inter_file_dependencies: true,
+                        workspace_diagnostics: true,
+                        ..Default::default()
+                    },
+                )),
                 ..Default::default()
             },
         })
@@ -1152,6 +1418,20 @@ impl LanguageServer for Backend {
         self.client
             .log_message(MessageType::INFO, "HelixQL Language Server initialized")
             .await;
+
+        if self.configuration_registration.load(Ordering::Relaxed) {
+            let registration = Registration {
+                id: "helixql-configuration".to_string(),
+                method: "workspace/didChangeConfiguration".to_string(),
+                register_options: None,
+            };
+            let _ = self.client.register_capability(vec![registration]).await;
+        }
+
+        // Index every workspace folder up front
+        self.refresh_settings().await;
+        let projects = self.known_projects();
+        self.analyze_projects(&projects).await;
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -1181,17 +1461,220 @@ impl LanguageServer for Backend {
         self.documents.remove(&params.text_document.uri);
     }
 
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        if self.configuration_support.load(Ordering::Relaxed) {
+            self.refresh_settings().await;
+        } else if let Some(global) = Settings::from_value(&params.settings) {
+            if let Ok(mut settings) = self.global_settings.write() {
+                *settings = global;
+            }
+            self.refresh_settings().await;
+        }
+
+        let projects = self.known_projects();
+        self.analyze_projects(&projects).await;
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        for folder in &params.event.removed {
+            if let Ok(root) = folder.uri.to_file_path() {
+                self.remove_folder(&root).await;
+            }
+        }
+
+        let added: Vec<PathBuf> = params
+            .event
+            .added
+            .iter()
+            .filter_map(|folder| folder.uri.to_file_path().ok())
+            .collect();
+        if added.is_empty() {
+            return;
+        }
+        for root in &added {
+            self.folders.insert(root.clone(), Settings::default());
+        }
+        self.refresh_settings().await;
+
+        let projects: Vec<PathBuf> = added
+            .iter()
+            .flat_map(|root| workspace::find_projects(root))
+            .collect();
+        self.analyze_projects(&projects).await;
+    }
+
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> Result<DocumentDiagnosticReportResult> {
+        let uri = params.text_document.uri;
+        let projects = uri
+            .to_file_path()
+            .ok()
+            .and_then(|p| p.parent().map(|dir| self.dependent_projects(dir)))
+            .unwrap_or_default();
+
+        // The other files of these projects share the analysis, so report them alongside
+        let mut files: Vec<Url> = Vec::new();
+        for dir in &projects {
+            files.extend(self.compute_diagnostics(dir).into_keys());
+        }
+        files.sort();
+        files.dedup();
+
+        let items = self.merged_diagnostics(&uri);
+        let result_id = self.result_id(&uri);
+
+        let related: HashMap<Url, DocumentDiagnosticReportKind> = files
+            .into_iter()
+            .filter(|file_uri| *file_uri != uri)
+            .map(|file_uri| {
+                let report = FullDocumentDiagnosticReport {
+                    result_id: Some(self.result_id(&file_uri)),
+                    items: self.merged_diagnostics(&file_uri),
+                };
+                (file_uri, DocumentDiagnosticReportKind::Full(report))
+            })
+            .collect();
+        let related_documents = (!related.is_empty()).then_some(related);
+
+        let report = if params.previous_result_id.as_deref() == Some(result_id.as_str()) {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id,
+                },
+            })
+        } else {
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                    result_id: Some(result_id),
+                    items,
+                },
+            })
+        };
+        Ok(DocumentDiagnosticReportResult::Report(report))
+    }
+
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> Result<WorkspaceDiagnosticReportResult> {
+        let previous: HashMap<Url, String> = params
+            .previous_result_ids
+            .into_iter()
+            .map(|p| (p.uri, p.value))
+            .collect();
+
+        let mut files: Vec<Url> = Vec::new();
+        for dir in &self.known_projects() {
+            files.extend(self.compute_diagnostics(dir).into_keys());
+        }
+        files.sort();
+        files.dedup();
+
+        let mut items = Vec::new();
+        for uri in files {
+            let result_id = self.result_id(&uri);
+            let diagnostics = self.merged_diagnostics(&uri);
+            if previous.get(&uri) == Some(&result_id) {
+                items.push(WorkspaceDocumentDiagnosticReport::Unchanged(
+                    WorkspaceUnchangedDocumentDiagnosticReport {
+                        uri,
+                        version: None,
+                        unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                            result_id,
+                        },
+                    },
+                ));
+            } else {
+                items.push(WorkspaceDocumentDiagnosticReport::Full(
+                    WorkspaceFullDocumentDiagnosticReport {
+                        uri,
+                        version: None,
+                        full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                            result_id: Some(result_id),
+                            items: diagnostics,
+                        },
+                    },
+                ));
+            }
+        }
+
+        Ok(WorkspaceDiagnosticReportResult::Report(
+            WorkspaceDiagnosticReport { items },
+        ))
+    }
+
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let uri = &params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
 
         if let Some(word) = self.get_word_at_position(uri, position) {
-            // First check for keyword documentation
-            if let Some(docs) = self.get_keyword_docs(&word) {
+            // First check for built-in documentation; macros are documented as `#[name]`
+            let in_macro = self
+                .documents
+                .get(uri)
+                .and_then(|doc| {
+                    let line = doc.lines().nth(position.line as usize)?;
+                    Some(line.trim_start().starts_with(&format!("#[{}", word)))
+                })
+                .unwrap_or(false);
+            let builtin = if in_macro {
+                format!("#[{}]", word)
+            } else {
+                word.clone()
+            };
+            let math = self
+                .documents
+                .get(uri)
+                .and_then(|doc| syntax::math_function_at(&doc, position))
+                .unwrap_or(false);
+            let doc = if math {
+                docs::lookup_as(&builtin, docs::Category::Math)
+            } else {
+                docs::lookup(&builtin)
+            };
+            if let Some(doc) = doc {
+                let mut value = format!("**{}**\n\n{}", builtin, doc.markdown());
+                let resolved: Vec<String> = [
+                    self.get_endpoint_hover(uri, position, &word),
+                    self.get_path_hover(uri, position, &word),
+                    self.get_return_hover(uri, position, &word),
+                ]
+                .into_iter()
+                .flatten()
+                .collect();
+                if !resolved.is_empty() {
+                    value = format!("{}\n\n---\n\n{}", resolved.join("\n\n"), value);
+                }
                 return Ok(Some(Hover {
                     contents: HoverContents::Markup(MarkupContent {
                         kind: MarkupKind::Markdown,
-                        value: format!("**{}**\n\n{}", word, docs),
+                        value,
+                    }),
+                    range: None,
+                }));
+            }
+
+            // Fields at their use sites, typed by the element they belong to
+            if let Some(field_info) = self.get_field_hover(uri, position) {
+                return Ok(Some(Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: field_info,
+                    }),
+                    range: None,
+                }));
+            }
+
+            // A query's name shows the shape of its response
+            if let Some(shape) = self.get_return_hover(uri, position, &word) {
+                return Ok(Some(Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: shape,
                     }),
                     range: None,
                 }));
@@ -1231,17 +1714,6 @@ impl LanguageServer for Backend {
                     range: None,
                 }));
             }
-
-            // Check if it's a field in an object access context (::{ })
-            if let Some(field_info) = self.get_field_context_hover(uri, position, &word) {
-                return Ok(Some(Hover {
-                    contents: HoverContents::Markup(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: field_info,
-                    }),
-                    range: None,
-                }));
-            }
         }
 
         Ok(None)
@@ -1275,13 +1747,268 @@ impl LanguageServer for Backend {
             Ok(Some(CompletionResponse::Array(items)))
         }
     }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let Some(doc) = self.documents.get(uri) else {
+            return Ok(None);
+        };
+        let Some((name, argument)) = syntax::call_at(&doc, position) else {
+            return Ok(None);
+        };
+        // Only math functions among the names that share one take arguments in
+        // parentheses, so calls the tree cannot place yet are taken as math
+        let entry = if syntax::math_function_at(&doc, position).unwrap_or(true) {
+            docs::lookup_as(&name, docs::Category::Math)
+        } else {
+            docs::lookup(&name)
+        };
+        let Some(signature) = entry.and_then(|entry| entry.signature()) else {
+            return Ok(None);
+        };
+
+        // Variadic calls keep the last parameter active
+        let last = signature
+            .parameters
+            .as_ref()
+            .map_or(0, |params| params.len().saturating_sub(1) as u32);
+        Ok(Some(SignatureHelp {
+            signatures: vec![signature],
+            active_signature: Some(0),
+            active_parameter: Some(argument.min(last)),
+        }))
+    }
+
+    async fn document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let Some(doc) = self.documents.get(uri) else {
+            return Ok(None);
+        };
+
+        let highlights = highlight::document_highlights(&doc, position);
+        Ok((!highlights.is_empty()).then_some(highlights))
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        let uri = &params.text_document.uri;
+        let Some(doc) = self.documents.get(uri) else {
+            return Ok(None);
+        };
+        let width = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| path.parent().map(|dir| self.settings_for(dir).0))
+            .unwrap_or_default()
+            .format_width();
+        Ok(Some(format::edits(&doc, width, &params.options)))
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let Some(doc) = self.documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        Ok(Some(ranges::folding_ranges(&doc)))
+    }
+
+    async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        let Some(doc) = self.documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        Ok(Some(ranges::selection_ranges(&doc, &params.positions)))
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let Some(word) = self.get_word_at_position(uri, position) else {
+            return Ok(None);
+        };
+        let Some(key) = dir_key(uri) else {
+            return Ok(None);
+        };
+        let Some(source) = self.parsed_cache.get(&key) else {
+            return Ok(None);
+        };
+
+        if let Some(query) = source.queries.iter().find(|q| q.name == word) {
+            return Ok(Self::query_hierarchy_item(&key, query).map(|item| vec![item]));
+        }
+
+        let item = deps::schema_items(&source)
+            .into_iter()
+            .find(|item| item.split("::").nth(1) == Some(word.as_str()));
+
+        Ok(item
+            .and_then(|item| self.schema_hierarchy_item(uri, &item))
+            .map(|item| vec![item]))
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        // Only schema items have callers: the queries that touch them
+        let Some(item) = params
+            .item
+            .data
+            .as_ref()
+            .and_then(|d| d.get("item"))
+            .and_then(|v| v.as_str())
+        else {
+            return Ok(None);
+        };
+        let Some(key) = dir_key(&params.item.uri) else {
+            return Ok(None);
+        };
+        let Some(source) = self.parsed_cache.get(&key) else {
+            return Ok(None);
+        };
+
+        let mut calls = Vec::new();
+        for query in &source.queries {
+            let usages: Vec<_> = deps::query_usages(&source, query)
+                .into_iter()
+                .filter(|u| u.item == item)
+                .collect();
+            if usages.is_empty() {
+                continue;
+            }
+            if let Some(mut from) = Self::query_hierarchy_item(&key, query) {
+                let mut accesses: Vec<_> = usages.iter().map(|u| u.access).collect();
+                accesses.sort();
+                accesses.dedup();
+                from.detail = Some(
+                    accesses
+                        .iter()
+                        .map(|a| format!("{:?}", a).to_lowercase())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                );
+                calls.push(CallHierarchyIncomingCall {
+                    from,
+                    from_ranges: usages.iter().map(|u| loc_to_range(&u.loc)).collect(),
+                });
+            }
+        }
+
+        Ok(Some(calls))
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        // Only queries have callees: the schema items they touch
+        let Some(name) = params
+            .item
+            .data
+            .as_ref()
+            .and_then(|d| d.get("query"))
+            .and_then(|v| v.as_str())
+        else {
+            return Ok(None);
+        };
+        let Some(key) = dir_key(&params.item.uri) else {
+            return Ok(None);
+        };
+
+        let usages = {
+            let Some(source) = self.parsed_cache.get(&key) else {
+                return Ok(None);
+            };
+            let Some(query) = source.queries.iter().find(|q| q.name == name) else {
+                return Ok(None);
+            };
+            deps::query_usages(&source, query)
+        };
+
+        let mut by_item: HashMap<String, Vec<Range>> = HashMap::new();
+        for usage in &usages {
+            by_item
+                .entry(usage.item.clone())
+                .or_default()
+                .push(loc_to_range(&usage.loc));
+        }
+
+        let mut calls = Vec::new();
+        for (item, from_ranges) in by_item {
+            if let Some(to) = self.schema_hierarchy_item(&params.item.uri, &item) {
+                calls.push(CallHierarchyOutgoingCall { to, from_ranges });
+            }
+        }
+
+        Ok(Some(calls))
+    }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        if params.command != codegen::COMMAND {
+            return Err(tower_lsp::jsonrpc::Error::method_not_found());
+        }
+        // One argument: `{ textDocument, lang }`
+        let args: codegen::CodegenArgs = params
+            .arguments
+            .into_iter()
+            .next()
+            .and_then(|arg| serde_json::from_value(arg).ok())
+            .ok_or_else(|| {
+                tower_lsp::jsonrpc::Error::invalid_params("expected { textDocument, lang }")
+            })?;
+        let Some(source) =
+            dir_key(&args.text_document.uri).and_then(|key| self.parsed_cache.get(&key))
+        else {
+            return Ok(None);
+        };
+
+        let result = codegen::CodegenResult {
+            lang: args.lang,
+            content: codegen::generate(&source, args.lang),
+        };
+        Ok(serde_json::to_value(result).ok())
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+        let mut actions = Self::get_fix_actions(uri, &params.context.diagnostics);
+        actions.extend(self.get_migration_actions(uri));
+
+        if actions.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(actions))
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(Backend::new);
+    let (service, socket) = LspService::build(Backend::new)
+        .custom_method("helixql/dependencyGraph", Backend::dependency_graph)
+        .custom_method("helixql/schemaGraph", Backend::schema_graph)
+        .custom_method("helixql/lintRules", Backend::lint_rules)
+        .custom_method("helixql/returnShape", Backend::return_shape)
+        .finish();
     Server::new(stdin, stdout, socket).serve(service).await;
 }
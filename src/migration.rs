@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use helix_db::helixc::parser::types::{DefaultValue, Field, FieldType, Schema, Source};
+
+use crate::Backend;
+
+/// A node, edge or vector definition flattened to the parts a migration cares about
+struct Item<'a> {
+    prefix: &'static str,
+    name: &'a str,
+    fields: &'a [Field],
+}
+
+fn items(schema: &Schema) -> Vec<Item<'_>> {
+    let mut items = Vec::new();
+    for node in &schema.node_schemas {
+        items.push(Item {
+            prefix: "N",
+            name: &node.name.1,
+            fields: &node.fields,
+        });
+    }
+    for edge in &schema.edge_schemas {
+        items.push(Item {
+            prefix: "E",
+            name: &edge.name.1,
+            fields: edge.properties.as_deref().unwrap_or(&[]),
+        });
+    }
+    for vector in &schema.vector_schemas {
+        items.push(Item {
+            prefix: "V",
+            name: &vector.name,
+            fields: &vector.fields,
+        });
+    }
+    items
+}
+
+/// Schema version pairs (in ascending order) that have no MIGRATION block yet
+pub fn missing_migrations(source: &Source) -> Vec<(usize, usize)> {
+    let mut versions: Vec<usize> = source.schema.keys().copied().collect();
+    versions.sort_unstable();
+
+    versions
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .filter(|(from, to)| {
+            !source
+                .migrations
+                .iter()
+                .any(|m| m.from_version.1 == *from && m.to_version.1 == *to)
+        })
+        .collect()
+}
+
+/// Render a literal for a schema DEFAULT value as it is written in a field migration
+pub fn default_literal(default: &DefaultValue) -> String {
+    match default {
+        DefaultValue::Now => "NOW".to_string(),
+        DefaultValue::String(s) => format!("\"{}\"", s),
+        DefaultValue::F32(v) => format!("{:?}", v),
+        DefaultValue::F64(v) => format!("{:?}", v),
+        DefaultValue::I8(v) => v.to_string(),
+        DefaultValue::I16(v) => v.to_string(),
+        DefaultValue::I32(v) => v.to_string(),
+        DefaultValue::I64(v) => v.to_string(),
+        DefaultValue::U8(v) => v.to_string(),
+        DefaultValue::U16(v) => v.to_string(),
+        DefaultValue::U32(v) => v.to_string(),
+        DefaultValue::U64(v) => v.to_string(),
+        DefaultValue::U128(v) => v.to_string(),
+        DefaultValue::Boolean(v) => v.to_string(),
+        DefaultValue::Empty => "NONE".to_string(),
+    }
+}
+
+/// The migration grammar only allows casting to named scalar types, `ID` and `Date`
+fn cast_target(ft: &FieldType) -> Option<String> {
+    match ft {
+        FieldType::Array(_) | FieldType::Object(_) | FieldType::Identifier(_) => None,
+        FieldType::Uuid => Some("ID".to_string()),
+        other => Some(Backend::field_type_to_string(other)),
+    }
+}
+
+/// Build the field mappings for one item that exists in both versions.
+/// Returns `None` when the item is unchanged and needs no mapping.
+fn field_mappings(old: &[Field], new: &[Field]) -> Option<Vec<String>> {
+    let old_by_name: HashMap<&str, &Field> = old.iter().map(|f| (f.name.as_str(), f)).collect();
+    let new_names: Vec<&str> = new.iter().map(|f| f.name.as_str()).collect();
+
+    // Fields that disappeared are candidates for renames of fields that appeared
+    let mut removed: Vec<&Field> = old
+        .iter()
+        .filter(|f| !new_names.contains(&f.name.as_str()))
+        .collect();
+    let mut changed = !removed.is_empty();
+
+    let mut lines = Vec::new();
+    for field in new {
+        if let Some(previous) = old_by_name.get(field.name.as_str()) {
+            if previous.field_type == field.field_type {
+                lines.push(format!("{}: {}", field.name, field.name));
+            } else {
+                changed = true;
+                match cast_target(&field.field_type) {
+                    Some(target) => {
+                        lines.push(format!("{}: {} AS {}", field.name, field.name, target))
+                    }
+                    None => lines.push(format!(
+                        "{}: {}, // TODO: cannot cast {} to {}",
+                        field.name,
+                        field.name,
+                        Backend::field_type_to_string(&previous.field_type),
+                        Backend::field_type_to_string(&field.field_type)
+                    )),
+                }
+            }
+            continue;
+        }
+
+        changed = true;
+
+        // A new field with exactly one removed field of the same type is treated as a rename
+        let candidates: Vec<usize> = removed
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.field_type == field.field_type)
+            .map(|(i, _)| i)
+            .collect();
+        if candidates.len() == 1 {
+            let renamed_from = removed.remove(candidates[0]);
+            lines.push(format!("{}: {}", field.name, renamed_from.name));
+            continue;
+        }
+
+        let value = field
+            .defaults
+            .as_ref()
+            .map(default_literal)
+            .unwrap_or_else(|| "NONE".to_string());
+        lines.push(format!("{}: {}", field.name, value));
+    }
+
+    if changed {
+        Some(lines)
+    } else {
+        None
+    }
+}
+
+/// Generate a `MIGRATION schema::from => schema::to { ... }` stub from the differences
+/// between two parsed schema versions
+pub fn scaffold(source: &Source, from: usize, to: usize) -> Option<String> {
+    let old_schema = source.schema.get(&from)?;
+    let new_schema = source.schema.get(&to)?;
+
+    let old_items = items(old_schema);
+    let new_items = items(new_schema);
+
+    let mut body = String::new();
+    for new_item in &new_items {
+        let Some(old_item) = old_items
+            .iter()
+            .find(|i| i.prefix == new_item.prefix && i.name == new_item.name)
+        else {
+            continue;
+        };
+
+        let Some(lines) = field_mappings(old_item.fields, new_item.fields) else {
+            continue;
+        };
+
+        body.push_str(&format!(
+            "    {}::{} => _::{{\n",
+            new_item.prefix, new_item.name
+        ));
+        let indent = if new_item.prefix == "E" {
+            body.push_str("        Properties: {\n");
+            "            "
+        } else {
+            "        "
+        };
+        for line in &lines {
+            // Lines carrying a trailing comment already include their comma
+            let comma = if line.contains("//") { "" } else { "," };
+            body.push_str(&format!("{}{}{}\n", indent, line, comma));
+        }
+        if new_item.prefix == "E" {
+            body.push_str("        }\n");
+        }
+        body.push_str("    }\n");
+    }
+
+    for old_item in &old_items {
+        if !new_items
+            .iter()
+            .any(|i| i.prefix == old_item.prefix && i.name == old_item.name)
+        {
+            body.push_str(&format!(
+                "    // {}::{} was removed in schema::{}\n",
+                old_item.prefix, old_item.name, to
+            ));
+        }
+    }
+
+    if body.is_empty() {
+        body.push_str("    // No changes to existing items\n");
+    }
+
+    Some(format!(
+        "MIGRATION schema::{} => schema::{} {{\n{}}}\n",
+        from, to, body
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_text;
+
+    fn scaffold_text(schema: &str) -> String {
+        let source = parse_text(schema);
+        scaffold(&source, 1, 2).unwrap()
+    }
+
+    #[test]
+    fn renames_the_only_removed_field_of_the_same_type() {
+        let schema = "schema::1 {
+    N::User { name: String, age: U32 }
+    N::Post { title: String }
+}
+schema::2 {
+    N::User { full_name: String, age: U32 }
+}
+";
+        assert_eq!(
+            scaffold_text(schema),
+            "MIGRATION schema::1 => schema::2 {
+    N::User => _::{
+        full_name: name,
+        age: age,
+    }
+    // N::Post was removed in schema::2
+}
+"
+        );
+    }
+
+    #[test]
+    fn ambiguous_renames_fall_back_to_none() {
+        let schema = "schema::1 {
+    N::User { first: String, last: String }
+}
+schema::2 {
+    N::User { name: String }
+}
+";
+        assert_eq!(
+            scaffold_text(schema),
+            "MIGRATION schema::1 => schema::2 {
+    N::User => _::{
+        name: NONE,
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn changed_types_are_cast_where_the_grammar_allows() {
+        let schema = "schema::1 {
+    N::User { age: U32, key: String, tags: String }
+}
+schema::2 {
+    N::User { age: I64, key: ID, tags: [String] }
+}
+";
+        assert_eq!(
+            scaffold_text(schema),
+            "MIGRATION schema::1 => schema::2 {
+    N::User => _::{
+        age: age AS I64,
+        key: key AS ID,
+        tags: tags, // TODO: cannot cast String to [String]
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn new_fields_take_their_default_or_none() {
+        let schema = "schema::1 {
+    N::User { name: String }
+    E::Follows { From: User, To: User, Properties: { since: Date } }
+}
+schema::2 {
+    N::User {
+        name: String,
+        role: String DEFAULT \"member\",
+        joined: Date DEFAULT NOW,
+        score: F64 DEFAULT 0.5,
+        bio: String,
+    }
+    E::Follows { From: User, To: User, Properties: { since: Date, muted: Boolean DEFAULT false } }
+}
+";
+        assert_eq!(
+            scaffold_text(schema),
+            "MIGRATION schema::1 => schema::2 {
+    N::User => _::{
+        name: name,
+        role: \"member\",
+        joined: NOW,
+        score: 0.5,
+        bio: NONE,
+    }
+    E::Follows => _::{
+        Properties: {
+            since: since,
+            muted: false,
+        }
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn unchanged_schemas_need_no_mappings() {
+        let schema = "schema::1 {
+    N::User { name: String }
+}
+schema::2 {
+    N::User { name: String }
+}
+";
+        assert_eq!(
+            scaffold_text(schema),
+            "MIGRATION schema::1 => schema::2 {
+    // No changes to existing items
+}
+"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use pest::iterators::{Pair, Pairs};
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind, Position, Range, SelectionRange};
+
+use crate::syntax::{self, LineIndex, Rule};
+
+/// Whether `pair` is a block worth folding: schema version blocks, schema item
+/// bodies, edge properties, queries, FOR loops, migrations and their item mappings
+fn folds(pair: &Pair<Rule>) -> bool {
+    match pair.as_rule() {
+        Rule::schema_def => pair
+            .clone()
+            .into_inner()
+            .any(|child| child.as_rule() == Rule::schema_version),
+        Rule::node_body
+        | Rule::edge_body
+        | Rule::properties
+        | Rule::query_def
+        | Rule::for_loop
+        | Rule::migration_def
+        | Rule::migration_item_mapping => true,
+        _ => false,
+    }
+}
+
+fn collect_folds(pair: Pair<Rule>, base: usize, index: &LineIndex, ranges: &mut Vec<FoldingRange>) {
+    if folds(&pair) {
+        let (start, end) = syntax::span(&pair, base);
+        let (start, end) = (index.position(start), index.position(end));
+        if end.line > start.line {
+            ranges.push(FoldingRange {
+                start_line: start.line,
+                start_character: Some(start.character),
+                end_line: end.line,
+                end_character: Some(end.character),
+                kind: None,
+                collapsed_text: None,
+            });
+        }
+    }
+    for child in pair.into_inner() {
+        collect_folds(child, base, index, ranges);
+    }
+}
+
+/// Runs of two or more `//` comment lines
+fn comment_folds(text: &str) -> Vec<FoldingRange> {
+    let mut ranges = Vec::new();
+    let mut run: Option<(u32, u32)> = None;
+    // A trailing empty line closes a run at the end of the file
+    for (line, content) in text.lines().chain([""]).enumerate() {
+        let line = line as u32;
+        if content.trim_start().starts_with("//") {
+            run = Some(run.map_or((line, line), |(start, _)| (start, line)));
+        } else if let Some((start, end)) = run.take().filter(|(start, end)| end > start) {
+            ranges.push(FoldingRange {
+                start_line: start,
+                end_line: end,
+                kind: Some(FoldingRangeKind::Comment),
+                ..Default::default()
+            });
+        }
+    }
+    ranges
+}
+
+/// Folding ranges for the structure of a file and its comment runs
+pub fn folding_ranges(text: &str) -> Vec<FoldingRange> {
+    let index = LineIndex::new(text);
+    let mut ranges = Vec::new();
+    for (base, pairs) in syntax::parse_items(text) {
+        for pair in pairs {
+            collect_folds(pair, base, &index, &mut ranges);
+        }
+    }
+    ranges.extend(comment_folds(text));
+    ranges.sort_by_key(|range| (range.start_line, std::cmp::Reverse(range.end_line)));
+    ranges
+}
+
+/// Spans of the nodes containing `offset`, from the outermost to the innermost
+fn enclosing_spans(items: &[(usize, Pairs<Rule>)], offset: usize) -> Vec<(usize, usize)> {
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    for (base, pairs) in items {
+        let base = *base;
+        let mut level: Vec<Pair<Rule>> = pairs.clone().collect();
+        while let Some(pair) = level.into_iter().find(|pair| {
+            let (start, end) = syntax::span(pair, base);
+            start <= offset && offset <= end
+        }) {
+            let range = syntax::span(&pair, base);
+            if pair.as_rule() != Rule::source && spans.last() != Some(&range) {
+                spans.push(range);
+            }
+            level = pair.into_inner().collect();
+        }
+        if !spans.is_empty() {
+            break;
+        }
+    }
+    spans
+}
+
+/// Selection ranges that grow from the token at each position through its step,
+/// the traversal chain and the statement up to the whole item
+pub fn selection_ranges(text: &str, positions: &[Position]) -> Vec<SelectionRange> {
+    let index = LineIndex::new(text);
+    let items = syntax::parse_items(text);
+    positions
+        .iter()
+        .map(|&position| {
+            let spans = enclosing_spans(&items, index.offset(position));
+            let mut selection: Option<SelectionRange> = None;
+            for (start, end) in spans {
+                selection = Some(SelectionRange {
+                    range: index.range(start, end),
+                    parent: selection.map(Box::new),
+                });
+            }
+            selection.unwrap_or(SelectionRange {
+                range: Range::new(position, position),
+                parent: None,
+            })
+        })
+        .collect()
+}
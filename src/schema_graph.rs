@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+use helix_db::helixc::parser::types::{Field, FieldPrefix, Source};
+
+use crate::Backend;
+
+/// Output formats for the schema diagram
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    #[default]
+    Mermaid,
+    Dot,
+    Json,
+}
+
+impl GraphFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "mermaid" => Some(GraphFormat::Mermaid),
+            "dot" | "graphviz" => Some(GraphFormat::Dot),
+            "json" => Some(GraphFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaGraphParams {
+    pub text_document: TextDocumentIdentifier,
+    #[serde(default)]
+    pub format: GraphFormat,
+    /// Schema version to render; defaults to the latest
+    pub version: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+    /// `"index"` or `"unique"` when the field is indexed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub name: String,
+    /// `"node"` or `"vector"`
+    pub kind: String,
+    pub fields: Vec<GraphField>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+    pub unique: bool,
+    pub properties: Vec<GraphField>,
+}
+
+/// The property graph declared by one schema version
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchemaGraph {
+    pub version: usize,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Response of `helixql/schemaGraph`: the rendered diagram plus the structured graph
+#[derive(Debug, Serialize)]
+pub struct SchemaGraphResult {
+    pub format: GraphFormat,
+    pub content: String,
+    pub graph: SchemaGraph,
+}
+
+fn graph_fields(fields: &[Field]) -> Vec<GraphField> {
+    fields
+        .iter()
+        .map(|f| GraphField {
+            name: f.name.clone(),
+            field_type: Backend::field_type_to_string(&f.field_type),
+            index: match f.prefix {
+                FieldPrefix::Index => Some("index".to_string()),
+                FieldPrefix::UniqueIndex => Some("unique".to_string()),
+                _ => None,
+            },
+        })
+        .collect()
+}
+
+/// Collect the graph for `version`, or the latest schema version when none is given
+pub fn build(source: &Source, version: Option<usize>) -> Option<SchemaGraph> {
+    let version = version.or_else(|| source.schema.keys().max().copied())?;
+    let schema = source.schema.get(&version)?;
+
+    let mut nodes = Vec::new();
+    for node in &schema.node_schemas {
+        nodes.push(GraphNode {
+            name: node.name.1.clone(),
+            kind: "node".to_string(),
+            fields: graph_fields(&node.fields),
+        });
+    }
+    for vector in &schema.vector_schemas {
+        nodes.push(GraphNode {
+            name: vector.name.clone(),
+            kind: "vector".to_string(),
+            fields: graph_fields(&vector.fields),
+        });
+    }
+
+    let edges = schema
+        .edge_schemas
+        .iter()
+        .map(|edge| GraphEdge {
+            name: edge.name.1.clone(),
+            from: edge.from.1.clone(),
+            to: edge.to.1.clone(),
+            unique: edge.unique,
+            properties: graph_fields(edge.properties.as_deref().unwrap_or(&[])),
+        })
+        .collect();
+
+    Some(SchemaGraph {
+        version,
+        nodes,
+        edges,
+    })
+}
+
+fn index_suffix(field: &GraphField) -> &'static str {
+    match field.index.as_deref() {
+        Some("unique") => " UNIQUE INDEX",
+        Some(_) => " INDEX",
+        None => "",
+    }
+}
+
+fn edge_label(edge: &GraphEdge) -> String {
+    let mut label = edge.name.clone();
+    if edge.unique {
+        label.push_str(" (UNIQUE)");
+    }
+    if !edge.properties.is_empty() {
+        let props: Vec<String> = edge
+            .properties
+            .iter()
+            .map(|p| format!("{}: {}", p.name, p.field_type))
+            .collect();
+        label.push_str(&format!(" {}", props.join(", ")));
+    }
+    label
+}
+
+pub fn to_mermaid(graph: &SchemaGraph) -> String {
+    let mut out = String::from("classDiagram\n");
+    for node in &graph.nodes {
+        out.push_str(&format!("    class {} {{\n", node.name));
+        if node.kind == "vector" {
+            out.push_str("        <<vector>>\n");
+        }
+        for field in &node.fields {
+            out.push_str(&format!(
+                "        {} {}{}\n",
+                field.field_type,
+                field.name,
+                index_suffix(field)
+            ));
+        }
+        out.push_str("    }\n");
+    }
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "    {} --> {} : {}\n",
+            edge.from,
+            edge.to,
+            edge_label(edge)
+        ));
+    }
+    out
+}
+
+/// Escape characters that have meaning inside Graphviz record labels
+fn dot_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+pub fn to_dot(graph: &SchemaGraph) -> String {
+    let mut out = String::from("digraph schema {\n    rankdir=LR;\n    node [shape=record];\n");
+    for node in &graph.nodes {
+        let prefix = if node.kind == "vector" { "V" } else { "N" };
+        let fields: String = node
+            .fields
+            .iter()
+            .map(|f| {
+                dot_escape(&format!("{}: {}{}", f.name, f.field_type, index_suffix(f))) + "\\l"
+            })
+            .collect();
+        out.push_str(&format!(
+            "    \"{}\" [label=\"{{{}::{}|{}}}\"];\n",
+            node.name, prefix, node.name, fields
+        ));
+    }
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+            edge.from,
+            edge.to,
+            edge_label(edge).replace('"', "\\\"")
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Render the graph in the requested format
+pub fn render(graph: &SchemaGraph, format: GraphFormat) -> String {
+    match format {
+        GraphFormat::Mermaid => to_mermaid(graph),
+        GraphFormat::Dot => to_dot(graph),
+        GraphFormat::Json => serde_json::to_string_pretty(graph).unwrap_or_default(),
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::{Component, Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::format;
+use crate::lint::LintConfig;
+
+/// Configuration section requested through `workspace/configuration`
+pub const SECTION: &str = "helixql";
+
+/// Which diagnostic passes run for a project
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Features {
+    /// Run the helix-db analyzer on top of parsing
+    pub analyzer: bool,
+    /// Run the server's lint rules
+    pub lints: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            analyzer: true,
+            lints: true,
+        }
+    }
+}
+
+/// Editor settings for one workspace folder:
+///
+/// ```json
+/// {
+///   "lints": { "unused-type": "off" },
+///   "searchKThreshold": 200,
+///   "docsBaseUrl": "https://docs.example.com/helixql",
+///   "includeDirs": ["../shared"],
+///   "formatWidth": 100,
+///   "features": { "analyzer": true, "lints": true }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    #[serde(flatten)]
+    pub lint: LintConfig,
+    /// Directories whose .hx files are analyzed together with every project in the
+    /// folder; relative paths are resolved against the folder root
+    pub include_dirs: Vec<PathBuf>,
+    /// Line width the formatter breaks long traversals at
+    pub format_width: Option<usize>,
+    pub features: Features,
+}
+
+impl Settings {
+    /// Parse a settings object, accepting both `{ "helixql": {...} }` and the bare section
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let section = value.get(SECTION).unwrap_or(value);
+        if section.is_null() {
+            return None;
+        }
+        serde_json::from_value(section.clone()).ok()
+    }
+
+    /// Lint config for a project: editor settings, overridden by the project's `helixql.json`
+    pub fn lint_config(&self, dir: &Path) -> LintConfig {
+        let project = LintConfig::load(dir);
+        let mut config = self.lint.clone();
+        config.lints.extend(project.lints);
+        if project.search_k_threshold.is_some() {
+            config.search_k_threshold = project.search_k_threshold;
+        }
+        if project.docs_base_url.is_some() {
+            config.docs_base_url = project.docs_base_url;
+        }
+        config
+    }
+
+    pub fn format_width(&self) -> usize {
+        self.format_width.unwrap_or(format::DEFAULT_WIDTH)
+    }
+
+    /// Include directories as absolute paths, with `.` and `..` resolved so they
+    /// compare equal to the project directories of document URIs
+    pub fn include_dirs(&self, base: &Path) -> Vec<PathBuf> {
+        self.include_dirs
+            .iter()
+            .map(|dir| normalize(&base.join(dir)))
+            .collect()
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
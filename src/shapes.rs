@@ -0,0 +1,569 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as Json};
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+use helix_db::helixc::parser::types::{
+    Expression, ExpressionType, FieldAddition, FieldType, FieldValueType, GraphStepType, Query,
+    ReturnType, Source, StartNode, StatementType, StepType, Traversal,
+};
+use helix_db::protocol::value::Value;
+
+use crate::infer::{self, ElementType, Vars};
+
+/// Shape of a value in a query response
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Scalar(FieldType),
+    /// Keys in the order the query produces them
+    Object(Vec<(String, Shape)>),
+    Array(Box<Shape>),
+    /// An `[a, b]` array of differently shaped values
+    Tuple(Vec<Shape>),
+    Unknown,
+}
+
+impl Shape {
+    fn array(item: Shape) -> Shape {
+        Shape::Array(Box::new(item))
+    }
+
+    /// Render as a TypeScript type, indenting nested lines by `depth` levels
+    pub fn typescript(&self, depth: usize) -> String {
+        self.typescript_in(depth, false)
+    }
+
+    /// Render as a TypeScript type of a generated client, which names scalars by the
+    /// aliases its prelude declares
+    pub fn typescript_with_aliases(&self, depth: usize) -> String {
+        self.typescript_in(depth, true)
+    }
+
+    fn typescript_in(&self, depth: usize, aliases: bool) -> String {
+        match self {
+            Shape::Scalar(ty) => typescript_scalar(ty, aliases).to_string(),
+            Shape::Object(fields) if fields.is_empty() => "{}".to_string(),
+            Shape::Object(fields) => {
+                let indent = "  ".repeat(depth + 1);
+                let lines: Vec<String> = fields
+                    .iter()
+                    .map(|(key, shape)| {
+                        format!(
+                            "{}{}: {};",
+                            indent,
+                            typescript_key(key),
+                            shape.typescript_in(depth + 1, aliases)
+                        )
+                    })
+                    .collect();
+                format!("{{\n{}\n{}}}", lines.join("\n"), "  ".repeat(depth))
+            }
+            Shape::Array(item) => format!("{}[]", item.typescript_in(depth, aliases)),
+            Shape::Tuple(items) => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|item| item.typescript_in(depth, aliases))
+                    .collect();
+                format!("[{}]", items.join(", "))
+            }
+            Shape::Unknown => "unknown".to_string(),
+        }
+    }
+
+    /// Render as a JSON Schema (draft 2020-12)
+    pub fn json_schema(&self) -> Json {
+        match self {
+            Shape::Scalar(ty) => json_schema_scalar(ty),
+            Shape::Object(fields) => {
+                let properties: serde_json::Map<String, Json> = fields
+                    .iter()
+                    .map(|(key, shape)| (key.clone(), shape.json_schema()))
+                    .collect();
+                let required: Vec<&str> = fields.iter().map(|(key, _)| key.as_str()).collect();
+                json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false,
+                })
+            }
+            Shape::Array(item) => json!({ "type": "array", "items": item.json_schema() }),
+            Shape::Tuple(items) => {
+                let items: Vec<Json> = items.iter().map(Shape::json_schema).collect();
+                json!({ "type": "array", "prefixItems": items, "items": false })
+            }
+            Shape::Unknown => json!({}),
+        }
+    }
+}
+
+/// An object key as written in a TypeScript type, quoted unless it is an identifier
+pub fn typescript_key(key: &str) -> String {
+    let identifier = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        key.to_string()
+    } else {
+        serde_json::to_string(key).unwrap_or_else(|_| format!("\"{}\"", key))
+    }
+}
+
+/// TypeScript type of a scalar field. Integers of 64 bits or more can exceed
+/// `Number.MAX_SAFE_INTEGER`, so they may arrive as `bigint`. With `aliases`, the
+/// names declared by generated clients are used instead of the types they stand for.
+fn typescript_scalar(ty: &FieldType, aliases: bool) -> &'static str {
+    match (ty, aliases) {
+        (FieldType::Uuid, true) => "Uuid",
+        (FieldType::Date, true) => "DateTime",
+        (FieldType::I64 | FieldType::U64, true) => "Int64",
+        (FieldType::U128, true) => "U128",
+        (FieldType::String | FieldType::Uuid | FieldType::Date, _) => "string",
+        (FieldType::Boolean, _) => "boolean",
+        (FieldType::I64 | FieldType::U64 | FieldType::U128, _) => "number | bigint",
+        (ty, _) if infer::is_numeric(ty) => "number",
+        _ => "unknown",
+    }
+}
+
+fn json_schema_scalar(ty: &FieldType) -> Json {
+    match ty {
+        FieldType::String => json!({ "type": "string" }),
+        FieldType::Uuid => json!({ "type": "string", "format": "uuid" }),
+        FieldType::Date => json!({ "type": "string", "format": "date-time" }),
+        FieldType::Boolean => json!({ "type": "boolean" }),
+        FieldType::F32 | FieldType::F64 => json!({ "type": "number" }),
+        ty if infer::is_numeric(ty) => json!({ "type": "integer" }),
+        _ => json!({}),
+    }
+}
+
+/// Insert `key`, replacing an earlier value under the same key in place
+fn set(fields: &mut Vec<(String, Shape)>, key: &str, shape: Shape) {
+    match fields.iter_mut().find(|(k, _)| k == key) {
+        Some(field) => field.1 = shape,
+        None => fields.push((key.to_string(), shape)),
+    }
+}
+
+/// Properties every element of a kind has, in the order responses list them
+fn builtin_properties(owner: &ElementType) -> &'static [&'static str] {
+    match owner {
+        ElementType::Node(_) => &["id", "label"],
+        ElementType::Edge(_) => &["id", "label", "from_node", "to_node"],
+        ElementType::Vector(_) => &["id", "label", "data", "score"],
+        _ => &[],
+    }
+}
+
+/// A node, edge or vector as returned whole: its implicit properties, then its fields
+pub fn element_shape(source: &Source, owner: &ElementType) -> Shape {
+    element_shape_in(source, owner, &mut Vec::new())
+}
+
+/// Shape of a value of `ty`; type names resolve to the element they name
+pub fn field_shape(source: &Source, ty: &FieldType) -> Shape {
+    field_shape_in(source, ty, &mut Vec::new())
+}
+
+/// `element_shape`, where `expanding` holds the types whose fields are being
+/// shaped further up. A type that refers back to one of them, directly or
+/// through other types, is left `Unknown` there rather than expanded forever.
+fn element_shape_in(source: &Source, owner: &ElementType, expanding: &mut Vec<String>) -> Shape {
+    let (name, declared) = match owner {
+        ElementType::Node(name) => (name, infer::node_schema(source, name).map(|n| &n.fields)),
+        ElementType::Edge(name) => (
+            name,
+            infer::edge_schema(source, name).and_then(|e| e.properties.as_ref()),
+        ),
+        ElementType::Vector(name) => (name, infer::vector_schema(source, name).map(|v| &v.fields)),
+        ElementType::Scalar(ty) => return field_shape_in(source, ty, expanding),
+        ElementType::Unknown => return Shape::Unknown,
+    };
+    let Some(declared) = declared else {
+        return Shape::Unknown;
+    };
+    if expanding.contains(name) {
+        return Shape::Unknown;
+    }
+    expanding.push(name.clone());
+    let mut fields = Vec::new();
+    for name in builtin_properties(owner) {
+        if let Some(ty) = infer::builtin_field_type(owner, name) {
+            set(&mut fields, name, Shape::Scalar(ty));
+        }
+    }
+    for field in declared {
+        set(
+            &mut fields,
+            &field.name,
+            field_shape_in(source, &field.field_type, expanding),
+        );
+    }
+    expanding.pop();
+    Shape::Object(fields)
+}
+
+fn field_shape_in(source: &Source, ty: &FieldType, expanding: &mut Vec<String>) -> Shape {
+    match ty {
+        FieldType::Array(inner) => Shape::array(field_shape_in(source, inner, expanding)),
+        FieldType::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            Shape::Object(
+                keys.into_iter()
+                    .map(|key| (key.clone(), field_shape_in(source, &map[key], expanding)))
+                    .collect(),
+            )
+        }
+        FieldType::Identifier(name) => {
+            let owner = if infer::node_schema(source, name).is_some() {
+                ElementType::Node(name.clone())
+            } else if infer::edge_schema(source, name).is_some() {
+                ElementType::Edge(name.clone())
+            } else if infer::vector_schema(source, name).is_some() {
+                ElementType::Vector(name.clone())
+            } else {
+                return Shape::Unknown;
+            };
+            element_shape_in(source, &owner, expanding)
+        }
+        ty => Shape::Scalar(ty.clone()),
+    }
+}
+
+fn literal_shape(value: &Value) -> Shape {
+    let ty = match value {
+        Value::String(_) => FieldType::String,
+        Value::F32(_) => FieldType::F32,
+        Value::F64(_) => FieldType::F64,
+        Value::I8(_) => FieldType::I8,
+        Value::I16(_) => FieldType::I16,
+        Value::I32(_) => FieldType::I32,
+        Value::I64(_) => FieldType::I64,
+        Value::U8(_) => FieldType::U8,
+        Value::U16(_) => FieldType::U16,
+        Value::U32(_) => FieldType::U32,
+        Value::U64(_) => FieldType::U64,
+        Value::U128(_) => FieldType::U128,
+        Value::Boolean(_) => FieldType::Boolean,
+        Value::Array(items) => {
+            return Shape::array(items.first().map(literal_shape).unwrap_or(Shape::Unknown))
+        }
+        _ => return Shape::Unknown,
+    };
+    Shape::Scalar(ty)
+}
+
+/// Follows a query's statements, keeping the shape of every variable alongside
+/// its element type
+struct Shapes<'a> {
+    source: &'a Source,
+    vars: Vars,
+    values: HashMap<String, Shape>,
+}
+
+impl Shapes<'_> {
+    /// Shape of a variable; parameters are shaped by their declared type
+    fn variable(&self, name: &str) -> Shape {
+        match self.values.get(name) {
+            Some(shape) => shape.clone(),
+            None => match self.vars.get(name) {
+                Some(ty) => element_shape(self.source, ty),
+                None => Shape::Unknown,
+            },
+        }
+    }
+
+    /// Shape of `expr`, where `_` stands for one element of type `anonymous`
+    fn expression(&self, expr: &Expression, anonymous: &ElementType) -> Shape {
+        match &expr.expr {
+            ExpressionType::Traversal(traversal) => self.traversal(traversal, anonymous),
+            ExpressionType::Identifier(name) => self.variable(name),
+            ExpressionType::SearchVector(_) | ExpressionType::BM25Search(_) => {
+                let ty = infer::expression_type(self.source, expr, &self.vars);
+                Shape::array(element_shape(self.source, &ty))
+            }
+            ExpressionType::ArrayLiteral(items) => {
+                let items: Vec<Shape> = items
+                    .iter()
+                    .map(|item| self.expression(item, anonymous))
+                    .collect();
+                match items.first() {
+                    Some(first) if items.iter().all(|item| item == first) => {
+                        Shape::array(first.clone())
+                    }
+                    Some(_) => Shape::Tuple(items),
+                    None => Shape::array(Shape::Unknown),
+                }
+            }
+            _ => element_shape(
+                self.source,
+                &infer::expression_type(self.source, expr, &self.vars),
+            ),
+        }
+    }
+
+    fn traversal(&self, traversal: &Traversal, anonymous: &ElementType) -> Shape {
+        let types = infer::traversal_types(self.source, traversal, &self.vars, anonymous);
+
+        // Whether the traversal yields a list, and the shape of each item
+        let (mut many, mut item) = match &traversal.start {
+            StartNode::Node { ids, .. }
+            | StartNode::Edge { ids, .. }
+            | StartNode::Vector { ids, .. } => (
+                ids.as_ref().is_none_or(|ids| ids.len() != 1),
+                element_shape(self.source, &types[0]),
+            ),
+            StartNode::SearchVector(_) => (true, element_shape(self.source, &types[0])),
+            StartNode::Identifier(name) => match self.variable(name) {
+                Shape::Array(item) => (true, *item),
+                shape => (false, shape),
+            },
+            StartNode::Anonymous => (false, element_shape(self.source, anonymous)),
+        };
+
+        for (i, step) in traversal.steps.iter().enumerate() {
+            let (before, after) = (&types[i], &types[i + 1]);
+            match &step.step {
+                StepType::First => many = false,
+                StepType::Count => {
+                    many = false;
+                    item = Shape::Scalar(FieldType::U64);
+                }
+                StepType::BooleanOperation(_) => item = Shape::Scalar(FieldType::Boolean),
+                StepType::Object(object) => {
+                    item = self.object(&item, before, &object.fields, object.should_spread, None)
+                }
+                StepType::Closure(closure) => {
+                    let object = &closure.object;
+                    item = self.object(
+                        &item,
+                        before,
+                        &object.fields,
+                        object.should_spread,
+                        Some(&closure.identifier),
+                    )
+                }
+                StepType::Exclude(exclude) => {
+                    if let Shape::Object(fields) = &mut item {
+                        fields.retain(|(key, _)| !exclude.fields.iter().any(|(_, f)| f == key));
+                    }
+                }
+                StepType::GroupBy(_) | StepType::Aggregate(_) => {
+                    many = true;
+                    item = element_shape(self.source, after);
+                }
+                StepType::AddEdge(_)
+                | StepType::UpsertN(_)
+                | StepType::UpsertE(_)
+                | StepType::UpsertV(_) => {
+                    many = false;
+                    item = element_shape(self.source, after);
+                }
+                StepType::Node(graph) | StepType::Edge(graph) => {
+                    // FromN and ToN keep one node per edge; every other hop may fan out
+                    many |= !matches!(
+                        graph.step,
+                        GraphStepType::FromN
+                            | GraphStepType::ToN
+                            | GraphStepType::FromV
+                            | GraphStepType::ToV
+                    );
+                    item = element_shape(self.source, after);
+                }
+                StepType::SearchVector(_) => {
+                    many = true;
+                    item = element_shape(self.source, after);
+                }
+                // Filters, ordering, updates and reranking keep the items as they are
+                _ => {}
+            }
+        }
+
+        if many {
+            Shape::array(item)
+        } else {
+            item
+        }
+    }
+
+    /// `::{a, b: _::Out<E>}`, `::{..., a}` and closures `::|x|{...}` applied to one
+    /// element of type `owner`, currently shaped as `item`
+    fn object(
+        &self,
+        item: &Shape,
+        owner: &ElementType,
+        additions: &[FieldAddition],
+        spread: bool,
+        binding: Option<&String>,
+    ) -> Shape {
+        // A closure names the element so its fields can refer to it
+        let scope;
+        let this = match binding {
+            Some(name) => {
+                let mut values = self.values.clone();
+                values.insert(name.clone(), item.clone());
+                let mut vars = self.vars.clone();
+                vars.insert(name.clone(), owner.clone());
+                scope = Shapes {
+                    source: self.source,
+                    vars,
+                    values,
+                };
+                &scope
+            }
+            None => self,
+        };
+
+        let mut fields = match (spread, item) {
+            (true, Shape::Object(fields)) => fields.clone(),
+            _ => Vec::new(),
+        };
+        for field in additions {
+            let shape = match &field.value.value {
+                FieldValueType::Identifier(name) => {
+                    match infer::property_type(this.source, owner, name) {
+                        Some(ty) => field_shape(this.source, &ty),
+                        None => this.variable(name),
+                    }
+                }
+                FieldValueType::Empty => infer::property_type(this.source, owner, &field.key)
+                    .map(|ty| field_shape(this.source, &ty))
+                    .unwrap_or(Shape::Unknown),
+                FieldValueType::Traversal(traversal) => this.traversal(traversal, owner),
+                FieldValueType::Expression(expr) => this.expression(expr, owner),
+                FieldValueType::Fields(nested) => this.object(item, owner, nested, false, None),
+                FieldValueType::Literal(value) => literal_shape(value),
+            };
+            set(&mut fields, &field.key, shape);
+        }
+        Shape::Object(fields)
+    }
+
+    fn returned(&self, ret: &ReturnType) -> Shape {
+        match ret {
+            ReturnType::Expression(expr) => self.expression(expr, &ElementType::Unknown),
+            ReturnType::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                Shape::Object(
+                    keys.into_iter()
+                        .map(|key| (key.clone(), self.returned(&map[key])))
+                        .collect(),
+                )
+            }
+            ReturnType::Array(items) => {
+                let items: Vec<Shape> = items.iter().map(|item| self.returned(item)).collect();
+                match items.first() {
+                    Some(first) if items.iter().all(|item| item == first) => {
+                        Shape::array(first.clone())
+                    }
+                    Some(_) => Shape::Tuple(items),
+                    None => Shape::array(Shape::Unknown),
+                }
+            }
+            ReturnType::Empty => Shape::Unknown,
+        }
+    }
+}
+
+/// Key a returned value appears under: the variable it starts from, or `data`
+fn return_key(ret: &ReturnType) -> String {
+    let ReturnType::Expression(expr) = ret else {
+        return "data".to_string();
+    };
+    match &expr.expr {
+        ExpressionType::Identifier(name) => name.clone(),
+        ExpressionType::Traversal(traversal) => match &traversal.start {
+            StartNode::Identifier(name) => name.clone(),
+            _ => "data".to_string(),
+        },
+        _ => "data".to_string(),
+    }
+}
+
+/// The response body of `query`. Each value of `RETURN a, b` appears under its
+/// variable's name; a query returning a single `{...}` responds with that object.
+pub fn return_shape(source: &Source, query: &Query) -> Shape {
+    let mut shapes = Shapes {
+        source,
+        vars: infer::parameters(query),
+        values: HashMap::new(),
+    };
+    for stmt in &query.statements {
+        if let StatementType::Assignment(assignment) = &stmt.statement {
+            let shape = shapes.expression(&assignment.value, &ElementType::Unknown);
+            shapes.values.insert(assignment.variable.clone(), shape);
+        }
+        infer::bind(source, stmt, &mut shapes.vars);
+    }
+
+    if let [ret @ ReturnType::Object(_)] = &query.return_values[..] {
+        return shapes.returned(ret);
+    }
+    let mut fields = Vec::new();
+    for ret in &query.return_values {
+        set(&mut fields, &return_key(ret), shapes.returned(ret));
+    }
+    Shape::Object(fields)
+}
+
+/// Hover text for a query's response: a TypeScript type named after the query
+pub fn describe(source: &Source, query: &Query) -> String {
+    format!(
+        "**{}** responds with\n\n```typescript\ntype {}Response = {}\n```",
+        query.name,
+        query.name,
+        return_shape(source, query).typescript(0)
+    )
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnShapeParams {
+    pub text_document: TextDocumentIdentifier,
+    /// Only describe this query; defaults to every query in the project
+    pub query: Option<String>,
+}
+
+/// One entry of the `helixql/returnShape` response
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnShapeInfo {
+    pub query: String,
+    pub typescript: String,
+    pub json_schema: Json,
+}
+
+pub fn describe_queries(source: &Source, only: Option<&str>) -> Vec<ReturnShapeInfo> {
+    source
+        .queries
+        .iter()
+        .filter(|query| only.is_none_or(|name| query.name == name))
+        .map(|query| {
+            let shape = return_shape(source, query);
+            let mut schema = shape.json_schema();
+            if let Json::Object(map) = &mut schema {
+                map.insert(
+                    "$schema".to_string(),
+                    json!("https://json-schema.org/draft/2020-12/schema"),
+                );
+                map.insert(
+                    "title".to_string(),
+                    json!(format!("{}Response", query.name)),
+                );
+            }
+            ReturnShapeInfo {
+                query: query.name.clone(),
+                typescript: shape.typescript(0),
+                json_schema: schema,
+            }
+        })
+        .collect()
+}
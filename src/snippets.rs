@@ -0,0 +1,211 @@
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, InsertTextFormat};
+
+use helix_db::helixc::parser::types::Source;
+
+/// Field types offered in schema and parameter snippets
+const FIELD_TYPES: &[&str] = &[
+    "String", "Boolean", "I32", "I64", "U32", "U64", "F32", "F64", "Date", "ID",
+];
+
+/// Type names of the latest schema version, used to fill snippet choices
+#[derive(Debug, Default)]
+pub struct SchemaNames {
+    pub nodes: Vec<String>,
+    pub edges: Vec<String>,
+    pub vectors: Vec<String>,
+    pub latest_version: Option<usize>,
+}
+
+impl SchemaNames {
+    pub fn from_source(source: &Source) -> Self {
+        let latest_version = source.schema.keys().max().copied();
+        let Some(schema) = latest_version.and_then(|v| source.schema.get(&v)) else {
+            return Self::default();
+        };
+        Self {
+            nodes: schema
+                .node_schemas
+                .iter()
+                .map(|n| n.name.1.clone())
+                .collect(),
+            edges: schema
+                .edge_schemas
+                .iter()
+                .map(|e| e.name.1.clone())
+                .collect(),
+            vectors: schema
+                .vector_schemas
+                .iter()
+                .map(|v| v.name.clone())
+                .collect(),
+            latest_version,
+        }
+    }
+}
+
+/// `${n|A,B|}` when there are names to choose from, `${n:fallback}` otherwise
+pub fn choice<S: AsRef<str>>(index: usize, names: &[S], fallback: &str) -> String {
+    if names.is_empty() {
+        format!("${{{}:{}}}", index, fallback)
+    } else {
+        let names: Vec<&str> = names.iter().map(|n| n.as_ref()).collect();
+        format!("${{{}|{}|}}", index, names.join(","))
+    }
+}
+
+fn snippet(label: &str, detail: &str, body: String) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind: Some(CompletionItemKind::SNIPPET),
+        detail: Some(detail.to_string()),
+        insert_text: Some(body),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        ..Default::default()
+    }
+}
+
+/// Snippets for whole constructs: queries, schema definitions, migrations,
+/// creation and search expressions, loops and query macros
+pub fn construct_snippets(names: &SchemaNames) -> Vec<CompletionItem> {
+    let nodes = &names.nodes;
+    let edges = &names.edges;
+    let vectors = &names.vectors;
+    let (from_version, to_version) = match names.latest_version {
+        Some(v) if v > 1 => (v - 1, v),
+        Some(v) => (v, v + 1),
+        None => (1, 2),
+    };
+
+    vec![
+        snippet(
+            "QUERY",
+            "Define a query",
+            format!(
+                "QUERY ${{1:QueryName}}(${{2:id}}: ${{3:ID}}) =>\n    ${{4:result}} <- N<{}>(${{2:id}})\n    RETURN ${{4:result}}$0",
+                choice(5, nodes, "Type")
+            ),
+        ),
+        snippet(
+            "N::",
+            "Node schema",
+            format!(
+                "N::${{1:Name}} {{\n    ${{2:field}}: {}$0\n}}",
+                choice(3, FIELD_TYPES, "String")
+            ),
+        ),
+        snippet(
+            "E::",
+            "Edge schema",
+            format!(
+                "E::${{1:Name}} {{\n    From: {},\n    To: {},\n    Properties: {{\n        $0\n    }}\n}}",
+                choice(2, nodes, "From"),
+                choice(3, nodes, "To")
+            ),
+        ),
+        snippet(
+            "V::",
+            "Vector schema",
+            format!(
+                "V::${{1:Name}} {{\n    ${{2:field}}: {}$0\n}}",
+                choice(3, FIELD_TYPES, "String")
+            ),
+        ),
+        snippet(
+            "MIGRATION",
+            "Schema migration",
+            format!(
+                "MIGRATION schema::${{1:{}}} => schema::${{2:{}}} {{\n    $0\n}}",
+                from_version, to_version
+            ),
+        ),
+        snippet(
+            "AddN",
+            "Add node",
+            format!(
+                "AddN<{}>({{${{2:field}}: ${{3:value}}}})$0",
+                choice(1, nodes, "Type")
+            ),
+        ),
+        snippet(
+            "AddE",
+            "Add edge",
+            format!(
+                "AddE<{}>::From(${{2:from}})::To(${{3:to}})$0",
+                choice(1, edges, "Type")
+            ),
+        ),
+        snippet(
+            "AddV",
+            "Add vector",
+            format!(
+                "AddV<{}>(${{2:vector}}, {{${{3:field}}: ${{4:value}}}})$0",
+                choice(1, vectors, "Type")
+            ),
+        ),
+        snippet(
+            "SearchV",
+            "Search vectors",
+            format!(
+                "SearchV<{}>(Embed(${{2:text}}), ${{3:10}})$0",
+                choice(1, vectors, "Type")
+            ),
+        ),
+        snippet(
+            "FOR",
+            "Loop iteration",
+            "FOR ${1:item} IN ${2:items} {\n    $0\n}".to_string(),
+        ),
+        snippet(
+            "#[model]",
+            "Embedding model for the query",
+            "#[model(\"${1:model}\")]$0".to_string(),
+        ),
+        snippet(
+            "#[mcp]",
+            "Expose the query as an MCP tool",
+            "#[mcp]$0".to_string(),
+        ),
+    ]
+}
+
+/// Snippets for traversal steps that take a type argument or a condition
+pub fn step_snippets(names: &SchemaNames) -> Vec<CompletionItem> {
+    let edges = &names.edges;
+    let mut items: Vec<CompletionItem> = [
+        ("Out", "Traverse outgoing edges"),
+        ("In", "Traverse incoming edges"),
+        ("OutE", "Get outgoing edge objects"),
+        ("InE", "Get incoming edge objects"),
+    ]
+    .into_iter()
+    .map(|(step, detail)| {
+        snippet(
+            step,
+            detail,
+            format!("{}<{}>$0", step, choice(1, edges, "Edge")),
+        )
+    })
+    .collect();
+
+    items.push(snippet(
+        "WHERE",
+        "Filter by condition",
+        "WHERE(_::{${1:field}}::${2|EQ,NEQ,GT,GTE,LT,LTE|}(${3:value}))$0".to_string(),
+    ));
+    items.push(snippet(
+        "RANGE",
+        "Select range",
+        "RANGE(${1:0}, ${2:10})$0".to_string(),
+    ));
+    items.push(snippet(
+        "UPDATE",
+        "Update properties",
+        "UPDATE({${1:field}: ${2:value}})$0".to_string(),
+    ));
+    items.push(snippet(
+        "ORDER",
+        "Order results",
+        "ORDER<${1|Asc,Desc|}>(_::{${2:field}})$0".to_string(),
+    ));
+    items
+}
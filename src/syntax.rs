@@ -0,0 +1,217 @@
+use pest::iterators::{Pair, Pairs};
+use pest::Parser;
+use pest_derive::Parser;
+use tower_lsp::lsp_types::{Position, Range};
+
+/// The HelixQL grammar. The helix-db parser only keeps coarse locations, so
+/// features that need exact token spans or partial input parse with this instead.
+#[derive(Parser)]
+#[grammar = "grammar.pest"]
+pub struct HqlParser;
+
+/// Lines starting one of these at column 0, outside any braces, begin a top-level item
+pub const ITEM_STARTS: &[&str] = &["QUERY", "#[", "MIGRATION", "schema::", "N::", "E::", "V::"];
+
+/// Converts between byte offsets and LSP positions (UTF-16 columns)
+pub struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self { text, line_starts }
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+        let mut units = 0;
+        for (byte, c) in self.text[start..].char_indices() {
+            if units >= position.character as usize || c == '\n' {
+                return start + byte;
+            }
+            units += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let character = self.text[start..offset]
+            .chars()
+            .map(|c| c.len_utf16() as u32)
+            .sum();
+        Position::new(line as u32, character)
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> Range {
+        Range::new(self.position(start), self.position(end))
+    }
+}
+
+/// Byte offsets where the top-level items of `text` begin. A `#[...]` macro line
+/// starts the item of the query below it.
+pub fn item_starts(text: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    let mut depth = 0i32;
+    let mut offset = 0;
+    let mut after_macro = false;
+    for line in text.split_inclusive('\n') {
+        if depth <= 0 && ITEM_STARTS.iter().any(|s| line.starts_with(s)) {
+            if offset > 0 && !after_macro {
+                starts.push(offset);
+            }
+            after_macro = line.starts_with("#[");
+        }
+        let code = line.split("//").next().unwrap_or_default();
+        depth += code.matches('{').count() as i32 - code.matches('}').count() as i32;
+        offset += line.len();
+    }
+    starts
+}
+
+/// Parse each top-level item on its own, so a syntax error only loses the item it is in.
+/// Yields the item's byte offset in `text` with its parse tree; spans are relative to it.
+pub fn parse_items(text: &str) -> Vec<(usize, Pairs<'_, Rule>)> {
+    let mut starts = item_starts(text);
+    starts.push(text.len());
+    starts
+        .windows(2)
+        .filter_map(|w| {
+            let pairs = HqlParser::parse(Rule::source, &text[w[0]..w[1]]).ok()?;
+            Some((w[0], pairs))
+        })
+        .collect()
+}
+
+/// Byte range of `pair` in the file, given its item's offset. Spans can run into
+/// the whitespace after a rule, which is left out.
+pub fn span(pair: &Pair<Rule>, base: usize) -> (usize, usize) {
+    let span = pair.as_span();
+    let start = base + span.start();
+    (start, start + span.as_str().trim_end().len())
+}
+
+/// The call the cursor is inside of: the name before the unclosed `(` and the
+/// index of the argument being written. Generic arguments are skipped, so
+/// `SearchV<Doc>(` gives `SearchV`, and macros are named as in `#[model]`.
+pub fn call_at(text: &str, position: Position) -> Option<(String, u32)> {
+    let before = &text[..LineIndex::new(text).offset(position)];
+    let before = &before[item_starts(before).last().copied().unwrap_or_default()..];
+
+    let mut depth = 0;
+    let mut argument = 0;
+    for (i, c) in before.char_indices().rev() {
+        match c {
+            ')' | ']' | '}' => depth += 1,
+            '[' | '{' if depth > 0 => depth -= 1,
+            // The cursor is inside an object or array argument
+            '[' | '{' => argument = 0,
+            '(' if depth > 0 => depth -= 1,
+            ',' if depth == 0 => argument += 1,
+            '(' => {
+                let mut callee = before[..i].trim_end();
+                if let Some(open) = callee.strip_suffix('>').and_then(|c| c.rfind('<')) {
+                    callee = callee[..open].trim_end();
+                }
+                let name_start = callee
+                    .trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_')
+                    .len();
+                let name = &callee[name_start..];
+                if name.is_empty() {
+                    return None;
+                }
+                if callee[..name_start].ends_with("#[") {
+                    return Some((format!("#[{}]", name), argument));
+                }
+                return Some((name.to_string(), argument));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether the name at the cursor is a math function rather than what else it can
+/// be: `E` also starts edge traversals and `COUNT` is also a step. Inside the
+/// arguments of a call, the innermost enclosing construct decides. `None` when the
+/// item at the cursor does not parse.
+pub fn math_function_at(text: &str, position: Position) -> Option<bool> {
+    let cursor = LineIndex::new(text).offset(position);
+    for (base, pairs) in parse_items(text) {
+        let mut rules: Vec<Rule> = Vec::new();
+        let mut level: Vec<Pair<Rule>> = pairs.collect();
+        while let Some(pair) = level.into_iter().find(|pair| {
+            let (start, end) = span(pair, base);
+            start <= cursor && cursor <= end
+        }) {
+            rules.push(pair.as_rule());
+            level = pair.into_inner().collect();
+        }
+        if rules.is_empty() {
+            continue;
+        }
+        let innermost = rules.iter().rev().find(|rule| {
+            matches!(
+                rule,
+                Rule::math_function_call | Rule::start_edge | Rule::count
+            )
+        });
+        return Some(innermost == Some(&Rule::math_function_call));
+    }
+    None
+}
+
+/// The edge type connected by the edge step the cursor is in: the type argument
+/// of `AddE` and `ShortestPath*`, or the `E<T>` traversal an `UpsertE` applies to
+pub fn edge_type_at(text: &str, position: Position) -> Option<String> {
+    let cursor = LineIndex::new(text).offset(position);
+    for (base, pairs) in parse_items(text) {
+        let mut stack: Vec<Pair<Rule>> = Vec::new();
+        let mut level: Vec<Pair<Rule>> = pairs.collect();
+        while let Some(pair) = level.into_iter().find(|pair| {
+            let (start, end) = span(pair, base);
+            start <= cursor && cursor <= end
+        }) {
+            level = pair.clone().into_inner().collect();
+            stack.push(pair);
+        }
+
+        let Some(i) = stack.iter().rposition(|pair| {
+            matches!(
+                pair.as_rule(),
+                Rule::AddE
+                    | Rule::upsert_e
+                    | Rule::shortest_path
+                    | Rule::shortest_path_bfs
+                    | Rule::shortest_path_dijkstras
+                    | Rule::shortest_path_astar
+            )
+        }) else {
+            continue;
+        };
+        let typed = if stack[i].as_rule() == Rule::upsert_e {
+            stack[..i]
+                .iter()
+                .rev()
+                .find(|pair| pair.as_rule() == Rule::traversal)?
+                .clone()
+                .into_inner()
+                .next()
+                .filter(|pair| pair.as_rule() == Rule::start_edge)?
+        } else {
+            stack[i].clone()
+        };
+        return typed
+            .into_inner()
+            .flatten()
+            .find(|pair| pair.as_rule() == Rule::identifier_upper)
+            .map(|pair| pair.as_str().to_string());
+    }
+    None
+}
@@ -0,0 +1,323 @@
+use helix_db::helixc::parser::types::{
+    BooleanOpType, EdgeConnection, EvaluatesToNumber, EvaluatesToNumberType, EvaluatesToString,
+    Expression, ExpressionType, FieldValue, FieldValueType, GraphStepType, IdType, Loc,
+    MMRDistance, Query, ReturnType, SearchVector, StartNode, Statement, StatementType, Step,
+    StepType, Traversal, ValueType, VectorData,
+};
+
+/// Callbacks for a depth-first walk over a query's statements and expressions.
+///
+/// Every hook is called before the walk descends into the node's children, except
+/// `statement_end`, which is called after.
+pub trait Visitor {
+    fn statement(&mut self, _stmt: &Statement) {}
+    /// Called once the statement's value and any loop body have been walked
+    fn statement_end(&mut self, _stmt: &Statement) {}
+    fn expression(&mut self, _expr: &Expression) {}
+    fn traversal(&mut self, _traversal: &Traversal) {}
+    fn step(&mut self, _step: &Step) {}
+    /// A bare identifier that may refer to a parameter or variable. `loc` is the
+    /// closest location the parser records for it, which may cover more than the name.
+    fn identifier(&mut self, _name: &str, _loc: &Loc) {}
+}
+
+/// Walk the statements and return values of a query
+pub fn walk_query<V: Visitor>(visitor: &mut V, query: &Query) {
+    walk_statements(visitor, &query.statements);
+    for ret in &query.return_values {
+        walk_return(visitor, ret);
+    }
+}
+
+pub fn walk_statements<V: Visitor>(visitor: &mut V, statements: &[Statement]) {
+    for stmt in statements {
+        visitor.statement(stmt);
+        match &stmt.statement {
+            StatementType::Assignment(assignment) => walk_expression(visitor, &assignment.value),
+            StatementType::Expression(expr) => walk_expression(visitor, expr),
+            StatementType::Drop(expr) => walk_expression(visitor, expr),
+            StatementType::ForLoop(for_loop) => {
+                visitor.identifier(&for_loop.in_variable.1, &for_loop.in_variable.0);
+                walk_statements(visitor, &for_loop.statements)
+            }
+        }
+        visitor.statement_end(stmt);
+    }
+}
+
+pub fn walk_return<V: Visitor>(visitor: &mut V, ret: &ReturnType) {
+    match ret {
+        ReturnType::Array(items) => {
+            for item in items {
+                walk_return(visitor, item);
+            }
+        }
+        ReturnType::Object(fields) => {
+            for value in fields.values() {
+                walk_return(visitor, value);
+            }
+        }
+        ReturnType::Expression(expr) => walk_expression(visitor, expr),
+        ReturnType::Empty => {}
+    }
+}
+
+pub fn walk_expression<V: Visitor>(visitor: &mut V, expr: &Expression) {
+    visitor.expression(expr);
+    match &expr.expr {
+        ExpressionType::Traversal(traversal) => walk_traversal(visitor, traversal),
+        ExpressionType::Identifier(name) => visitor.identifier(name, &expr.loc),
+        ExpressionType::AddNode(add) => {
+            for value in add.fields.iter().flat_map(|f| f.values()) {
+                walk_value(visitor, value);
+            }
+        }
+        ExpressionType::AddEdge(add) => {
+            walk_connection(visitor, &add.connection);
+            for value in add.fields.iter().flat_map(|f| f.values()) {
+                walk_value(visitor, value);
+            }
+        }
+        ExpressionType::AddVector(add) => {
+            if let Some(data) = &add.data {
+                walk_vector_data(visitor, data, &add.loc);
+            }
+            for value in add.fields.iter().flat_map(|f| f.values()) {
+                walk_value(visitor, value);
+            }
+        }
+        ExpressionType::BatchAddVector(add) => {
+            if let Some(name) = &add.vec_identifier {
+                visitor.identifier(name, &add.loc);
+            }
+        }
+        ExpressionType::BM25Search(bm25) => {
+            if let Some(data) = &bm25.data {
+                walk_value(visitor, data);
+            }
+            if let Some(k) = &bm25.k {
+                walk_number(visitor, k);
+            }
+        }
+        ExpressionType::ArrayLiteral(items)
+        | ExpressionType::And(items)
+        | ExpressionType::Or(items) => {
+            for item in items {
+                walk_expression(visitor, item);
+            }
+        }
+        ExpressionType::Exists(exists) => walk_expression(visitor, &exists.expr),
+        ExpressionType::Not(inner) => walk_expression(visitor, inner),
+        ExpressionType::SearchVector(sv) => walk_search_vector(visitor, sv),
+        ExpressionType::MathFunctionCall(call) => {
+            for arg in &call.args {
+                walk_expression(visitor, arg);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn walk_traversal<V: Visitor>(visitor: &mut V, traversal: &Traversal) {
+    visitor.traversal(traversal);
+    match &traversal.start {
+        StartNode::Node { ids, .. }
+        | StartNode::Edge { ids, .. }
+        | StartNode::Vector { ids, .. } => {
+            for id in ids.iter().flatten() {
+                walk_id(visitor, id);
+            }
+        }
+        StartNode::SearchVector(sv) => walk_search_vector(visitor, sv),
+        StartNode::Identifier(name) => visitor.identifier(name, &traversal.loc),
+        StartNode::Anonymous => {}
+    }
+    for step in &traversal.steps {
+        walk_step(visitor, step);
+    }
+}
+
+pub fn walk_field_value<V: Visitor>(visitor: &mut V, value: &FieldValue) {
+    match &value.value {
+        FieldValueType::Traversal(traversal) => walk_traversal(visitor, traversal),
+        FieldValueType::Expression(expr) => walk_expression(visitor, expr),
+        FieldValueType::Fields(fields) => {
+            for field in fields {
+                walk_field_value(visitor, &field.value);
+            }
+        }
+        FieldValueType::Identifier(name) => visitor.identifier(name, &value.loc),
+        _ => {}
+    }
+}
+
+pub fn walk_value<V: Visitor>(visitor: &mut V, value: &ValueType) {
+    match value {
+        ValueType::Identifier { value, loc } => visitor.identifier(value, loc),
+        ValueType::Object { fields, .. } => {
+            for field in fields.values() {
+                walk_value(visitor, field);
+            }
+        }
+        ValueType::Literal { .. } => {}
+    }
+}
+
+pub fn walk_id<V: Visitor>(visitor: &mut V, id: &IdType) {
+    match id {
+        IdType::Identifier { value, loc } => visitor.identifier(value, loc),
+        IdType::ByIndex { value, .. } => walk_value(visitor, value),
+        IdType::Literal { .. } => {}
+    }
+}
+
+fn walk_connection<V: Visitor>(visitor: &mut V, connection: &EdgeConnection) {
+    for id in connection.from_id.iter().chain(connection.to_id.iter()) {
+        walk_id(visitor, id);
+    }
+}
+
+fn walk_vector_data<V: Visitor>(visitor: &mut V, data: &VectorData, loc: &Loc) {
+    match data {
+        VectorData::Identifier(name) => visitor.identifier(name, loc),
+        VectorData::Embed(embed) => {
+            if let EvaluatesToString::Identifier(name) = &embed.value {
+                visitor.identifier(name, &embed.loc);
+            }
+        }
+        VectorData::Vector(_) => {}
+    }
+}
+
+fn walk_number<V: Visitor>(visitor: &mut V, number: &EvaluatesToNumber) {
+    if let EvaluatesToNumberType::Identifier(name) = &number.value {
+        visitor.identifier(name, &number.loc);
+    }
+}
+
+fn walk_search_vector<V: Visitor>(visitor: &mut V, sv: &SearchVector) {
+    if let Some(data) = &sv.data {
+        walk_vector_data(visitor, data, &sv.loc);
+    }
+    if let Some(k) = &sv.k {
+        walk_number(visitor, k);
+    }
+    if let Some(pre_filter) = &sv.pre_filter {
+        walk_expression(visitor, pre_filter);
+    }
+}
+
+pub fn walk_step<V: Visitor>(visitor: &mut V, step: &Step) {
+    visitor.step(step);
+    match &step.step {
+        StepType::Node(graph_step) | StepType::Edge(graph_step) => match &graph_step.step {
+            GraphStepType::ShortestPath(sp) => {
+                for id in sp.from.iter().chain(sp.to.iter()) {
+                    walk_id(visitor, id);
+                }
+            }
+            GraphStepType::ShortestPathBFS(sp) => {
+                for id in sp.from.iter().chain(sp.to.iter()) {
+                    walk_id(visitor, id);
+                }
+            }
+            GraphStepType::ShortestPathDijkstras(sp) => {
+                for id in sp.from.iter().chain(sp.to.iter()) {
+                    walk_id(visitor, id);
+                }
+                if let Some(inner) = &sp.inner_traversal {
+                    walk_traversal(visitor, inner);
+                }
+                if let Some(weight) = &sp.weight_expression {
+                    walk_expression(visitor, weight);
+                }
+            }
+            GraphStepType::ShortestPathAStar(sp) => {
+                for id in sp.from.iter().chain(sp.to.iter()) {
+                    walk_id(visitor, id);
+                }
+                if let Some(weight) = &sp.weight_expression {
+                    walk_expression(visitor, weight);
+                }
+            }
+            GraphStepType::SearchVector(sv) => walk_search_vector(visitor, sv),
+            _ => {}
+        },
+        StepType::Where(expr) => walk_expression(visitor, expr),
+        StepType::BooleanOperation(op) => match &op.op {
+            BooleanOpType::And(exprs) | BooleanOpType::Or(exprs) => {
+                for expr in exprs {
+                    walk_expression(visitor, expr);
+                }
+            }
+            BooleanOpType::GreaterThan(expr)
+            | BooleanOpType::GreaterThanOrEqual(expr)
+            | BooleanOpType::LessThan(expr)
+            | BooleanOpType::LessThanOrEqual(expr)
+            | BooleanOpType::Equal(expr)
+            | BooleanOpType::NotEqual(expr)
+            | BooleanOpType::Contains(expr)
+            | BooleanOpType::IsIn(expr) => walk_expression(visitor, expr),
+        },
+        StepType::Update(update) => {
+            for field in &update.fields {
+                walk_field_value(visitor, &field.value);
+            }
+        }
+        StepType::UpsertN(upsert) => {
+            for field in &upsert.fields {
+                walk_field_value(visitor, &field.value);
+            }
+        }
+        StepType::UpsertE(upsert) => {
+            walk_connection(visitor, &upsert.connection);
+            for field in &upsert.fields {
+                walk_field_value(visitor, &field.value);
+            }
+        }
+        StepType::UpsertV(upsert) => {
+            if let Some(data) = &upsert.data {
+                walk_vector_data(visitor, data, &upsert.loc);
+            }
+            for field in &upsert.fields {
+                walk_field_value(visitor, &field.value);
+            }
+        }
+        StepType::Object(object) => {
+            for field in &object.fields {
+                walk_field_value(visitor, &field.value);
+            }
+        }
+        StepType::Closure(closure) => {
+            for field in &closure.object.fields {
+                walk_field_value(visitor, &field.value);
+            }
+        }
+        StepType::Range((start, end)) => {
+            walk_expression(visitor, start);
+            walk_expression(visitor, end);
+        }
+        StepType::OrderBy(order_by) => walk_expression(visitor, &order_by.expression),
+        StepType::AddEdge(add) => {
+            walk_connection(visitor, &add.connection);
+            for value in add.fields.iter().flat_map(|f| f.values()) {
+                walk_value(visitor, value);
+            }
+        }
+        StepType::SearchVector(sv) => walk_search_vector(visitor, sv),
+        StepType::RerankRRF(rrf) => {
+            if let Some(k) = &rrf.k {
+                walk_expression(visitor, k);
+            }
+        }
+        StepType::RerankMMR(mmr) => {
+            if let Some(lambda) = &mmr.lambda {
+                walk_expression(visitor, lambda);
+            }
+            if let Some(MMRDistance::Identifier(name)) = &mmr.distance {
+                visitor.identifier(name, &mmr.loc);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use tower_lsp::lsp_types::InitializeParams;
+
+/// Directories never searched for Helix projects
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target"];
+
+fn is_helix_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext == "hx" || ext == "hql")
+        .unwrap_or(false)
+}
+
+/// All .hx/.hql files directly inside `dir`; together they form one project
+pub fn project_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if is_helix_file(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Every directory below `root` that contains Helix files, skipping hidden and build directories
+pub fn find_projects(root: &Path) -> Vec<PathBuf> {
+    let mut projects = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut has_files = false;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                    pending.push(path);
+                }
+            } else if is_helix_file(&path) {
+                has_files = true;
+            }
+        }
+        if has_files {
+            projects.push(dir);
+        }
+    }
+    projects.sort();
+    projects
+}
+
+/// Workspace roots the client opened, preferring workspace folders over the legacy root URI
+pub fn roots_from_params(params: &InitializeParams) -> Vec<PathBuf> {
+    if let Some(folders) = &params.workspace_folders {
+        return folders
+            .iter()
+            .filter_map(|folder| folder.uri.to_file_path().ok())
+            .collect();
+    }
+    params
+        .root_uri
+        .as_ref()
+        .and_then(|uri| uri.to_file_path().ok())
+        .into_iter()
+        .collect()
+}
+
+/// Key of a project's analysis inputs: the name and content of every file and the
+/// options it is checked with. Equal keys give equal diagnostics, so an analysis
+/// can be reused as long as its key still matches.
+pub fn input_key<'a>(files: impl IntoIterator<Item = (&'a str, &'a str)>, options: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for (name, content) in files {
+        name.hash(&mut hasher);
+        content.hash(&mut hasher);
+    }
+    options.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Result ID for a file's diagnostics, from the input keys of the projects analyzing
+/// it: while none of them change, a client that already has the report gets `Unchanged`
+pub fn result_id(keys: &[u64]) -> String {
+    let mut hasher = DefaultHasher::new();
+    keys.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}